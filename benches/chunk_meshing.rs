@@ -0,0 +1,23 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use glam::Vec3;
+use minecraft_clone::rendering::build_chunk_mesh;
+use minecraft_clone::world::{ChunkCoordinate, World};
+
+/// Mesh a generated chunk surrounded by loaded neighbors, so border faces are
+/// meshed the same way as in game
+fn chunk_meshing(c: &mut Criterion) {
+    let mut world = World::with_seed(12345);
+    world.set_render_distance(2);
+    world.set_chunk_budget(usize::MAX);
+    world.load_chunks_around(Vec3::new(8.0, 100.0, 8.0));
+
+    let coord = ChunkCoordinate::new(0, 0);
+    let chunk = world.get_chunk(coord).expect("spawn chunk is loaded");
+
+    c.bench_function("build_chunk_mesh", |b| {
+        b.iter(|| build_chunk_mesh(black_box(chunk), &world))
+    });
+}
+
+criterion_group!(benches, chunk_meshing);
+criterion_main!(benches);
@@ -0,0 +1,68 @@
+/// Seconds between autosaves by default
+pub const DEFAULT_AUTOSAVE_INTERVAL: f32 = 300.0;
+
+/// Dirty chunks written per frame while an autosave is running, so saving a large
+/// world spreads over several frames instead of stalling one
+pub const AUTOSAVE_CHUNKS_PER_FRAME: usize = 4;
+
+/// Counts down to the next autosave
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Autosave {
+    /// Seconds between saves, or `None` to turn autosaving off
+    interval: Option<f32>,
+    elapsed: f32,
+}
+
+impl Autosave {
+    pub fn new(interval: Option<f32>) -> Self {
+        Self {
+            interval: interval.map(|seconds| seconds.max(1.0)),
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn interval(&self) -> Option<f32> {
+        self.interval
+    }
+
+    /// Change the interval, restarting the countdown. `None` disables autosave.
+    pub fn set_interval(&mut self, interval: Option<f32>) {
+        *self = Self::new(interval);
+    }
+
+    /// Advance the countdown. Returns true once each time an autosave is due.
+    pub fn tick(&mut self, delta_time: f32) -> bool {
+        let Some(interval) = self.interval else {
+            return false;
+        };
+        self.elapsed += delta_time;
+        if self.elapsed < interval {
+            return false;
+        }
+        // Don't try to catch up on saves missed during a long stall
+        self.elapsed = 0.0;
+        true
+    }
+}
+
+impl Default for Autosave {
+    fn default() -> Self {
+        Self::new(Some(DEFAULT_AUTOSAVE_INTERVAL))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_interval_of_ticks_triggers_exactly_one_autosave() {
+        let mut autosave = Autosave::new(Some(30.0));
+        let tick = 1.0 / 20.0;
+
+        let saves = (0..(30.5 / tick) as usize).filter(|_| autosave.tick(tick)).count();
+
+        assert_eq!(saves, 1);
+        assert!(!Autosave::new(None).tick(1000.0));
+    }
+}
@@ -0,0 +1,35 @@
+use super::autosave::DEFAULT_AUTOSAVE_INTERVAL;
+use super::time::DEFAULT_TICK_RATE;
+use crate::rendering::{GraphicsSettings, DEFAULT_AMBIENT_LIGHT, DEFAULT_CLEAR_COLOR};
+
+/// Settings fixed when the engine starts
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineConfig {
+    /// Simulation ticks per second. The world and player step at this rate while
+    /// frames render as fast as allowed, interpolating between ticks.
+    pub tick_rate: u32,
+    /// Which GPU the renderer asks for
+    pub graphics: GraphicsSettings,
+    /// Color behind everything the renderer draws
+    pub clear_color: wgpu::Color,
+    /// Minimum brightness of an unlit face, clamped to 0-1. 0 lets caves go fully
+    /// black.
+    pub ambient_light: f32,
+    /// Seconds between autosaves, or `None` to only save on exit
+    pub autosave_interval: Option<f32>,
+    /// In-game frame rate cap, or `None` to leave it uncapped
+    pub max_fps: Option<u32>,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            tick_rate: DEFAULT_TICK_RATE,
+            graphics: GraphicsSettings::default(),
+            clear_color: DEFAULT_CLEAR_COLOR,
+            ambient_light: DEFAULT_AMBIENT_LIGHT,
+            autosave_interval: Some(DEFAULT_AUTOSAVE_INTERVAL),
+            max_fps: None,
+        }
+    }
+}
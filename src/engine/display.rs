@@ -0,0 +1,45 @@
+use winit::dpi::PhysicalSize;
+
+/// Remembers the windowed size while the window is fullscreen, so leaving
+/// fullscreen puts the window back the way it was
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FullscreenState {
+    windowed_size: Option<PhysicalSize<u32>>,
+}
+
+impl FullscreenState {
+    pub fn is_fullscreen(&self) -> bool {
+        self.windowed_size.is_some()
+    }
+
+    /// Switch between windowed and fullscreen. `current_size` is the window's size
+    /// right now. Returns the size to restore when going back to windowed, or `None`
+    /// when entering fullscreen.
+    pub fn toggle(&mut self, current_size: PhysicalSize<u32>) -> Option<PhysicalSize<u32>> {
+        match self.windowed_size.take() {
+            Some(size) => Some(size),
+            None => {
+                self.windowed_size = Some(current_size);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windowed_size_is_restored_after_a_toggle_cycle() {
+        let mut state = FullscreenState::default();
+        let windowed = PhysicalSize::new(1280, 720);
+
+        assert_eq!(state.toggle(windowed), None);
+        assert!(state.is_fullscreen());
+
+        // The fullscreen size must not overwrite the one to go back to
+        assert_eq!(state.toggle(PhysicalSize::new(2560, 1440)), Some(windowed));
+        assert!(!state.is_fullscreen());
+    }
+}
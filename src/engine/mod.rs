@@ -1,47 +1,89 @@
 use anyhow::Result;
-use log::{info, warn};
+use log::{error, info, warn};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use winit::{
     event::{Event, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
-    window::{Window, WindowBuilder},
+    window::{Fullscreen, Window, WindowBuilder},
 };
 
+mod autosave;
+mod config;
+mod display;
 mod state;
 mod time;
 
+pub use autosave::{Autosave, AUTOSAVE_CHUNKS_PER_FRAME, DEFAULT_AUTOSAVE_INTERVAL};
+pub use config::EngineConfig;
+pub use display::FullscreenState;
 pub use state::EngineState;
-pub use time::TimeManager;
+pub use time::{TimeManager, DEFAULT_TICK_RATE};
 
 use crate::rendering::Renderer;
 use crate::input::InputManager;
-use crate::world::World;
+use crate::world::{ChunkCoordinate, World};
 use crate::game::GameManager;
 use crate::audio::AudioManager;
 use crate::ui::UIManager;
 
+/// How the event loop reacts to a frame that failed to render
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SurfaceRecovery {
+    /// The surface needs configuring again, e.g. after the display changed;
+    /// the next frame will draw normally even if the size is unchanged
+    Reconfigure,
+    /// Nothing sensible can be drawn any more; keep the player's world
+    SaveAndExit,
+    /// Drop this frame and try again with the next one
+    Skip,
+}
+
+impl SurfaceRecovery {
+    fn for_error(error: &anyhow::Error) -> Self {
+        match error.downcast_ref::<wgpu::SurfaceError>() {
+            Some(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => Self::Reconfigure,
+            Some(wgpu::SurfaceError::OutOfMemory) => Self::SaveAndExit,
+            _ => Self::Skip,
+        }
+    }
+}
+
 pub struct Engine {
-    pub window: Window,
+    pub window: Arc<Window>,
     pub state: EngineState,
     pub time_manager: TimeManager,
+    pub fullscreen: FullscreenState,
+    pub autosave: Autosave,
 }
 
 impl Engine {
     pub async fn new() -> Result<Self> {
+        Self::with_config(EngineConfig::default()).await
+    }
+
+    pub async fn with_config(config: EngineConfig) -> Result<Self> {
         let event_loop = EventLoop::new()?;
-        let window = WindowBuilder::new()
-            .with_title("Minecraft Clone")
-            .with_inner_size(winit::dpi::LogicalSize::new(1280, 720))
-            .build(&event_loop)?;
+        let window = Arc::new(
+            WindowBuilder::new()
+                .with_title("Minecraft Clone")
+                .with_inner_size(winit::dpi::LogicalSize::new(1280, 720))
+                .build(&event_loop)?,
+        );
 
         // Create state asynchronously
-        let state = EngineState::new(&window).await?;
-        let time_manager = TimeManager::new();
+        let mut state = EngineState::with_graphics(window.clone(), config.graphics).await?;
+        state.renderer.set_clear_color(config.clear_color);
+        state.renderer.set_ambient_light(config.ambient_light);
+        let mut time_manager = TimeManager::with_tick_rate(config.tick_rate);
+        time_manager.set_frame_limit(config.max_fps);
 
         Ok(Self {
             window,
             state,
             time_manager,
+            fullscreen: FullscreenState::default(),
+            autosave: Autosave::new(config.autosave_interval),
         })
     }
 
@@ -60,7 +102,12 @@ impl Engine {
                         self.state.input_manager.handle_event(event);
                         
                         match event {
-                            WindowEvent::CloseRequested => target.exit(),
+                            WindowEvent::CloseRequested => {
+                                if let Err(e) = self.state.save() {
+                                    warn!("Failed to save world: {}", e);
+                                }
+                                target.exit();
+                            }
                             WindowEvent::Resized(physical_size) => {
                                 if let Err(e) = self.state.renderer.resize(*physical_size) {
                                     eprintln!("Resize error: {}", e);
@@ -70,7 +117,19 @@ impl Engine {
                                 self.update();
                                 
                                 if let Err(e) = self.render() {
-                                    eprintln!("Render error: {}", e);
+                                    match SurfaceRecovery::for_error(&e) {
+                                        SurfaceRecovery::Reconfigure => {
+                                            self.state.renderer.reconfigure();
+                                        }
+                                        SurfaceRecovery::SaveAndExit => {
+                                            error!("Render error: {}", e);
+                                            if let Err(e) = self.state.save() {
+                                                warn!("Failed to save world: {}", e);
+                                            }
+                                            target.exit();
+                                        }
+                                        SurfaceRecovery::Skip => eprintln!("Render error: {}", e),
+                                    }
                                 }
                             }
                             _ => {}
@@ -78,7 +137,17 @@ impl Engine {
                     }
                 }
                 Event::AboutToWait => {
-                    self.window.request_redraw();
+                    // Sleep until the next frame is due when the frame rate is capped
+                    let in_menu = self.state.game_manager.is_paused() || self.state.game_manager.is_inventory_open();
+                    match self.time_manager.next_frame_at(in_menu) {
+                        Some(deadline) if Instant::now() < deadline => {
+                            target.set_control_flow(ControlFlow::WaitUntil(deadline));
+                        }
+                        _ => {
+                            target.set_control_flow(ControlFlow::Poll);
+                            self.window.request_redraw();
+                        }
+                    }
                 }
                 _ => {}
             }
@@ -91,15 +160,103 @@ impl Engine {
         // Update time
         self.time_manager.update();
         let delta_time = self.time_manager.delta_time();
-        
-        // Update game systems
+
+        // Input steers the player and the renderer's camera once per frame. Clear the
+        // just-pressed states afterwards so each press is seen exactly once
+        self.state.game_manager.handle_input(
+            &self.state.input_manager,
+            self.state.renderer.camera_mut(),
+            &mut self.state.world,
+            delta_time,
+        );
+        if self.state.input_manager.toggle_fullscreen() {
+            self.toggle_fullscreen();
+        }
+        if self.state.game_manager.is_debug_mode() && self.state.input_manager.regenerate_chunk() {
+            self.regenerate_current_chunk();
+        }
         self.state.input_manager.update();
-        self.state.game_manager.update(delta_time);
-        self.state.world.update(delta_time);
+
+        // Update game systems. The world and player advance in fixed ticks so the
+        // simulation doesn't depend on the frame rate
+        let tick = self.time_manager.fixed_timestep();
+        while self.time_manager.should_fixed_update() {
+            self.state.game_manager.update(&self.state.world, tick);
+            let player_box = self.state.game_manager.player().aabb();
+            self.state.world.update_pressure_plates(&[player_box]);
+            self.state.game_manager.collect_items(&mut self.state.world);
+            self.state.world.update(tick);
+        }
+        // Frames between ticks draw the player part way towards the next one
+        self.state.game_manager.sync_camera(self.state.renderer.camera_mut(), self.time_manager.interpolation_factor());
+
+        // Feed world events back into gameplay and audio
+        for explosion in self.state.world.take_explosions() {
+            self.state.game_manager.apply_explosion(&explosion);
+        }
+        for sound in self.state.world.take_sounds() {
+            self.state.audio_manager.play_sound(sound);
+        }
+        for change in self.state.world.take_block_changes() {
+            self.state.world.update_block_lighting(&change);
+            self.state.renderer.handle_block_change(&change);
+        }
+        for coord in self.state.world.take_remesh_chunks() {
+            self.state.renderer.mark_chunk_dirty(coord);
+        }
+
+        self.update_autosave(delta_time);
+    }
+
+    /// Start an autosave when one is due, and write the next batch of a running one
+    fn update_autosave(&mut self, delta_time: f32) {
+        if self.autosave.tick(delta_time) {
+            self.state.world.begin_autosave();
+        }
+        if !self.state.world.is_autosaving() {
+            return;
+        }
+
+        let game_manager = &self.state.game_manager;
+        let result = self.state.world.continue_autosave(
+            AUTOSAVE_CHUNKS_PER_FRAME,
+            game_manager.game_mode(),
+            game_manager.player(),
+        );
+        // The world has already dropped the running autosave; the unsaved chunks
+        // stay dirty and are retried when the next one is due
+        if let Err(e) = result {
+            warn!("Autosave failed, retrying at the next interval: {}", e);
+        }
+    }
+
+    /// Switch between windowed and borderless fullscreen. The resulting resize event
+    /// reconfigures the surface and camera.
+    fn toggle_fullscreen(&mut self) {
+        match self.fullscreen.toggle(self.window.inner_size()) {
+            None => self.window.set_fullscreen(Some(Fullscreen::Borderless(None))),
+            Some(windowed_size) => {
+                self.window.set_fullscreen(None);
+                let _ = self.window.request_inner_size(windowed_size);
+            }
+        }
+    }
+
+    /// Debug command: regenerate the chunk the player stands in and remesh it
+    fn regenerate_current_chunk(&mut self) {
+        let position = self.state.game_manager.player().position();
+        let coord = ChunkCoordinate::containing(position.x.floor() as i32, position.z.floor() as i32);
+        warn!("Regenerating chunk {:?}; player edits in it are lost", coord);
+        match self.state.world.regenerate_chunk(coord) {
+            Ok(true) => self.state.renderer.remesh_chunk(coord),
+            Ok(false) => {}
+            Err(e) => warn!("Failed to regenerate chunk {:?}: {}", coord, e),
+        }
     }
 
     fn render(&mut self) -> Result<()> {
-        // Get camera reference first to avoid borrow checker issues
+        // The renderer owns the live camera; render from a snapshot of it so the
+        // renderer can be borrowed mutably
         let camera = self.state.renderer.camera().clone();
         
         self.state.renderer.render(
@@ -110,4 +267,33 @@ impl Engine {
             &mut self.state.ui_manager,
         )
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lost_and_outdated_surfaces_are_reconfigured() {
+        // Reconfiguring re-applies the current config, so these recover even
+        // when the window size never changed
+        for error in [wgpu::SurfaceError::Lost, wgpu::SurfaceError::Outdated] {
+            let error = anyhow::Error::from(error);
+            assert_eq!(SurfaceRecovery::for_error(&error), SurfaceRecovery::Reconfigure);
+        }
+    }
+
+    #[test]
+    fn out_of_memory_saves_and_exits() {
+        let error = anyhow::Error::from(wgpu::SurfaceError::OutOfMemory);
+        assert_eq!(SurfaceRecovery::for_error(&error), SurfaceRecovery::SaveAndExit);
+    }
+
+    #[test]
+    fn other_render_errors_skip_the_frame() {
+        let timeout = anyhow::Error::from(wgpu::SurfaceError::Timeout);
+        assert_eq!(SurfaceRecovery::for_error(&timeout), SurfaceRecovery::Skip);
+        let other = anyhow::anyhow!("shader failed");
+        assert_eq!(SurfaceRecovery::for_error(&other), SurfaceRecovery::Skip);
+    }
+}
@@ -1,13 +1,17 @@
 use anyhow::Result;
+use std::sync::Arc;
 use winit::window::Window;
 
-use crate::rendering::{Renderer, Texture};
+use crate::rendering::{GraphicsSettings, Renderer, Texture};
 use crate::input::InputManager;
-use crate::world::World;
+use crate::world::{World, WorldStorage};
 use crate::game::GameManager;
 use crate::audio::AudioManager;
 use crate::ui::UIManager;
 
+/// Directory the world is saved to and loaded from
+const SAVE_DIRECTORY: &str = "saves/world";
+
 /// Central state container for all engine subsystems
 pub struct EngineState {
     pub renderer: Renderer,
@@ -19,22 +23,32 @@ pub struct EngineState {
 }
 
 impl EngineState {
-    pub async fn new(window: &Window) -> Result<Self> {
+    pub async fn new(window: Arc<Window>) -> Result<Self> {
+        Self::with_graphics(window, GraphicsSettings::default()).await
+    }
+
+    /// Set up every subsystem, with the renderer using the GPU `graphics` asks for
+    pub async fn with_graphics(window: Arc<Window>, graphics: GraphicsSettings) -> Result<Self> {
         // Initialize renderer first as other systems may depend on it
-        let renderer = Renderer::new(window).await?;
+        let mut renderer = Renderer::with_settings(window.clone(), graphics).await?;
         
         // Initialize other systems
         let input_manager = InputManager::new();
-        let world = World::new();
-        let game_manager = GameManager::new();
+        let (world, metadata) = World::open(WorldStorage::new(SAVE_DIRECTORY))?;
+        let mut game_manager = GameManager::with_spawn_point(world.spawn_point());
+        match metadata {
+            Some(metadata) => game_manager.restore_metadata(metadata, renderer.camera_mut()),
+            None => renderer.camera_mut().set_position(game_manager.player().position()),
+        }
         let audio_manager = AudioManager::new()?;
-        let ui_manager = UIManager::new(
+        let mut ui_manager = UIManager::new(
             renderer.device(),
             renderer.surface_format(),
             Some(Texture::DEPTH_FORMAT),
             1,
-            window,
+            &window,
         );
+        ui_manager.register_block_atlas(renderer.device(), renderer.texture_atlas());
 
         Ok(Self {
             renderer,
@@ -45,4 +59,9 @@ impl EngineState {
             ui_manager,
         })
     }
+
+    /// Persist modified chunks, world metadata and player state
+    pub fn save(&mut self) -> Result<()> {
+        self.world.save_all(self.game_manager.game_mode(), self.game_manager.player())
+    }
 }
\ No newline at end of file
@@ -1,29 +1,67 @@
 use std::time::{Duration, Instant};
 
-/// Manages game timing with support for fixed timestep and delta time
+/// Simulation ticks per second unless configured otherwise
+pub const DEFAULT_TICK_RATE: u32 = 60;
+
+/// Seconds a tick may be short and still run, absorbing rounding in the accumulator
+const TICK_TOLERANCE: f32 = 1e-5;
+
+/// Longest frame the simulation will catch up on; longer frames are clamped to this
+pub const MAX_FRAME_TIME: f32 = 0.25;
+
+/// Frame rate cap while a menu is open, to save power
+pub const MENU_FRAME_LIMIT: u32 = 30;
+
+/// When the next frame should start to keep to `max_fps`, given when the last one did
+pub fn next_frame_deadline(last_frame: Instant, max_fps: u32) -> Instant {
+    last_frame + Duration::from_secs_f64(1.0 / max_fps.max(1) as f64)
+}
+
+/// Manages game timing with support for fixed timestep and delta time.
+///
+/// World and player simulation advance in fixed ticks of `fixed_timestep` seconds,
+/// independent of the frame rate. Gameplay constants (gravity, speeds, timers) are
+/// expressed per second and scaled by the tick length, so changing the tick rate
+/// changes how finely the simulation is stepped, not how fast the game runs.
 pub struct TimeManager {
     last_update: Instant,
     delta_time: f32,
     total_time: f32,
+    tick_rate: u32,
     fixed_timestep: f32,
     accumulator: f32,
     frame_count: u64,
     fps_timer: Instant,
     current_fps: u32,
+    /// Maximum frames per second in game, or `None` to render as fast as possible
+    frame_limit: Option<u32>,
+    /// Frames longer than this many seconds are treated as stalls and simulate
+    /// nothing. Off by default, so long frames are only clamped.
+    stall_threshold: Option<f32>,
 }
 
 impl TimeManager {
     pub fn new() -> Self {
+        Self::with_tick_rate(DEFAULT_TICK_RATE)
+    }
+
+    /// Create a time manager running the simulation at `ticks_per_second`,
+    /// e.g. 20 for Minecraft-like ticks
+    pub fn with_tick_rate(ticks_per_second: u32) -> Self {
         let now = Instant::now();
+        let tick_rate = ticks_per_second.max(1);
         Self {
             last_update: now,
             delta_time: 0.0,
             total_time: 0.0,
-            fixed_timestep: 1.0 / 60.0, // 60 FPS target
+            tick_rate,
+            fixed_timestep: 1.0 / tick_rate as f32,
             accumulator: 0.0,
             frame_count: 0,
             fps_timer: now,
             current_fps: 0,
+            frame_limit: None,
+            stall_threshold: None,
         }
     }
 
@@ -31,11 +69,7 @@ impl TimeManager {
         let now = Instant::now();
         let frame_time = now.duration_since(self.last_update).as_secs_f32();
         self.last_update = now;
-
-        // Cap frame time to prevent spiral of death
-        self.delta_time = frame_time.min(0.25);
-        self.total_time += self.delta_time;
-        self.accumulator += self.delta_time;
+        self.advance(frame_time);
 
         // Update FPS counter
         self.frame_count += 1;
@@ -46,6 +80,22 @@ impl TimeManager {
         }
     }
 
+    /// Account for `frame_time` seconds having passed, queueing up fixed ticks.
+    /// `update` calls this with the measured frame time.
+    pub fn advance(&mut self, frame_time: f32) {
+        // A hitch such as loading chunks or sitting at a breakpoint isn't game time;
+        // skip it rather than lurch the player forward
+        if self.stall_threshold.is_some_and(|threshold| frame_time > threshold) {
+            self.delta_time = 0.0;
+            return;
+        }
+
+        // Cap frame time to prevent spiral of death
+        self.delta_time = frame_time.min(MAX_FRAME_TIME);
+        self.total_time += self.delta_time;
+        self.accumulator += self.delta_time;
+    }
+
     /// Get the delta time for the current frame
     pub fn delta_time(&self) -> f32 {
         self.delta_time
@@ -56,6 +106,17 @@ impl TimeManager {
         self.total_time
     }
 
+    /// Simulation ticks per second
+    pub fn tick_rate(&self) -> u32 {
+        self.tick_rate
+    }
+
+    /// Change the simulation rate; at least one tick per second
+    pub fn set_tick_rate(&mut self, ticks_per_second: u32) {
+        self.tick_rate = ticks_per_second.max(1);
+        self.fixed_timestep = 1.0 / self.tick_rate as f32;
+    }
+
     /// Get the fixed timestep value
     pub fn fixed_timestep(&self) -> f32 {
         self.fixed_timestep
@@ -63,14 +124,46 @@ impl TimeManager {
 
     /// Check if we should run a fixed timestep update
     pub fn should_fixed_update(&mut self) -> bool {
-        if self.accumulator >= self.fixed_timestep {
-            self.accumulator -= self.fixed_timestep;
+        // Frame times summed in floating point land a hair short of whole ticks;
+        // without some slack a tick that is due slips to the next frame
+        if self.accumulator + TICK_TOLERANCE >= self.fixed_timestep {
+            self.accumulator = (self.accumulator - self.fixed_timestep).max(0.0);
             true
         } else {
             false
         }
     }
 
+    pub fn stall_threshold(&self) -> Option<f32> {
+        self.stall_threshold
+    }
+
+    /// Skip simulating frames longer than `threshold` seconds, or pass `None` to
+    /// always simulate, clamped to `MAX_FRAME_TIME`
+    pub fn set_stall_threshold(&mut self, threshold: Option<f32>) {
+        self.stall_threshold = threshold;
+    }
+
+    pub fn frame_limit(&self) -> Option<u32> {
+        self.frame_limit
+    }
+
+    /// Cap the in-game frame rate, or pass `None` to leave it uncapped
+    pub fn set_frame_limit(&mut self, max_fps: Option<u32>) {
+        self.frame_limit = max_fps.map(|fps| fps.max(1));
+    }
+
+    /// When the next frame is due, or `None` to start it right away. Menus are
+    /// capped at `MENU_FRAME_LIMIT` even if the game itself isn't.
+    pub fn next_frame_at(&self, in_menu: bool) -> Option<Instant> {
+        let limit = match (self.frame_limit, in_menu) {
+            (Some(limit), true) => Some(limit.min(MENU_FRAME_LIMIT)),
+            (None, true) => Some(MENU_FRAME_LIMIT),
+            (limit, false) => limit,
+        };
+        limit.map(|max_fps| next_frame_deadline(self.last_update, max_fps))
+    }
+
     /// Get the current FPS
     pub fn fps(&self) -> u32 {
         self.current_fps
@@ -80,4 +173,57 @@ impl TimeManager {
     pub fn interpolation_factor(&self) -> f32 {
         self.accumulator / self.fixed_timestep
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn twenty_ticks_per_second_fire_twenty_times_per_simulated_second() {
+        let mut time = TimeManager::with_tick_rate(20);
+        let mut ticks = 0;
+        for _ in 0..100 {
+            time.advance(0.01);
+            while time.should_fixed_update() {
+                ticks += 1;
+            }
+        }
+        assert_eq!(ticks, 20);
+    }
+
+    #[test]
+    fn stalled_frame_advances_nothing_when_skipping() {
+        let mut time = TimeManager::new();
+        time.set_stall_threshold(Some(MAX_FRAME_TIME));
+
+        time.advance(0.5);
+
+        assert_eq!(time.delta_time(), 0.0);
+        assert_eq!(time.total_time(), 0.0);
+        assert!(!time.should_fixed_update());
+    }
+
+    #[test]
+    fn long_frames_are_clamped_by_default() {
+        let mut time = TimeManager::new();
+        assert_eq!(time.stall_threshold(), None);
+
+        time.advance(0.5);
+
+        assert_eq!(time.delta_time(), MAX_FRAME_TIME);
+    }
+
+    #[test]
+    fn thirty_fps_cap_spaces_frames_about_33ms_apart() {
+        let last_frame = Instant::now();
+        let spacing = next_frame_deadline(last_frame, 30) - last_frame;
+        assert!((spacing.as_secs_f64() - 1.0 / 30.0).abs() < 1e-6, "{spacing:?}");
+
+        // Menus fall back to the menu cap even with the game uncapped
+        let time = TimeManager::new();
+        let menu_spacing = time.next_frame_at(true).unwrap() - time.last_update;
+        assert_eq!(menu_spacing, spacing);
+        assert_eq!(time.next_frame_at(false), None);
+    }
+}
@@ -1,26 +1,53 @@
 use crate::world::BlockType;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Item stack with type and count
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(from = "StoredItemStack")]
 pub struct ItemStack {
     pub item_type: BlockType,
+    /// Block state of the item, e.g. which flower; only equal states stack
+    pub state: u8,
     pub count: u32,
+    /// Derived from the item type, so it is recomputed on load rather than saved
+    #[serde(skip)]
     pub max_stack_size: u32,
 }
 
+/// On-disk form of an `ItemStack`
+#[derive(Deserialize)]
+struct StoredItemStack {
+    item_type: BlockType,
+    state: u8,
+    count: u32,
+}
+
+impl From<StoredItemStack> for ItemStack {
+    fn from(stored: StoredItemStack) -> Self {
+        Self::new(stored.item_type, stored.count).with_state(stored.state)
+    }
+}
+
 impl ItemStack {
     pub fn new(item_type: BlockType, count: u32) -> Self {
         Self {
             item_type,
+            state: 0,
             count,
             max_stack_size: Self::get_max_stack_size(item_type),
         }
     }
 
+    pub fn with_state(mut self, state: u8) -> Self {
+        self.state = state;
+        self
+    }
+
     pub fn empty() -> Self {
         Self {
             item_type: BlockType::Air,
+            state: 0,
             count: 0,
             max_stack_size: 64,
         }
@@ -35,7 +62,7 @@ impl ItemStack {
     }
 
     pub fn can_stack_with(&self, other: &ItemStack) -> bool {
-        self.item_type == other.item_type && !self.is_full()
+        self.item_type == other.item_type && self.state == other.state && !self.is_full()
     }
 
     pub fn add(&mut self, count: u32) -> u32 {
@@ -62,14 +89,70 @@ impl ItemStack {
     }
 }
 
+pub const HOTBAR_SLOTS: usize = 9;
+pub const MAIN_SLOTS: usize = 27;
+pub const ARMOR_SLOTS: usize = 4;
+/// Hotbar, main, armor and offhand slots together
+pub const TOTAL_SLOTS: usize = HOTBAR_SLOTS + MAIN_SLOTS + ARMOR_SLOTS + 1;
+
+/// A single inventory slot. Also numbered with one flat index, in the order hotbar,
+/// main, armor, offhand, for the inventory screen and network messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SlotId {
+    Hotbar(usize),
+    Main(usize),
+    Armor(usize),
+    Offhand,
+}
+
+impl SlotId {
+    /// The slot with the given flat index, or `None` past the last slot
+    pub fn from_index(index: usize) -> Option<SlotId> {
+        let slot = match index {
+            i if i < HOTBAR_SLOTS => SlotId::Hotbar(i),
+            i if i < HOTBAR_SLOTS + MAIN_SLOTS => SlotId::Main(i - HOTBAR_SLOTS),
+            i if i < HOTBAR_SLOTS + MAIN_SLOTS + ARMOR_SLOTS => SlotId::Armor(i - HOTBAR_SLOTS - MAIN_SLOTS),
+            i if i < TOTAL_SLOTS => SlotId::Offhand,
+            _ => return None,
+        };
+        Some(slot)
+    }
+
+    /// Flat index of the slot; the inverse of `from_index` for valid slots
+    pub fn index(&self) -> usize {
+        match *self {
+            SlotId::Hotbar(i) => i,
+            SlotId::Main(i) => HOTBAR_SLOTS + i,
+            SlotId::Armor(i) => HOTBAR_SLOTS + MAIN_SLOTS + i,
+            SlotId::Offhand => TOTAL_SLOTS - 1,
+        }
+    }
+
+    /// Whether the slot's position is inside its region
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            SlotId::Hotbar(i) => i < HOTBAR_SLOTS,
+            SlotId::Main(i) => i < MAIN_SLOTS,
+            SlotId::Armor(i) => i < ARMOR_SLOTS,
+            SlotId::Offhand => true,
+        }
+    }
+
+    /// Every slot, in flat index order
+    pub fn all() -> impl Iterator<Item = SlotId> {
+        (0..TOTAL_SLOTS).filter_map(SlotId::from_index)
+    }
+}
+
 /// Player inventory with hotbar and storage
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Inventory {
     // 9 slots for hotbar
-    hotbar: [ItemStack; 9],
+    hotbar: [ItemStack; HOTBAR_SLOTS],
     // 27 slots for main inventory
-    main: [ItemStack; 27],
+    main: [ItemStack; MAIN_SLOTS],
     // 4 slots for armor
-    armor: [ItemStack; 4],
+    armor: [ItemStack; ARMOR_SLOTS],
     // 1 slot for offhand
     offhand: ItemStack,
 }
@@ -82,8 +165,8 @@ impl Inventory {
                 ItemStack::empty(), ItemStack::empty(), ItemStack::empty(),
                 ItemStack::empty(), ItemStack::empty(), ItemStack::empty(),
             ],
-            main: [ItemStack::empty(); 27],
-            armor: [ItemStack::empty(); 4],
+            main: [ItemStack::empty(); MAIN_SLOTS],
+            armor: [ItemStack::empty(); ARMOR_SLOTS],
             offhand: ItemStack::empty(),
         }
     }
@@ -211,17 +294,17 @@ impl Inventory {
     }
 
     /// Get hotbar slots
-    pub fn hotbar(&self) -> &[ItemStack; 9] {
+    pub fn hotbar(&self) -> &[ItemStack; HOTBAR_SLOTS] {
         &self.hotbar
     }
 
     /// Get main inventory slots
-    pub fn main(&self) -> &[ItemStack; 27] {
+    pub fn main(&self) -> &[ItemStack; MAIN_SLOTS] {
         &self.main
     }
 
     /// Get armor slots
-    pub fn armor(&self) -> &[ItemStack; 4] {
+    pub fn armor(&self) -> &[ItemStack; ARMOR_SLOTS] {
         &self.armor
     }
 
@@ -239,6 +322,13 @@ impl Inventory {
         }
     }
 
+    /// First hotbar slot holding the given block variant
+    pub fn find_hotbar_slot(&self, item_type: BlockType, state: u8) -> Option<usize> {
+        self.hotbar
+            .iter()
+            .position(|stack| !stack.is_empty() && stack.item_type == item_type && stack.state == state)
+    }
+
     /// Set item in specific hotbar slot
     pub fn set_hotbar_item(&mut self, slot: usize, item: ItemStack) -> Option<ItemStack> {
         if slot < 9 {
@@ -253,6 +343,57 @@ impl Inventory {
         }
     }
 
+    /// Item in any slot, or `None` if the slot is out of range
+    pub fn get_slot(&self, slot: SlotId) -> Option<&ItemStack> {
+        match slot {
+            SlotId::Hotbar(i) => self.hotbar.get(i),
+            SlotId::Main(i) => self.main.get(i),
+            SlotId::Armor(i) => self.armor.get(i),
+            SlotId::Offhand => Some(&self.offhand),
+        }
+    }
+
+    fn slot_mut(&mut self, slot: SlotId) -> Option<&mut ItemStack> {
+        match slot {
+            SlotId::Hotbar(i) => self.hotbar.get_mut(i),
+            SlotId::Main(i) => self.main.get_mut(i),
+            SlotId::Armor(i) => self.armor.get_mut(i),
+            SlotId::Offhand => Some(&mut self.offhand),
+        }
+    }
+
+    /// Put an item in a slot, returning the stack it displaced (empty if the slot
+    /// was). Returns `None` and leaves the inventory untouched if the slot is out
+    /// of range.
+    pub fn set_slot(&mut self, slot: SlotId, item: ItemStack) -> Option<ItemStack> {
+        self.slot_mut(slot).map(|current| std::mem::replace(current, item))
+    }
+
+    /// Empty a slot, returning what was in it, or `None` if the slot is out of range
+    pub fn take_slot(&mut self, slot: SlotId) -> Option<ItemStack> {
+        self.set_slot(slot, ItemStack::empty())
+    }
+
+    /// Drop a held stack onto a slot, as when dragging items in the inventory screen.
+    ///
+    /// The same item merges into the slot up to its stack size and the leftover is
+    /// returned (the whole stack if the slot is full); anything else swaps with the
+    /// slot's contents, which are returned. Returns `None` if the slot is out of range.
+    pub fn place_in_slot(&mut self, slot: SlotId, mut stack: ItemStack) -> Option<ItemStack> {
+        let current = self.slot_mut(slot)?;
+        if current.is_empty() || stack.is_empty() {
+            return Some(std::mem::replace(current, stack));
+        }
+        if current.item_type == stack.item_type && current.state == stack.state {
+            stack.count = current.add(stack.count);
+            if stack.count == 0 {
+                stack = ItemStack::empty();
+            }
+            return Some(stack);
+        }
+        Some(std::mem::replace(current, stack))
+    }
+
     /// Clear all items from inventory
     pub fn clear(&mut self) {
         for slot in &mut self.hotbar {
@@ -285,10 +426,94 @@ impl Inventory {
 impl Default for Inventory {
     fn default() -> Self {
         Self {
-            hotbar: [ItemStack::empty(); 9],
+            hotbar: [ItemStack::empty(); HOTBAR_SLOTS],
             main: core::array::from_fn(|_| ItemStack::empty()),
             armor: core::array::from_fn(|_| ItemStack::empty()),
             offhand: ItemStack::empty(),
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn populated_inventory_round_trips() {
+        let mut inventory = Inventory::new();
+        inventory.add_item(ItemStack::new(BlockType::Stone, 64));
+        inventory.add_item(ItemStack::new(BlockType::Dirt, 100));
+        inventory.set_slot(SlotId::Armor(3), ItemStack::new(BlockType::Wool, 1));
+        inventory.set_slot(SlotId::Offhand, ItemStack::new(BlockType::Torch, 5));
+        inventory.set_slot(SlotId::Main(26), ItemStack::new(BlockType::Flower, 3).with_state(2));
+
+        let bytes = bincode::serialize(&inventory).unwrap();
+        let loaded: Inventory = bincode::deserialize(&bytes).unwrap();
+
+        assert_eq!(loaded, inventory);
+    }
+
+    #[test]
+    fn stack_size_is_recomputed_on_load() {
+        let bytes = bincode::serialize(&ItemStack::new(BlockType::Stone, 10)).unwrap();
+        let loaded: ItemStack = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(loaded.max_stack_size, ItemStack::new(BlockType::Stone, 0).max_stack_size);
+    }
+
+    #[test]
+    fn first_and_last_slot_of_each_region_round_trip() {
+        let mut inventory = Inventory::new();
+        let slots = [
+            SlotId::Hotbar(0),
+            SlotId::Hotbar(HOTBAR_SLOTS - 1),
+            SlotId::Main(0),
+            SlotId::Main(MAIN_SLOTS - 1),
+            SlotId::Armor(0),
+            SlotId::Armor(ARMOR_SLOTS - 1),
+            SlotId::Offhand,
+        ];
+        for (count, slot) in (1..).zip(slots) {
+            let stack = ItemStack::new(BlockType::Cobblestone, count);
+            assert_eq!(SlotId::from_index(slot.index()), Some(slot));
+            assert_eq!(inventory.set_slot(slot, stack), Some(ItemStack::empty()));
+            assert_eq!(inventory.get_slot(slot), Some(&stack));
+            assert_eq!(inventory.take_slot(slot), Some(stack));
+            assert!(inventory.get_slot(slot).unwrap().is_empty());
+        }
+
+        assert_eq!(inventory.set_slot(SlotId::Main(MAIN_SLOTS), ItemStack::new(BlockType::Dirt, 1)), None);
+        assert_eq!(SlotId::from_index(TOTAL_SLOTS), None);
+    }
+
+    #[test]
+    fn partial_stacks_of_the_same_item_merge() {
+        let mut inventory = Inventory::new();
+        inventory.set_slot(SlotId::Main(0), ItemStack::new(BlockType::Sand, 40));
+
+        let leftover = inventory.place_in_slot(SlotId::Main(0), ItemStack::new(BlockType::Sand, 30)).unwrap();
+
+        assert_eq!(inventory.get_slot(SlotId::Main(0)).unwrap().count, 64);
+        assert_eq!((leftover.item_type, leftover.count), (BlockType::Sand, 6));
+    }
+
+    #[test]
+    fn merging_into_a_full_slot_hands_the_whole_stack_back() {
+        let mut inventory = Inventory::new();
+        inventory.set_slot(SlotId::Hotbar(2), ItemStack::new(BlockType::Sand, 64));
+        let held = ItemStack::new(BlockType::Sand, 10);
+
+        assert_eq!(inventory.place_in_slot(SlotId::Hotbar(2), held), Some(held));
+        assert_eq!(inventory.get_slot(SlotId::Hotbar(2)).unwrap().count, 64);
+    }
+
+    #[test]
+    fn different_items_swap() {
+        let mut inventory = Inventory::new();
+        let sand = ItemStack::new(BlockType::Sand, 12);
+        let glass = ItemStack::new(BlockType::Glass, 3);
+        inventory.set_slot(SlotId::Armor(1), sand);
+
+        assert_eq!(inventory.place_in_slot(SlotId::Armor(1), glass), Some(sand));
+        assert_eq!(inventory.get_slot(SlotId::Armor(1)), Some(&glass));
+    }
+}
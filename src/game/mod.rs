@@ -1,23 +1,75 @@
-use glam::Vec3;
-use crate::world::{BlockType, World, RaycastHit};
-use crate::rendering::camera::{Camera, CameraMovement, Ray};
+use glam::{IVec3, Vec3};
+use serde::{Deserialize, Serialize};
+use crate::world::{BlockType, ChunkCoordinate, DoorFacing, World, RaycastHit, ExplosionResult, WorldMetadata, CHUNK_HEIGHT};
+use crate::rendering::camera::{Camera, Ray};
+use crate::rendering::Face;
 use crate::input::InputManager;
 
 mod player;
 mod inventory;
 mod physics;
 
-pub use player::Player;
-pub use inventory::{Inventory, ItemStack};
+pub use player::{Player, PlayerDimensions};
+pub use inventory::{Inventory, ItemStack, SlotId};
+pub use physics::{Aabb, Physics, PhysicsConfig};
+
+/// How far away, in blocks, the player can reach blocks
+pub const REACH_DISTANCE: f32 = 5.0;
+/// Damage dealt by hitting a mob with a bare hand
+pub const ATTACK_DAMAGE: f32 = 1.0;
+/// Blocks a new creative game starts with on the hotbar
+const CREATIVE_HOTBAR: [BlockType; 9] = [
+    BlockType::Stone,
+    BlockType::Dirt,
+    BlockType::Grass,
+    BlockType::Wood,
+    BlockType::Sand,
+    BlockType::Glass,
+    BlockType::Cobblestone,
+    BlockType::Leaves,
+    BlockType::Torch,
+];
+
+/// Rate limits on held mouse buttons, in game ticks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InteractionConfig {
+    /// Ticks between placements while place is held in survival and adventure
+    pub place_cooldown_ticks: u32,
+    /// Ticks between placements while place is held in creative
+    pub creative_place_cooldown_ticks: u32,
+    /// Ticks after breaking a block before the next one starts, so instant
+    /// breaking in creative doesn't tunnel through everything in reach
+    pub break_cooldown_ticks: u32,
+}
+
+impl Default for InteractionConfig {
+    fn default() -> Self {
+        Self {
+            place_cooldown_ticks: 12,
+            creative_place_cooldown_ticks: 6,
+            break_cooldown_ticks: 0,
+        }
+    }
+}
 
 /// Main game manager that handles game logic and player state
 pub struct GameManager {
     player: Player,
     game_mode: GameMode,
     selected_block_type: BlockType,
+    /// Variant placed along with the selected block
+    selected_block_state: u8,
     breaking_progress: f32,
-    breaking_target: Option<Vec3>,
+    /// Block being mined, by integer coordinates so hits that land at slightly
+    /// different points on the same block keep its progress
+    breaking_target: Option<IVec3>,
     breaking_time: f32,
+    /// Block and face under the crosshair, updated every frame from the camera ray
+    targeted: Option<(IVec3, BlockType, Face)>,
+    interaction: InteractionConfig,
+    /// Ticks left before the held place or break button acts again
+    place_cooldown: u32,
+    break_cooldown: u32,
     
     // Game state
     paused: bool,
@@ -25,7 +77,7 @@ pub struct GameManager {
     show_inventory: bool,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum GameMode {
     Survival,
     Creative,
@@ -35,35 +87,92 @@ pub enum GameMode {
 
 impl GameManager {
     pub fn new() -> Self {
+        Self::with_spawn_point(Vec3::new(0.0, 100.0, 0.0))
+    }
+
+    /// Start a new game with the player's feet at `spawn`, usually `World::find_safe_spawn`
+    pub fn with_spawn_point(spawn: Vec3) -> Self {
+        let mut player = Player::new(Vec3::ZERO);
+        player.set_feet_position(spawn);
+        player.set_flying(true); // Creative players start flying
+        for (slot, block) in CREATIVE_HOTBAR.into_iter().enumerate() {
+            player.inventory_mut().set_hotbar_item(slot, ItemStack::new(block, 1));
+        }
+
         Self {
-            player: Player::new(Vec3::new(0.0, 100.0, 0.0)),
+            player,
             game_mode: GameMode::Creative, // Start in creative for testing
             selected_block_type: BlockType::Stone,
+            selected_block_state: 0,
             breaking_progress: 0.0,
             breaking_target: None,
             breaking_time: 0.0,
+            targeted: None,
+            interaction: InteractionConfig::default(),
+            place_cooldown: 0,
+            break_cooldown: 0,
             paused: false,
             debug_mode: false,
             show_inventory: false,
         }
     }
 
-    pub fn update(&mut self, delta_time: f32) {
+    pub fn update(&mut self, world: &World, delta_time: f32) {
         if self.paused {
             return;
         }
 
-        // Update player
-        self.player.update(delta_time);
-        
-        // Update breaking progress
-        if let Some(_target) = self.breaking_target {
-            self.breaking_time += delta_time;
-            let block_type = self.selected_block_type; // In real game, this would be the target block
-            let mining_time = block_type.mining_time();
-            
-            self.breaking_progress = (self.breaking_time / mining_time).min(1.0);
+        self.place_cooldown = self.place_cooldown.saturating_sub(1);
+        self.break_cooldown = self.break_cooldown.saturating_sub(1);
+
+        // Update player. Breaking progress is advanced by `handle_block_breaking`,
+        // which knows the targeted block.
+        self.player.update(world, delta_time);
+
+        if !self.player.is_alive() {
+            let spawn = self.free_feet_position(world, world.spawn_point());
+            self.player.respawn(spawn);
+        }
+    }
+
+    /// Pick up dropped items within reach of the player, as far as the inventory has room
+    pub fn collect_items(&mut self, world: &mut World) {
+        if self.game_mode == GameMode::Spectator || !self.player.is_alive() {
+            return;
+        }
+        let collector = self.player.aabb();
+        let inventory = self.player.inventory_mut();
+        world.collect_items(&collector, |item_type, count| {
+            count - inventory.add_item(ItemStack::new(item_type, count)).count
+        });
+    }
+
+    /// Move the player to stand in block `target`, for commands and tools. See `teleport_to`.
+    pub fn teleport(&mut self, world: &mut World, camera: &mut Camera, target: IVec3) {
+        self.teleport_to(world, camera, target.as_vec3() + Vec3::new(0.5, 0.0, 0.5));
+    }
+
+    /// Move the player's feet to `feet`. The chunk there is loaded, or generated,
+    /// on the spot, and the player is lifted out of any solid blocks and stops moving.
+    pub fn teleport_to(&mut self, world: &mut World, camera: &mut Camera, feet: Vec3) {
+        let coord = ChunkCoordinate::containing(feet.x.floor() as i32, feet.z.floor() as i32);
+        world.load_chunk_now(coord);
+
+        let feet = self.free_feet_position(world, feet);
+        self.player.teleport(feet);
+        camera.set_position(self.player.camera_position());
+        world.load_chunks_around(self.player.position());
+    }
+
+    /// The lowest whole-block height at or above `feet` where the player doesn't
+    /// overlap a solid block
+    fn free_feet_position(&self, world: &World, feet: Vec3) -> Vec3 {
+        let physics = world.physics();
+        let mut feet = feet;
+        while feet.y < CHUNK_HEIGHT as f32 && physics.is_colliding(world, &self.player.aabb_at(feet)) {
+            feet.y = feet.y.floor() + 1.0;
         }
+        feet
     }
 
     /// Process input and update game state
@@ -86,57 +195,62 @@ impl GameManager {
         }
 
         // Handle camera movement
-        self.handle_camera_movement(input, camera, delta_time);
+        self.handle_camera_movement(input, camera, world, delta_time);
         
         // Handle block interaction
         self.handle_block_interaction(input, camera, world, delta_time);
         
         // Handle hotbar selection
         if let Some(slot) = input.get_hotbar_selection() {
-            self.player.set_selected_hotbar_slot(slot);
-            
-            // Set selected block type based on hotbar (simplified)
-            self.selected_block_type = match slot {
-                0 => BlockType::Stone,
-                1 => BlockType::Dirt,
-                2 => BlockType::Grass,
-                3 => BlockType::Wood,
-                4 => BlockType::Sand,
-                5 => BlockType::Glass,
-                6 => BlockType::Cobblestone,
-                7 => BlockType::Leaves,
-                8 => BlockType::Torch,
-                _ => BlockType::Stone,
-            };
-        }
-
-        // Update player position and world chunk loading
-        let player_pos = camera.position();
-        self.player.set_position(player_pos);
-        world.load_chunks_around(player_pos);
-    }
-
-    fn handle_camera_movement(&mut self, input: &InputManager, camera: &mut Camera, delta_time: f32) {
-        // Movement
-        if input.move_forward() {
-            camera.process_keyboard(CameraMovement::Forward, delta_time);
+            self.select_hotbar_slot(slot);
         }
-        if input.move_backward() {
-            camera.process_keyboard(CameraMovement::Backward, delta_time);
-        }
-        if input.move_left() {
-            camera.process_keyboard(CameraMovement::Left, delta_time);
+
+        // Flying moves the camera directly; otherwise the camera follows the simulated
+        // player, see `sync_camera`
+        if self.player.is_flying() {
+            self.player.set_position(camera.position());
         }
-        if input.move_right() {
-            camera.process_keyboard(CameraMovement::Right, delta_time);
+        let player_pos = self.player.position();
+        world.load_chunks_around(player_pos);
+
+        // Contact damage (cactus, lava) only applies to modes that can take damage
+        if matches!(self.game_mode, GameMode::Survival | GameMode::Adventure) {
+            let aabb = self.player.aabb();
+            let touching = world.physics().blocks_touching(world, &aabb);
+            let overlapping = world.physics().blocks_overlapping(world, &aabb);
+            self.player.apply_block_contacts(&touching, &overlapping, delta_time);
         }
-        if input.jump() {
-            camera.process_keyboard(CameraMovement::Up, delta_time);
+
+        // Aim from where the camera ended up, after this frame's edits
+        self.update_target(world, camera);
+    }
+
+    /// Raycast from the camera for the block under the crosshair
+    fn update_target(&mut self, world: &World, camera: &Camera) {
+        self.targeted = world
+            .raycast(&camera.cast_ray(REACH_DISTANCE))
+            .map(|hit| (hit.position.floor().as_ivec3(), hit.block_type, hit.face));
+    }
+
+    /// Move the camera to the walking player, `alpha` of the way from the previous
+    /// tick's position to the latest one. Flying players follow the camera instead.
+    pub fn sync_camera(&self, camera: &mut Camera, alpha: f32) {
+        if !self.player.is_flying() {
+            camera.set_position(self.player.interpolated_camera_position(alpha));
         }
-        if input.sneak() {
-            camera.process_keyboard(CameraMovement::Down, delta_time);
+    }
+
+    fn handle_camera_movement(&mut self, input: &InputManager, camera: &mut Camera, world: &World, delta_time: f32) {
+        let config = world.physics().config();
+        if self.player.is_flying() {
+            self.handle_flying_movement(input, camera, config, delta_time);
+        } else {
+            self.handle_walking_movement(input, camera, config);
         }
 
+        // Zoom only lasts while the key is held
+        camera.set_zoomed(input.zoom());
+
         // Mouse look
         if input.is_mouse_captured() {
             let (mouse_dx, mouse_dy) = input.mouse_delta();
@@ -149,72 +263,223 @@ impl GameManager {
         }
     }
 
+    fn handle_flying_movement(&mut self, input: &InputManager, camera: &mut Camera, config: &PhysicsConfig, delta_time: f32) {
+        // Submersion in lava slows movement
+        camera.set_move_speed(config.flying_speed * self.player.speed_multiplier(config));
+
+        // Accumulate this frame's intent so diagonal movement is normalized
+        let axis = |positive: bool, negative: bool| positive as i32 as f32 - negative as i32 as f32;
+        let forward = axis(input.move_forward(), input.move_backward());
+        let right = axis(input.move_right(), input.move_left());
+        let up = axis(input.jump(), input.sneak());
+
+        camera.process_movement(forward, right, up, delta_time);
+    }
+
+    /// Set the player's target horizontal velocity from input; physics integrates it in `update`
+    fn handle_walking_movement(&mut self, input: &InputManager, camera: &Camera, config: &PhysicsConfig) {
+        let forward = Vec3::new(camera.front().x, 0.0, camera.front().z).normalize_or_zero();
+        let right = Vec3::new(camera.right().x, 0.0, camera.right().z).normalize_or_zero();
+
+        let mut direction = Vec3::ZERO;
+        if input.move_forward() {
+            direction += forward;
+        }
+        if input.move_backward() {
+            direction -= forward;
+        }
+        if input.move_right() {
+            direction += right;
+        }
+        if input.move_left() {
+            direction -= right;
+        }
+
+        let speed = if input.sprint() {
+            config.sprinting_speed
+        } else {
+            config.walking_speed
+        } * self.player.speed_multiplier(config);
+
+        // Physics ramps the actual velocity towards this in `Player::update`
+        self.player.set_movement_target(direction.normalize_or_zero() * speed);
+        self.player.set_sneaking(input.sneak());
+
+        // Jump on land, swim upwards in water
+        if input.jump() {
+            self.player.jump(config);
+        }
+    }
+
     fn handle_block_interaction(&mut self, input: &InputManager, camera: &Camera, world: &mut World, delta_time: f32) {
-        let ray = camera.cast_ray(5.0); // 5 block reach distance
-        
+        let ray = camera.cast_ray(REACH_DISTANCE);
+
+        if input.pick_block() {
+            self.pick_block(&ray, world);
+        }
+
+        // Right-clicking a door, lever or button uses it
+        if input.use_block() {
+            if let Some(hit) = world.raycast(&ray) {
+                world.use_block(hit.position.floor().as_ivec3());
+            }
+        }
+
+        // A mob in front of the targeted block is hit instead of the block
+        if input.attack() && self.attack(&ray, world) {
+            return;
+        }
+
         if input.break_block() {
-            self.handle_block_breaking(&ray, world, delta_time);
+            if self.break_cooldown == 0 {
+                self.handle_block_breaking(&ray, world, delta_time);
+            }
         } else if input.place_block() {
-            self.handle_block_placement(&ray, world);
+            self.handle_held_placement(&ray, world);
         } else {
-            // Reset breaking if not holding break
+            // Reset breaking if not holding break. Cooldowns only limit held buttons,
+            // so separate clicks are never delayed.
             self.breaking_target = None;
             self.breaking_progress = 0.0;
             self.breaking_time = 0.0;
+            self.place_cooldown = 0;
+            self.break_cooldown = 0;
         }
     }
 
+    /// Place a block while place is held, at most once per place cooldown.
+    /// Returns whether a block was placed.
+    fn handle_held_placement(&mut self, ray: &Ray, world: &mut World) -> bool {
+        if self.place_cooldown > 0 || !self.handle_block_placement(ray, world) {
+            return false;
+        }
+        self.place_cooldown = match self.game_mode {
+            GameMode::Creative => self.interaction.creative_place_cooldown_ticks,
+            _ => self.interaction.place_cooldown_ticks,
+        };
+        true
+    }
+
     fn handle_block_breaking(&mut self, ray: &Ray, world: &mut World, delta_time: f32) {
         if let Some(hit) = world.raycast(ray) {
-            let target_pos = hit.position;
-            
-            // Check if we're breaking the same block
-            if let Some(current_target) = self.breaking_target {
-                if current_target != target_pos {
-                    // Started breaking a different block
-                    self.breaking_target = Some(target_pos);
-                    self.breaking_progress = 0.0;
-                    self.breaking_time = 0.0;
-                }
-            } else {
-                // Started breaking a new block
+            let target_pos = hit.position.floor().as_ivec3();
+
+            // Progress only resets when the crosshair moves onto a different block
+            if self.breaking_target != Some(target_pos) {
                 self.breaking_target = Some(target_pos);
                 self.breaking_progress = 0.0;
                 self.breaking_time = 0.0;
             }
 
-            // Update breaking progress
+            // Update breaking progress from the targeted block, not the held one
             self.breaking_time += delta_time;
-            let mining_time = hit.block_type.mining_time();
-            self.breaking_progress = (self.breaking_time / mining_time).min(1.0);
+            self.breaking_progress = Self::breaking_progress_for(hit.block_type, self.breaking_time);
 
             // Break the block if progress is complete
             if self.breaking_progress >= 1.0 {
-                let x = target_pos.x as i32;
-                let y = target_pos.y as i32;
-                let z = target_pos.z as i32;
-                
                 // Add drops to player inventory (simplified)
                 let drops = hit.block_type.drops();
                 for (block_type, count) in drops {
-                    self.player.inventory_mut().add_item(ItemStack::new(block_type, count));
+                    // A block that drops itself keeps its variant
+                    let state = if block_type == hit.block_type { hit.block_state } else { 0 };
+                    self.player.inventory_mut().add_item(ItemStack::new(block_type, count).with_state(state));
                 }
                 
                 // Remove the block
-                world.set_block_at(x, y, z, BlockType::Air);
+                world.set_block_at(target_pos.x, target_pos.y, target_pos.z, BlockType::Air);
                 
                 // Reset breaking state
                 self.breaking_target = None;
                 self.breaking_progress = 0.0;
                 self.breaking_time = 0.0;
+                self.break_cooldown = self.interaction.break_cooldown_ticks;
             }
         }
     }
 
-    fn handle_block_placement(&mut self, ray: &Ray, world: &mut World) {
+    /// Hit the nearest mob along the ray, if no block is in the way. Returns whether
+    /// a mob was hit.
+    pub fn attack(&mut self, ray: &Ray, world: &mut World) -> bool {
+        if self.game_mode == GameMode::Spectator {
+            return false;
+        }
+        let Some((index, distance)) = world.raycast_entity(ray) else {
+            return false;
+        };
+        if world.raycast(ray).is_some_and(|hit| hit.distance < distance) {
+            return false;
+        }
+
+        world.attack_entity(index, ATTACK_DAMAGE);
+        true
+    }
+
+    /// Fraction of `block` broken after mining it for `elapsed` seconds
+    fn breaking_progress_for(block: BlockType, elapsed: f32) -> f32 {
+        let mining_time = block.mining_time();
+        if mining_time <= 0.0 {
+            return 1.0;
+        }
+        (elapsed / mining_time).min(1.0)
+    }
+
+    /// Hold whatever is in the given hotbar slot, including its variant. An empty
+    /// slot holds air, which places nothing.
+    fn select_hotbar_slot(&mut self, slot: usize) {
+        self.player.set_selected_hotbar_slot(slot);
+        let (block, state) = match self.player.inventory().get_hotbar_item(slot) {
+            Some(stack) if !stack.is_empty() => (stack.item_type, stack.state),
+            _ => (BlockType::Air, 0),
+        };
+        self.select_block(block, state);
+    }
+
+    /// Select the block under the crosshair. Creative mode puts it in the held slot
+    /// unless it's already on the hotbar; other modes only switch to a hotbar slot
+    /// that already holds it. Returns whether the selection changed.
+    pub fn pick_block(&mut self, ray: &Ray, world: &World) -> bool {
+        let hit = match world.raycast(ray) {
+            Some(hit) => hit,
+            None => return false,
+        };
+        let (block, state) = (hit.block_type, hit.block_state);
+
+        let slot = match self.player.inventory().find_hotbar_slot(block, state) {
+            Some(slot) => slot,
+            None if self.game_mode == GameMode::Creative => {
+                let slot = self.player.selected_hotbar_slot();
+                let stack = ItemStack::new(block, 1).with_state(state);
+                self.player.inventory_mut().set_hotbar_item(slot, stack);
+                slot
+            }
+            None => return false,
+        };
+
+        self.player.set_selected_hotbar_slot(slot);
+        self.select_block(block, state);
+        true
+    }
+
+    /// Place the selected block against the targeted one, or light targeted TNT.
+    /// Returns whether anything happened.
+    fn handle_block_placement(&mut self, ray: &Ray, world: &mut World) -> bool {
+        if self.selected_block_type == BlockType::Air {
+            return false;
+        }
         if let Some(hit) = world.raycast(ray) {
+            // Interactive blocks are used on the first click, never built against
+            if hit.block_type.is_interactive() {
+                return false;
+            }
+
+            // Using a torch on TNT lights it (stand-in for flint and steel until tools exist)
+            if hit.block_type == BlockType::Tnt && self.selected_block_type == BlockType::Torch {
+                world.ignite_tnt(hit.position.x as i32, hit.position.y as i32, hit.position.z as i32);
+                return true;
+            }
+
             // Calculate placement position (adjacent to hit block)
-            let place_pos = self.calculate_placement_position(&hit, ray);
+            let place_pos = self.calculate_placement_position(&hit);
             
             if let Some(pos) = place_pos {
                 let x = pos.x as i32;
@@ -222,53 +487,79 @@ impl GameManager {
                 let z = pos.z as i32;
                 
                 // Check if position is valid for placement
+                if !world.is_within_build_limit(IVec3::new(x, y, z)) {
+                    return false;
+                }
+                // Don't build a solid block into the player
+                let cell = IVec3::new(x, y, z).as_vec3();
+                let player_box = self.player.aabb();
+                let blocks_player = self.selected_block_type
+                    .collision_boxes(self.selected_block_state)
+                    .into_iter()
+                    .any(|(min, max)| Aabb::new(cell + min, cell + max).intersects(&player_box));
+                if blocks_player {
+                    return false;
+                }
                 if let Some(existing_block) = world.get_block_at(x, y, z) {
                     if existing_block.is_replaceable() {
                         // Remove item from inventory if in survival mode
                         if self.game_mode == GameMode::Survival {
-                            if self.player.inventory().has_item(self.selected_block_type) {
+                            if self.player.inventory().has_item(self.selected_block_type)
+                                && self.place_selected_block(world, IVec3::new(x, y, z), ray)
+                            {
                                 self.player.inventory_mut().remove_item(self.selected_block_type, 1);
-                                world.set_block_at(x, y, z, self.selected_block_type);
+                                return true;
                             }
                         } else {
                             // Creative mode - place without cost
-                            world.set_block_at(x, y, z, self.selected_block_type);
+                            return self.place_selected_block(world, IVec3::new(x, y, z), ray);
                         }
                     }
                 }
             }
         }
+        false
     }
 
-    fn calculate_placement_position(&self, hit: &RaycastHit, ray: &Ray) -> Option<Vec3> {
-        // Simple approach: place adjacent to the hit block
-        // This should be improved to check which face was hit
-        let hit_pos = hit.position;
-        let ray_dir = ray.direction.normalize();
-        
-        // Try different adjacent positions
-        let offsets = [
-            Vec3::new(1.0, 0.0, 0.0),
-            Vec3::new(-1.0, 0.0, 0.0),
-            Vec3::new(0.0, 1.0, 0.0),
-            Vec3::new(0.0, -1.0, 0.0),
-            Vec3::new(0.0, 0.0, 1.0),
-            Vec3::new(0.0, 0.0, -1.0),
-        ];
-        
-        // Choose the offset that's most opposite to the ray direction
-        let mut best_offset = offsets[0];
-        let mut best_dot = ray_dir.dot(offsets[0]);
-        
-        for offset in offsets.iter() {
-            let dot = ray_dir.dot(*offset);
-            if dot < best_dot {
-                best_dot = dot;
-                best_offset = *offset;
+    /// Put the selected block at `pos`. Doors take two cells; doors and stairs face
+    /// along the ray.
+    /// Returns whether anything was placed.
+    fn place_selected_block(&self, world: &mut World, pos: IVec3, ray: &Ray) -> bool {
+        match self.selected_block_type {
+            BlockType::Door => world.place_door(pos, DoorFacing::from_direction(ray.direction.x, ray.direction.z)),
+            BlockType::Stairs => {
+                let facing = DoorFacing::from_direction(ray.direction.x, ray.direction.z);
+                world.set_block_with_state(pos, BlockType::Stairs, facing as u8)
             }
+            block => world.set_block_with_state(pos, block, self.selected_block_state),
         }
-        
-        Some(hit_pos + best_offset)
+    }
+
+    /// Restore the player and game mode from saved metadata, moving the camera to the player
+    pub fn restore_metadata(&mut self, metadata: WorldMetadata, camera: &mut Camera) {
+        self.game_mode = metadata.game_mode;
+        self.player = metadata.player;
+        camera.set_position(self.player.position());
+    }
+
+    /// Apply an explosion's effects to the player
+    pub fn apply_explosion(&mut self, explosion: &ExplosionResult) {
+        if matches!(self.game_mode, GameMode::Survival | GameMode::Adventure) {
+            let damage = explosion.damage_at(self.player.position());
+            if damage > 0.0 {
+                self.player.damage(damage);
+            }
+        }
+    }
+
+    /// The cell in front of the face the ray hit
+    fn calculate_placement_position(&self, hit: &RaycastHit) -> Option<Vec3> {
+        Some(hit.position + Vec3::from(hit.face.normal()))
+    }
+
+    /// Block position, type and face under the crosshair as of the last input update
+    pub fn targeted_block(&self) -> Option<(IVec3, BlockType, Face)> {
+        self.targeted
     }
 
     // Getters
@@ -288,15 +579,33 @@ impl GameManager {
         self.game_mode = mode;
     }
 
+    pub fn interaction_config(&self) -> &InteractionConfig {
+        &self.interaction
+    }
+
+    pub fn set_interaction_config(&mut self, config: InteractionConfig) {
+        self.interaction = config;
+    }
+
     pub fn selected_block_type(&self) -> BlockType {
         self.selected_block_type
     }
 
+    pub fn selected_block_state(&self) -> u8 {
+        self.selected_block_state
+    }
+
+    /// Choose the block and variant to place
+    pub fn select_block(&mut self, block: BlockType, state: u8) {
+        self.selected_block_type = block;
+        self.selected_block_state = state.min(block.variant_count() - 1);
+    }
+
     pub fn breaking_progress(&self) -> f32 {
         self.breaking_progress
     }
 
-    pub fn breaking_target(&self) -> Option<Vec3> {
+    pub fn breaking_target(&self) -> Option<IVec3> {
         self.breaking_target
     }
 
@@ -321,4 +630,194 @@ impl Default for GameManager {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::testing::flat_world;
+    use crate::world::{EntityKind, Pig};
+
+    /// Ray along +z from just above the ground at the origin
+    fn ray_along_z() -> Ray {
+        Ray {
+            origin: Vec3::new(0.5, 11.5, 0.5),
+            direction: Vec3::Z,
+            max_distance: REACH_DISTANCE,
+        }
+    }
+
+    #[test]
+    fn attacking_a_pig_until_it_dies_leaves_a_drop() {
+        let mut world = flat_world(10);
+        world.spawn_entity(Box::new(Pig::new(Vec3::new(0.5, 11.0, 3.5), 1)));
+        let mut game = GameManager::new();
+
+        for _ in 0..10 {
+            assert!(game.attack(&ray_along_z(), &mut world));
+        }
+        world.update(0.0);
+
+        let kinds: Vec<EntityKind> = world.entities().iter().map(|entity| entity.kind()).collect();
+        assert_eq!(kinds, vec![EntityKind::Item]);
+    }
+
+    #[test]
+    fn blocks_in_front_of_a_pig_stop_attacks() {
+        let mut world = flat_world(10);
+        world.spawn_entity(Box::new(Pig::new(Vec3::new(0.5, 11.0, 3.5), 1)));
+        world.set_block(IVec3::new(0, 11, 2), BlockType::Stone);
+        let mut game = GameManager::new();
+
+        assert!(!game.attack(&ray_along_z(), &mut world));
+    }
+
+    #[test]
+    fn pigs_out_of_reach_are_missed() {
+        let mut world = flat_world(10);
+        world.spawn_entity(Box::new(Pig::new(Vec3::new(0.5, 11.0, REACH_DISTANCE + 2.0), 1)));
+        let mut game = GameManager::new();
+
+        assert!(!game.attack(&ray_along_z(), &mut world));
+    }
+
+    #[test]
+    fn mining_time_comes_from_the_target_not_the_held_block() {
+        let mut world = flat_world(10);
+        let wall = IVec3::new(0, 11, 2);
+        world.set_block(wall, BlockType::Stone);
+        let mut game = GameManager::new();
+        game.select_block(BlockType::Dirt, 0);
+
+        // Long enough to break dirt, but only a third of the way through stone
+        game.handle_block_breaking(&ray_along_z(), &mut world, BlockType::Dirt.mining_time());
+        assert_eq!(world.block_at(wall), Some(BlockType::Stone));
+        assert_eq!(game.breaking_progress(), BlockType::Dirt.mining_time() / BlockType::Stone.mining_time());
+
+        game.handle_block_breaking(&ray_along_z(), &mut world, BlockType::Stone.mining_time());
+        assert_eq!(world.block_at(wall), Some(BlockType::Air));
+    }
+
+    #[test]
+    fn new_game_starts_on_the_ground_at_the_safe_spawn() {
+        let mut world = World::with_seed(12345);
+        let spawn = world.find_safe_spawn();
+
+        let game = GameManager::with_spawn_point(spawn);
+
+        let feet = game.player().feet_position();
+        assert_eq!(feet, spawn);
+        let below = (feet - Vec3::Y).floor().as_ivec3();
+        assert!(world.block_at(below).is_some_and(|block| block.is_solid()));
+        let player_box = game.player().aabb();
+        assert!(world.physics().blocks_overlapping(&world, &player_box).iter().all(|block| !block.is_solid()));
+    }
+
+    #[test]
+    fn number_keys_select_the_hotbar_stack_and_its_variant() {
+        let mut game = GameManager::new();
+        game.player_mut().inventory_mut().set_hotbar_item(4, ItemStack::new(BlockType::Slab, 3).with_state(1));
+
+        game.select_hotbar_slot(4);
+        assert_eq!(game.player().selected_hotbar_slot(), 4);
+        assert_eq!((game.selected_block_type(), game.selected_block_state()), (BlockType::Slab, 1));
+
+        game.player_mut().inventory_mut().set_hotbar_item(5, ItemStack::empty());
+        game.select_hotbar_slot(5);
+        assert_eq!(game.selected_block_type(), BlockType::Air);
+    }
+
+    #[test]
+    fn middle_clicking_stone_selects_stone() {
+        let mut world = flat_world(10);
+        world.set_block(IVec3::new(0, 11, 2), BlockType::Stone);
+        let mut game = GameManager::new();
+        game.select_hotbar_slot(2);
+
+        assert!(game.pick_block(&ray_along_z(), &world));
+
+        assert_eq!(game.selected_block_type(), BlockType::Stone);
+        assert_eq!(game.player().selected_hotbar_slot(), 0);
+    }
+
+    #[test]
+    fn looking_straight_down_targets_the_top_of_the_block_below() {
+        let mut world = flat_world(10);
+        let block = IVec3::new(0, 12, 0);
+        world.set_block(block, BlockType::Stone);
+        let camera = Camera::new(Vec3::new(0.5, 14.5, 0.5), 0.0, -89.9, 1.0);
+        let mut game = GameManager::new();
+
+        game.update_target(&world, &camera);
+
+        assert_eq!(game.targeted_block(), Some((block, BlockType::Stone, Face::Top)));
+    }
+
+    #[test]
+    fn progress_is_kept_while_aim_jitters_across_the_same_block() {
+        let mut world = flat_world(10);
+        let wall = IVec3::new(0, 11, 2);
+        world.set_block(wall, BlockType::Stone);
+        let jittered = Ray { origin: Vec3::new(0.93, 11.08, 0.5), ..ray_along_z() };
+        let mut game = GameManager::new();
+        let quarter = BlockType::Stone.mining_time() / 4.0;
+
+        game.handle_block_breaking(&ray_along_z(), &mut world, quarter);
+        game.handle_block_breaking(&jittered, &mut world, quarter);
+
+        assert_eq!(game.breaking_target(), Some(wall));
+        assert!((game.breaking_progress() - 0.5).abs() < 1e-5);
+
+        // Moving onto the neighboring block starts over
+        world.set_block(wall + IVec3::X, BlockType::Stone);
+        let neighbor = Ray { origin: Vec3::new(1.5, 11.5, 0.5), ..ray_along_z() };
+        game.handle_block_breaking(&neighbor, &mut world, quarter);
+        assert_eq!(game.breaking_target(), Some(wall + IVec3::X));
+        assert!((game.breaking_progress() - 0.25).abs() < 1e-5);
+    }
+
+    #[test]
+    fn held_place_with_a_four_tick_cooldown_places_once_every_four_ticks() {
+        let mut world = flat_world(10);
+        let mut game = GameManager::new();
+        game.set_interaction_config(InteractionConfig {
+            place_cooldown_ticks: 4,
+            creative_place_cooldown_ticks: 4,
+            ..InteractionConfig::default()
+        });
+        game.select_block(BlockType::Stone, 0);
+
+        // Aim down at a fresh column each tick so every attempt has somewhere to go
+        let placed: Vec<bool> = (0..12)
+            .map(|tick| {
+                game.update(&world, 1.0 / 20.0);
+                let ray = Ray {
+                    origin: Vec3::new(tick as f32 + 3.5, 13.5, 8.5),
+                    direction: Vec3::NEG_Y,
+                    max_distance: REACH_DISTANCE,
+                };
+                game.handle_held_placement(&ray, &mut world)
+            })
+            .collect();
+
+        let ticks: Vec<usize> = placed.iter().enumerate().filter(|(_, &placed)| placed).map(|(tick, _)| tick).collect();
+        assert_eq!(ticks, [0, 4, 8]);
+    }
+
+    #[test]
+    fn teleporting_into_a_solid_column_lands_on_top_of_it() {
+        let mut world = flat_world(10);
+        for y in 11..=15 {
+            world.set_block(IVec3::new(5, y, 5), BlockType::Stone);
+        }
+        let mut camera = Camera::new(Vec3::ZERO, 0.0, 0.0, 1.0);
+        let mut game = GameManager::new();
+        game.player_mut().set_velocity(Vec3::new(3.0, -8.0, 1.0));
+
+        game.teleport(&mut world, &mut camera, IVec3::new(5, 12, 5));
+
+        assert_eq!(game.player().feet_position(), Vec3::new(5.5, 16.0, 5.5));
+        assert_eq!(game.player().velocity(), Vec3::ZERO);
+        assert_eq!(camera.position(), game.player().camera_position());
+    }
+}
@@ -1,21 +1,310 @@
-// Physics system placeholder
-// TODO: Implement proper physics with collision detection
+// Physics system: gravity, AABB collision against blocks and block contact queries
 
-use glam::Vec3;
+use glam::{IVec3, Vec3};
+use crate::world::{BlockType, World};
 
-pub struct Physics {
-    gravity: f32,
+/// Axis-aligned bounding box used for collision and contact queries
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
 }
 
-impl Physics {
-    pub fn new() -> Self {
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    /// Create a box of the given size whose bottom face is centered on `feet`
+    pub fn from_feet(feet: Vec3, width: f32, height: f32) -> Self {
+        let half = width / 2.0;
+        Self {
+            min: Vec3::new(feet.x - half, feet.y, feet.z - half),
+            max: Vec3::new(feet.x + half, feet.y + height, feet.z + half),
+        }
+    }
+
+    pub fn translated(&self, offset: Vec3) -> Self {
+        Self {
+            min: self.min + offset,
+            max: self.max + offset,
+        }
+    }
+
+    /// Grow the box by `amount` on every side
+    pub fn expanded(&self, amount: f32) -> Self {
+        Self {
+            min: self.min - Vec3::splat(amount),
+            max: self.max + Vec3::splat(amount),
+        }
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.x < other.max.x && self.max.x > other.min.x &&
+        self.min.y < other.max.y && self.max.y > other.min.y &&
+        self.min.z < other.max.z && self.max.z > other.min.z
+    }
+
+    /// Distance along a ray from `origin` in the unit `direction` to where it enters
+    /// the box, if that happens within `max_distance`. A ray starting inside the box
+    /// hits it at distance 0.
+    pub fn ray_distance(&self, origin: Vec3, direction: Vec3, max_distance: f32) -> Option<f32> {
+        let mut near = 0.0f32;
+        let mut far = max_distance;
+        for axis in 0..3 {
+            if direction[axis] == 0.0 {
+                if origin[axis] < self.min[axis] || origin[axis] > self.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let to_min = (self.min[axis] - origin[axis]) / direction[axis];
+            let to_max = (self.max[axis] - origin[axis]) / direction[axis];
+            near = near.max(to_min.min(to_max));
+            far = far.min(to_min.max(to_max));
+            if near > far {
+                return None;
+            }
+        }
+        Some(near)
+    }
+}
+
+/// Tolerance for treating a box as resting against a block face
+const COLLISION_EPSILON: f32 = 1e-4;
+
+/// Gravity and movement tuning shared by physics, the player and the camera
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhysicsConfig {
+    /// Downward acceleration in blocks/second²
+    pub gravity: f32,
+    /// Movement speeds in blocks/second
+    pub walking_speed: f32,
+    pub sprinting_speed: f32,
+    pub flying_speed: f32,
+    /// Upward velocity applied when jumping off the ground
+    pub jump_velocity: f32,
+    /// Fraction of normal gravity felt while in water or lava
+    pub water_gravity_scale: f32,
+    /// Maximum sinking speed in fluids
+    pub water_max_sink_speed: f32,
+    /// Upward velocity while holding jump in a fluid
+    pub swim_up_speed: f32,
+    /// Movement speed multipliers while submerged
+    pub water_speed_multiplier: f32,
+    pub lava_speed_multiplier: f32,
+    /// Snap horizontal velocity straight to the target instead of ramping
+    pub instant_movement: bool,
+    /// How quickly horizontal velocity approaches the target while moving (1/s)
+    pub acceleration: f32,
+    /// How quickly horizontal velocity decays once movement keys are released (1/s)
+    pub friction: f32,
+    /// Tallest ledge, in blocks, climbed by walking into it while on the ground:
+    /// enough for slabs and stairs but not full blocks
+    pub step_height: f32,
+    /// Tallest ledge the player climbs automatically while not sneaking, so a
+    /// single block doesn't stop them dead
+    pub auto_step_height: f32,
+    /// Speed, in blocks/second, at which the camera catches up after a step up
+    pub step_smoothing_speed: f32,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
         Self {
             gravity: 9.81,
+            walking_speed: 4.317, // Minecraft walking speed
+            sprinting_speed: 5.612, // Minecraft sprinting speed
+            flying_speed: 4.317,
+            jump_velocity: 5.0,
+            water_gravity_scale: 0.2,
+            water_max_sink_speed: 2.0,
+            swim_up_speed: 2.5,
+            water_speed_multiplier: 0.5,
+            lava_speed_multiplier: 0.5,
+            instant_movement: false,
+            acceleration: 12.0,
+            friction: 10.0,
+            step_height: 0.6,
+            auto_step_height: 1.0,
+            step_smoothing_speed: 4.0,
         }
     }
+}
+
+pub struct Physics {
+    config: PhysicsConfig,
+}
+
+impl Physics {
+    pub fn new() -> Self {
+        Self::with_config(PhysicsConfig::default())
+    }
+
+    pub fn with_config(config: PhysicsConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &PhysicsConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: PhysicsConfig) {
+        self.config = config;
+    }
 
     pub fn apply_gravity(&self, velocity: &mut Vec3, delta_time: f32) {
-        velocity.y -= self.gravity * delta_time;
+        velocity.y -= self.config.gravity * delta_time;
+    }
+
+    /// Collect every block overlapping or directly touching the given box
+    pub fn blocks_touching(&self, world: &World, aabb: &Aabb) -> Vec<BlockType> {
+        // Expand slightly so solid blocks the box rests against (e.g. cactus) count as contact
+        self.blocks_overlapping(world, &aabb.expanded(0.01))
+    }
+
+    /// Collect every distinct non-air block whose cell overlaps the given box
+    pub fn blocks_overlapping(&self, world: &World, aabb: &Aabb) -> Vec<BlockType> {
+        let mut blocks = Vec::new();
+
+        let (min, max) = Self::block_bounds(aabb);
+        for (_, block) in world.blocks_in_aabb(min, max) {
+            if block != BlockType::Air && !blocks.contains(&block) {
+                blocks.push(block);
+            }
+        }
+
+        blocks
+    }
+
+    /// Whether the box overlaps the collision box of any solid block
+    pub fn is_colliding(&self, world: &World, aabb: &Aabb) -> bool {
+        let (min, max) = Self::block_bounds(aabb);
+        world.blocks_in_aabb(min, max).any(|(pos, block)| {
+            block
+                .collision_boxes(world.block_state_at(pos).unwrap_or(0))
+                .into_iter()
+                .any(|(low, high)| Aabb::new(pos.as_vec3() + low, pos.as_vec3() + high).intersects(aabb))
+        })
+    }
+
+    /// Move a box through the world, stopping at solid blocks.
+    ///
+    /// Axes are resolved one at a time (Y first) and the velocity component of any
+    /// blocked axis is zeroed. A box on the ground that walks into a ledge no taller
+    /// than `step_height` climbs onto it instead. Returns the applied offset and
+    /// whether the box landed on the ground.
+    pub fn move_and_collide(&self, world: &World, aabb: &Aabb, velocity: &mut Vec3, delta_time: f32) -> (Vec3, bool) {
+        self.move_and_collide_stepping(world, aabb, velocity, delta_time, self.config.step_height)
+    }
+
+    /// Apply gravity then move a body through the world, the shared path for all entities
+    pub fn step_body(&self, world: &World, aabb: &Aabb, velocity: &mut Vec3, delta_time: f32) -> (Vec3, bool) {
+        self.apply_gravity(velocity, delta_time);
+        self.move_and_collide(world, aabb, velocity, delta_time)
+    }
+
+    /// `move_and_collide` with a custom step height, e.g. the player's auto-step
+    pub fn move_and_collide_stepping(
+        &self,
+        world: &World,
+        aabb: &Aabb,
+        velocity: &mut Vec3,
+        delta_time: f32,
+        step_height: f32,
+    ) -> (Vec3, bool) {
+        let desired = *velocity * delta_time;
+        let mut current = *aabb;
+        let mut offset = Vec3::ZERO;
+        let mut on_ground = false;
+
+        for axis in [1, 0, 2] {
+            if desired[axis] == 0.0 {
+                continue;
+            }
+
+            let allowed = self.clip_axis(world, &current, axis, desired[axis]);
+            let mut step = Vec3::ZERO;
+            step[axis] = allowed;
+
+            if allowed != desired[axis] {
+                let climbed = if axis != 1 && on_ground {
+                    self.step_up(world, &current, axis, desired[axis], allowed, step_height)
+                } else {
+                    None
+                };
+                match climbed {
+                    Some(climb) => step = climb,
+                    None => {
+                        if axis == 1 && desired[axis] < 0.0 {
+                            on_ground = true;
+                        }
+                        velocity[axis] = 0.0;
+                    }
+                }
+            }
+
+            current = current.translated(step);
+            offset += step;
+        }
+
+        (offset, on_ground)
+    }
+
+    /// Try moving `delta` along a horizontal axis from up to `step_height` higher,
+    /// then settle back down onto whatever was climbed. Returns the full offset if
+    /// that gets further than the `blocked` distance managed at the current height.
+    fn step_up(&self, world: &World, aabb: &Aabb, axis: usize, delta: f32, blocked: f32, step_height: f32) -> Option<Vec3> {
+        let rise = self.clip_axis(world, aabb, 1, step_height);
+        let forward = self.clip_axis(world, &aabb.translated(Vec3::Y * rise), axis, delta);
+        if forward.abs() <= blocked.abs() + COLLISION_EPSILON {
+            return None;
+        }
+
+        let mut climb = Vec3::Y * rise;
+        climb[axis] = forward;
+        climb.y += self.clip_axis(world, &aabb.translated(climb), 1, -rise);
+        Some(climb)
+    }
+
+    /// Limit movement along one axis so the box doesn't enter a solid block
+    fn clip_axis(&self, world: &World, aabb: &Aabb, axis: usize, delta: f32) -> f32 {
+        let mut swept = *aabb;
+        if delta > 0.0 {
+            swept.max[axis] += delta;
+        } else {
+            swept.min[axis] += delta;
+        }
+
+        let mut allowed = delta;
+        let (min, max) = Self::block_bounds(&swept);
+        for (pos, block) in world.blocks_in_aabb(min, max) {
+            // Slabs and stairs collide with only part of their cell
+            for (low, high) in block.collision_boxes(world.block_state_at(pos).unwrap_or(0)) {
+                let block_box = Aabb::new(pos.as_vec3() + low, pos.as_vec3() + high);
+
+                // Only blocks overlapping on the other two axes can stop this one
+                let overlaps = (0..3).filter(|&a| a != axis).all(|a| {
+                    aabb.min[a] < block_box.max[a] && aabb.max[a] > block_box.min[a]
+                });
+                if !overlaps {
+                    continue;
+                }
+
+                if delta > 0.0 && block_box.min[axis] >= aabb.max[axis] - COLLISION_EPSILON {
+                    allowed = allowed.min(block_box.min[axis] - aabb.max[axis]);
+                } else if delta < 0.0 && block_box.max[axis] <= aabb.min[axis] + COLLISION_EPSILON {
+                    allowed = allowed.max(block_box.max[axis] - aabb.min[axis]);
+                }
+            }
+        }
+
+        allowed
+    }
+
+    /// Inclusive range of block positions a box covers
+    fn block_bounds(aabb: &Aabb) -> (IVec3, IVec3) {
+        (aabb.min.floor().as_ivec3(), aabb.max.floor().as_ivec3())
     }
 }
 
@@ -23,4 +312,56 @@ impl Default for Physics {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::testing::flat_world;
+
+    /// Walk a player-sized box standing on the ground at x = 2.5 one tick towards +x,
+    /// into whatever is at x = 3
+    fn walk_into_obstacle(world: &World, step_height: f32) -> Vec3 {
+        let physics = Physics::new();
+        let player = Aabb::from_feet(Vec3::new(2.5, 11.0, 2.5), 0.6, 1.8);
+        let mut velocity = Vec3::new(4.0, -1.0, 0.0);
+        let (offset, _) = physics.move_and_collide_stepping(world, &player, &mut velocity, 0.1, step_height);
+        offset
+    }
+
+    #[test]
+    fn walking_into_a_slab_steps_up_onto_it() {
+        let mut world = flat_world(10);
+        world.set_block(IVec3::new(3, 11, 2), BlockType::Slab);
+
+        let offset = walk_into_obstacle(&world, Physics::new().config().step_height);
+
+        assert!((offset.x - 0.4).abs() < 1e-4, "moved {offset}");
+        assert!((offset.y - 0.5).abs() < 1e-4, "moved {offset}");
+    }
+
+    #[test]
+    fn auto_step_climbs_one_block_but_not_two() {
+        let auto_step = PhysicsConfig::default().auto_step_height;
+        let mut world = flat_world(10);
+        world.set_block(IVec3::new(3, 11, 2), BlockType::Stone);
+
+        let offset = walk_into_obstacle(&world, auto_step);
+        assert!((offset.y - 1.0).abs() < 1e-4 && offset.x > 0.2, "moved {offset}");
+
+        world.set_block(IVec3::new(3, 12, 2), BlockType::Stone);
+        let offset = walk_into_obstacle(&world, auto_step);
+        assert_eq!(offset.y, 0.0);
+        assert!(offset.x <= 0.2 + 1e-4, "walked through the wall: {offset}");
+    }
+
+    #[test]
+    fn sneaking_step_height_does_not_climb_a_full_block() {
+        let mut world = flat_world(10);
+        world.set_block(IVec3::new(3, 11, 2), BlockType::Stone);
+
+        let offset = walk_into_obstacle(&world, PhysicsConfig::default().step_height);
+
+        assert_eq!(offset.y, 0.0);
+    }
+}
@@ -1,10 +1,48 @@
 use glam::Vec3;
+use serde::{Deserialize, Serialize};
 use crate::game::inventory::Inventory;
+use crate::game::physics::{Aabb, PhysicsConfig};
+use crate::world::{BlockType, Entity, EntityKind, World};
+
+/// Size of the player's collision box and where the camera sits in it, in blocks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayerDimensions {
+    pub width: f32,
+    pub height: f32,
+    /// Height of the camera above the player's feet
+    pub eye_height: f32,
+    /// Height and eye height while sneaking
+    pub sneak_height: f32,
+    pub sneak_eye_height: f32,
+}
+
+impl Default for PlayerDimensions {
+    fn default() -> Self {
+        Self {
+            width: 0.6,
+            height: 1.8,
+            eye_height: 1.62,
+            sneak_height: 1.5,
+            sneak_eye_height: 1.27,
+        }
+    }
+}
+
+/// Seconds between consecutive contact damage hits
+const CONTACT_DAMAGE_COOLDOWN: f32 = 0.5;
+/// How long the player keeps burning after leaving lava
+const FIRE_DURATION: f32 = 4.0;
+/// Damage dealt per second while burning
+const FIRE_DAMAGE_PER_SECOND: f32 = 1.0;
 
 /// Player state and data
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Player {
     position: Vec3,
     velocity: Vec3,
+    // Horizontal velocity the player is trying to reach this frame
+    #[serde(skip)]
+    movement_target: Vec3,
     health: f32,
     max_health: f32,
     hunger: f32,
@@ -16,9 +54,32 @@ pub struct Player {
     
     // Player properties
     reach_distance: f32,
-    walking_speed: f32,
-    sprinting_speed: f32,
     flying: bool,
+    #[serde(skip)]
+    dimensions: PlayerDimensions,
+
+    // Environmental effects
+    #[serde(skip)]
+    contact_damage_cooldown: f32,
+    fire_time: f32,
+    #[serde(skip)]
+    fire_damage_timer: f32,
+    // Recomputed from the surrounding blocks on the next update
+    #[serde(skip)]
+    in_lava: bool,
+    #[serde(skip)]
+    in_water: bool,
+    #[serde(skip)]
+    on_ground: bool,
+    // Sneaking turns off auto-step so the player can walk along ledges
+    #[serde(skip)]
+    sneaking: bool,
+    // How far the camera still trails below the eye after stepping up
+    #[serde(skip)]
+    step_lag: f32,
+    // Camera position before the latest tick, or `None` right after a jump in position
+    #[serde(skip)]
+    previous_camera_position: Option<Vec3>,
 }
 
 impl Player {
@@ -26,6 +87,7 @@ impl Player {
         Self {
             position,
             velocity: Vec3::ZERO,
+            movement_target: Vec3::ZERO,
             health: 20.0,
             max_health: 20.0,
             hunger: 20.0,
@@ -35,25 +97,86 @@ impl Player {
             inventory: Inventory::new(),
             selected_hotbar_slot: 0,
             reach_distance: 5.0,
-            walking_speed: 4.317, // Minecraft walking speed
-            sprinting_speed: 5.612, // Minecraft sprinting speed
             flying: false,
+            dimensions: PlayerDimensions::default(),
+            contact_damage_cooldown: 0.0,
+            fire_time: 0.0,
+            fire_damage_timer: 0.0,
+            in_lava: false,
+            in_water: false,
+            on_ground: false,
+            sneaking: false,
+            step_lag: 0.0,
+            previous_camera_position: None,
         }
     }
 
-    pub fn update(&mut self, delta_time: f32) {
-        // Update player physics (simplified)
-        self.position += self.velocity * delta_time;
-        
-        // Apply gravity if not flying
+    pub fn update(&mut self, world: &World, delta_time: f32) {
+        self.previous_camera_position = Some(self.camera_position());
+        let physics = world.physics();
+        let config = physics.config();
+
+        // Fluids change how gravity and movement behave
+        let fluids = physics.blocks_overlapping(world, &self.aabb());
+        self.in_water = fluids.contains(&BlockType::Water);
+        self.in_lava = fluids.contains(&BlockType::Lava);
+
         if !self.flying {
-            self.velocity.y -= 9.81 * delta_time; // Gravity
+            self.update_horizontal_velocity(config, delta_time);
+
+            if self.in_water || self.in_lava {
+                // Buoyancy: weaker gravity and a capped sinking speed
+                physics.apply_gravity(&mut self.velocity, delta_time * config.water_gravity_scale);
+                self.velocity.y = self.velocity.y.max(-config.water_max_sink_speed);
+            } else {
+                physics.apply_gravity(&mut self.velocity, delta_time);
+            }
+
+            let step_height = if self.sneaking { config.step_height } else { config.auto_step_height };
+            let (offset, on_ground) =
+                physics.move_and_collide_stepping(world, &self.aabb(), &mut self.velocity, delta_time, step_height);
+
+            // Rising while grounded means a step up; let the camera follow smoothly
+            if self.on_ground && offset.y > 0.0 {
+                self.step_lag += offset.y;
+            }
+            self.step_lag = (self.step_lag - config.step_smoothing_speed * delta_time).max(0.0);
+
+            self.position += offset;
+            self.on_ground = on_ground;
         }
         
         // Update inventory
         self.inventory.update(delta_time);
     }
 
+    /// Ramp horizontal velocity towards the movement target, or decay it with
+    /// friction when there is no input
+    fn update_horizontal_velocity(&mut self, config: &PhysicsConfig, delta_time: f32) {
+        let target = Vec3::new(self.movement_target.x, 0.0, self.movement_target.z);
+        let current = Vec3::new(self.velocity.x, 0.0, self.velocity.z);
+
+        let horizontal = if config.instant_movement {
+            target
+        } else {
+            let rate = if target == Vec3::ZERO { config.friction } else { config.acceleration };
+            // Exponential approach never overshoots the target
+            target + (current - target) * (-rate * delta_time).exp()
+        };
+
+        self.velocity.x = horizontal.x;
+        self.velocity.z = horizontal.z;
+    }
+
+    /// Set the horizontal velocity the player should move towards
+    pub fn set_movement_target(&mut self, target: Vec3) {
+        self.movement_target = target;
+    }
+
+    pub fn movement_target(&self) -> Vec3 {
+        self.movement_target
+    }
+
     // Position and movement
     pub fn position(&self) -> Vec3 {
         self.position
@@ -61,6 +184,79 @@ impl Player {
 
     pub fn set_position(&mut self, position: Vec3) {
         self.position = position;
+        self.previous_camera_position = None;
+    }
+
+    /// Where the camera should be: the eye, eased in after stepping up a block
+    pub fn camera_position(&self) -> Vec3 {
+        self.position - Vec3::Y * self.step_lag
+    }
+
+    /// Camera position `alpha` of the way from the previous tick to the latest one,
+    /// so frames drawn between ticks move smoothly
+    pub fn interpolated_camera_position(&self, alpha: f32) -> Vec3 {
+        let current = self.camera_position();
+        self.previous_camera_position
+            .map_or(current, |previous| previous.lerp(current, alpha.clamp(0.0, 1.0)))
+    }
+
+    /// Position of the player's feet (position is the eye/camera point)
+    pub fn feet_position(&self) -> Vec3 {
+        self.position - Vec3::Y * self.eye_height()
+    }
+
+    /// Move the player so their feet rest at `feet`
+    pub fn set_feet_position(&mut self, feet: Vec3) {
+        self.position = feet + Vec3::Y * self.eye_height();
+    }
+
+    pub fn dimensions(&self) -> &PlayerDimensions {
+        &self.dimensions
+    }
+
+    /// Change the player's size, keeping their feet where they are
+    pub fn set_dimensions(&mut self, dimensions: PlayerDimensions) {
+        let feet = self.feet_position();
+        self.dimensions = dimensions;
+        self.set_feet_position(feet);
+    }
+
+    /// Current collision box height, lower while sneaking
+    pub fn height(&self) -> f32 {
+        if self.sneaking { self.dimensions.sneak_height } else { self.dimensions.height }
+    }
+
+    /// Current camera height above the feet, lower while sneaking
+    pub fn eye_height(&self) -> f32 {
+        if self.sneaking { self.dimensions.sneak_eye_height } else { self.dimensions.eye_height }
+    }
+
+    /// Move the player's feet to `feet` with no momentum
+    pub fn teleport(&mut self, feet: Vec3) {
+        self.set_feet_position(feet);
+        self.velocity = Vec3::ZERO;
+        self.movement_target = Vec3::ZERO;
+        self.step_lag = 0.0;
+        self.previous_camera_position = None;
+    }
+
+    /// Put the player back at the spawn point with full health and no momentum
+    pub fn respawn(&mut self, spawn_feet: Vec3) {
+        self.teleport(spawn_feet);
+        self.health = self.max_health;
+        self.hunger = self.max_hunger;
+        self.fire_time = 0.0;
+        self.fire_damage_timer = 0.0;
+    }
+
+    /// Collision box of the player at its current position
+    pub fn aabb(&self) -> Aabb {
+        self.aabb_at(self.feet_position())
+    }
+
+    /// Collision box the player would have with their feet at `feet`
+    pub fn aabb_at(&self, feet: Vec3) -> Aabb {
+        Aabb::from_feet(feet, self.dimensions.width, self.height())
     }
 
     pub fn velocity(&self) -> Vec3 {
@@ -92,6 +288,95 @@ impl Player {
         self.health = (self.health + amount).min(self.max_health);
     }
 
+    /// Apply damage and burning from the blocks around the player. `touching` also
+    /// includes solid blocks the player rests against, `overlapping` only the blocks
+    /// inside the player's box.
+    pub fn apply_block_contacts(&mut self, touching: &[BlockType], overlapping: &[BlockType], delta_time: f32) {
+        self.contact_damage_cooldown = (self.contact_damage_cooldown - delta_time).max(0.0);
+
+        // Direct contact damage at a fixed hit rate: solid blocks (cactus) hurt on
+        // contact, fluids (lava) only once the player is inside them
+        let contact_damage = touching
+            .iter()
+            .filter(|block| block.is_solid())
+            .chain(overlapping)
+            .map(|block| block.contact_damage())
+            .fold(0.0, f32::max);
+        if contact_damage > 0.0 && self.contact_damage_cooldown <= 0.0 {
+            self.damage(contact_damage);
+            self.contact_damage_cooldown = CONTACT_DAMAGE_COOLDOWN;
+        }
+
+        // Lava sets the player on fire, water puts it out
+        if overlapping.contains(&BlockType::Lava) {
+            self.fire_time = FIRE_DURATION;
+        } else if overlapping.contains(&BlockType::Water) {
+            self.fire_time = 0.0;
+            self.fire_damage_timer = 0.0;
+        } else if self.fire_time > 0.0 {
+            self.fire_time = (self.fire_time - delta_time).max(0.0);
+            self.fire_damage_timer += delta_time;
+            while self.fire_damage_timer >= 1.0 {
+                self.fire_damage_timer -= 1.0;
+                self.damage(FIRE_DAMAGE_PER_SECOND);
+            }
+            if self.fire_time == 0.0 {
+                self.fire_damage_timer = 0.0;
+            }
+        }
+    }
+
+    pub fn is_on_fire(&self) -> bool {
+        self.fire_time > 0.0
+    }
+
+    pub fn is_in_lava(&self) -> bool {
+        self.in_lava
+    }
+
+    pub fn is_in_water(&self) -> bool {
+        self.in_water
+    }
+
+    pub fn is_on_ground(&self) -> bool {
+        self.on_ground
+    }
+
+    pub fn is_sneaking(&self) -> bool {
+        self.sneaking
+    }
+
+    /// Start or stop sneaking. The feet stay put while the box and eye lower or rise.
+    pub fn set_sneaking(&mut self, sneaking: bool) {
+        let feet = self.feet_position();
+        self.sneaking = sneaking;
+        self.set_feet_position(feet);
+    }
+
+    /// Multiplier applied to movement speed by the surrounding blocks
+    pub fn speed_multiplier(&self, config: &PhysicsConfig) -> f32 {
+        if self.in_lava {
+            config.lava_speed_multiplier
+        } else if self.in_water {
+            config.water_speed_multiplier
+        } else {
+            1.0
+        }
+    }
+
+    /// Jump off the ground, or swim upwards while in a fluid
+    pub fn jump(&mut self, config: &PhysicsConfig) {
+        if self.flying {
+            return;
+        }
+        if self.in_water || self.in_lava {
+            self.velocity.y = config.swim_up_speed;
+        } else if self.on_ground {
+            self.velocity.y = config.jump_velocity;
+            self.on_ground = false;
+        }
+    }
+
     pub fn hunger(&self) -> f32 {
         self.hunger
     }
@@ -142,14 +427,6 @@ impl Player {
         self.reach_distance
     }
 
-    pub fn walking_speed(&self) -> f32 {
-        self.walking_speed
-    }
-
-    pub fn sprinting_speed(&self) -> f32 {
-        self.sprinting_speed
-    }
-
     pub fn is_flying(&self) -> bool {
         self.flying
     }
@@ -159,9 +436,231 @@ impl Player {
         if flying {
             self.velocity.y = 0.0; // Stop falling when starting to fly
         }
+        self.movement_target = Vec3::ZERO;
     }
 
     pub fn is_alive(&self) -> bool {
         self.health > 0.0
     }
-}
\ No newline at end of file
+}
+
+/// The player shares the entity interface and collision path, but is owned by the game manager
+impl Entity for Player {
+    fn kind(&self) -> EntityKind {
+        EntityKind::Player
+    }
+
+    fn position(&self) -> Vec3 {
+        self.feet_position()
+    }
+
+    fn velocity(&self) -> Vec3 {
+        self.velocity
+    }
+
+    fn aabb(&self) -> Aabb {
+        Player::aabb(self)
+    }
+
+    fn update(&mut self, world: &World, delta_time: f32) {
+        Player::update(self, world, delta_time)
+    }
+
+    fn damage(&mut self, amount: f32) {
+        Player::damage(self, amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::IVec3;
+    use crate::world::testing::flat_world;
+
+    const STEP: f32 = 0.05;
+
+    fn run_contacts(player: &mut Player, overlapping: &[BlockType], seconds: f32) {
+        for _ in 0..(seconds / STEP).round() as usize {
+            player.apply_block_contacts(overlapping, overlapping, STEP);
+        }
+    }
+
+    #[test]
+    fn standing_in_lava_deals_periodic_damage() {
+        let mut player = Player::new(Vec3::ZERO);
+
+        player.apply_block_contacts(&[BlockType::Lava], &[BlockType::Lava], STEP);
+        assert_eq!(player.health(), 16.0);
+
+        // No second hit until the cooldown has passed
+        run_contacts(&mut player, &[BlockType::Lava], CONTACT_DAMAGE_COOLDOWN - 2.0 * STEP);
+        assert_eq!(player.health(), 16.0);
+
+        run_contacts(&mut player, &[BlockType::Lava], 2.0 * STEP);
+        assert_eq!(player.health(), 12.0);
+        assert!(player.is_on_fire());
+    }
+
+    #[test]
+    fn burning_lingers_after_leaving_lava_then_stops() {
+        let mut player = Player::new(Vec3::ZERO);
+        player.apply_block_contacts(&[BlockType::Lava], &[BlockType::Lava], STEP);
+        let after_lava = player.health();
+
+        run_contacts(&mut player, &[], 2.0);
+        assert!(player.is_on_fire());
+        assert_eq!(player.health(), after_lava - 2.0 * FIRE_DAMAGE_PER_SECOND);
+
+        run_contacts(&mut player, &[], FIRE_DURATION);
+        assert!(!player.is_on_fire());
+        let burnt_out = player.health();
+
+        run_contacts(&mut player, &[], 2.0);
+        assert_eq!(player.health(), burnt_out);
+    }
+
+    #[test]
+    fn lava_next_to_the_player_does_not_ignite() {
+        let mut player = Player::new(Vec3::ZERO);
+
+        player.apply_block_contacts(&[BlockType::Lava], &[], STEP);
+
+        assert_eq!(player.health(), player.max_health());
+        assert!(!player.is_on_fire());
+    }
+
+    /// A flat world with a pool of water from just above the ground at y = 10 up to y = 20
+    fn world_with_pool() -> World {
+        let mut world = flat_world(10);
+        for x in -3..=3 {
+            for y in 11..=20 {
+                for z in -3..=3 {
+                    world.set_block(IVec3::new(x, y, z), BlockType::Water);
+                }
+            }
+        }
+        world
+    }
+
+    #[test]
+    fn falling_into_water_is_damped() {
+        let world = world_with_pool();
+        let config = *world.physics().config();
+        let mut player = Player::new(Vec3::ZERO);
+        player.teleport(Vec3::new(0.5, 15.0, 0.5));
+        player.set_velocity(Vec3::new(0.0, -10.0, 0.0));
+
+        player.update(&world, 0.05);
+
+        assert!(player.is_in_water());
+        assert_eq!(player.velocity().y, -config.water_max_sink_speed);
+    }
+
+    #[test]
+    fn jumping_in_water_swims_upwards() {
+        let world = world_with_pool();
+        let config = *world.physics().config();
+        let mut player = Player::new(Vec3::ZERO);
+        player.teleport(Vec3::new(0.5, 15.0, 0.5));
+        player.update(&world, 0.05);
+        let start = player.feet_position().y;
+
+        player.jump(&config);
+        player.update(&world, 0.05);
+
+        assert!(player.velocity().y > 0.0);
+        assert!(player.feet_position().y > start);
+    }
+
+    #[test]
+    fn zero_gravity_keeps_vertical_velocity() {
+        let mut world = flat_world(10);
+        world.set_physics_config(PhysicsConfig { gravity: 0.0, ..PhysicsConfig::default() });
+        let mut player = Player::new(Vec3::ZERO);
+        player.teleport(Vec3::new(0.5, 40.0, 0.5));
+        player.set_velocity(Vec3::new(0.0, 1.5, 0.0));
+
+        for _ in 0..10 {
+            player.update(&world, STEP);
+        }
+
+        assert!(!player.is_flying());
+        assert_eq!(player.velocity().y, 1.5);
+    }
+
+    fn horizontal_speed(player: &Player) -> f32 {
+        Vec3::new(player.velocity().x, 0.0, player.velocity().z).length()
+    }
+
+    #[test]
+    fn velocity_ramps_up_without_overshooting_then_decays() {
+        let config = PhysicsConfig::default();
+        let target = config.walking_speed;
+        let mut player = Player::new(Vec3::ZERO);
+        player.set_movement_target(Vec3::new(target, 0.0, 0.0));
+
+        let mut previous = 0.0;
+        for _ in 0..20 {
+            player.update_horizontal_velocity(&config, STEP);
+            let speed = horizontal_speed(&player);
+            assert!(speed > previous && speed <= target);
+            previous = speed;
+        }
+        assert!(target - previous < 0.01 * target);
+
+        player.set_movement_target(Vec3::ZERO);
+        for _ in 0..40 {
+            player.update_horizontal_velocity(&config, STEP);
+        }
+        assert!(horizontal_speed(&player) < 0.01);
+    }
+
+    #[test]
+    fn instant_mode_snaps_to_the_target() {
+        let config = PhysicsConfig { instant_movement: true, ..PhysicsConfig::default() };
+        let mut player = Player::new(Vec3::ZERO);
+        player.set_movement_target(Vec3::new(0.0, 0.0, config.sprinting_speed));
+
+        player.update_horizontal_velocity(&config, STEP);
+        assert_eq!(player.velocity().z, config.sprinting_speed);
+
+        player.set_movement_target(Vec3::ZERO);
+        player.update_horizontal_velocity(&config, STEP);
+        assert_eq!(horizontal_speed(&player), 0.0);
+    }
+
+    #[test]
+    fn camera_interpolates_between_ticks_and_snaps_on_teleport() {
+        let world = flat_world(10);
+        let mut player = Player::new(Vec3::ZERO);
+        player.teleport(Vec3::new(0.5, 20.0, 0.5));
+        let before = player.camera_position();
+        assert_eq!(player.interpolated_camera_position(0.0), before);
+
+        player.update(&world, STEP);
+        let after = player.camera_position();
+        assert!(after.y < before.y);
+        assert_eq!(player.interpolated_camera_position(0.0), before);
+        assert_eq!(player.interpolated_camera_position(1.0), after);
+        assert_eq!(player.interpolated_camera_position(0.5), before.lerp(after, 0.5));
+
+        player.teleport(Vec3::new(40.5, 11.0, 0.5));
+        assert_eq!(player.interpolated_camera_position(0.0), player.camera_position());
+    }
+
+    #[test]
+    fn aabb_corners_follow_the_configured_dimensions() {
+        let mut player = Player::new(Vec3::ZERO);
+        let dimensions = PlayerDimensions { width: 0.8, height: 2.0, ..PlayerDimensions::default() };
+        player.set_dimensions(dimensions);
+        let feet = Vec3::new(10.0, 64.0, -3.0);
+
+        let aabb = player.aabb_at(feet);
+
+        assert_eq!(aabb.min, Vec3::new(9.6, 64.0, -3.4));
+        assert_eq!(aabb.max, Vec3::new(10.4, 66.0, -2.6));
+
+        player.set_sneaking(true);
+        assert_eq!(player.aabb_at(feet).max.y, 64.0 + dimensions.sneak_height);
+    }
+}
@@ -49,6 +49,11 @@ impl InputManager {
             WindowEvent::CursorMoved { position, .. } => {
                 self.handle_mouse_movement(position.x, position.y);
             },
+            WindowEvent::CursorLeft { .. } => {
+                // The cursor re-enters wherever it likes; measuring from where it left
+                // would turn the gap into a camera jump
+                self.last_mouse_position = None;
+            },
             _ => {}
         }
     }
@@ -86,6 +91,8 @@ impl InputManager {
     }
 
     fn handle_mouse_movement(&mut self, x: f64, y: f64) {
+        // The first movement after startup, a capture change or the cursor re-entering
+        // the window only establishes the reference position, so it never produces a jump
         if let Some((last_x, last_y)) = self.last_mouse_position {
             self.mouse_delta = (x - last_x, y - last_y);
         } else {
@@ -146,10 +153,12 @@ impl InputManager {
 
     // Mouse capture
     pub fn set_mouse_captured(&mut self, captured: bool) {
-        self.mouse_captured = captured;
-        if captured {
-            self.mouse_delta = (0.0, 0.0);
+        if captured != self.mouse_captured {
+            // Forget the old cursor position so the next movement doesn't measure from it
+            self.last_mouse_position = None;
         }
+        self.mouse_captured = captured;
+        self.mouse_delta = (0.0, 0.0);
     }
 
     pub fn is_mouse_captured(&self) -> bool {
@@ -185,14 +194,33 @@ impl InputManager {
         self.is_key_pressed(KeyCode::ControlLeft)
     }
 
+    pub fn zoom(&self) -> bool {
+        self.is_key_pressed(KeyCode::KeyC)
+    }
+
     pub fn break_block(&self) -> bool {
         self.is_mouse_button_pressed(MouseButton::Left)
     }
 
+    /// Left click pressed this frame: hit the mob under the crosshair
+    pub fn attack(&self) -> bool {
+        self.is_mouse_button_just_pressed(MouseButton::Left)
+    }
+
     pub fn place_block(&self) -> bool {
         self.is_mouse_button_pressed(MouseButton::Right)
     }
 
+    /// Right click pressed this frame: use a door, lever or button
+    pub fn use_block(&self) -> bool {
+        self.is_mouse_button_just_pressed(MouseButton::Right)
+    }
+
+    /// Select the targeted block into the hotbar
+    pub fn pick_block(&self) -> bool {
+        self.is_mouse_button_just_pressed(MouseButton::Middle)
+    }
+
     pub fn open_inventory(&self) -> bool {
         self.is_key_just_pressed(KeyCode::KeyE)
     }
@@ -201,6 +229,15 @@ impl InputManager {
         self.is_key_just_pressed(KeyCode::F3)
     }
 
+    pub fn toggle_fullscreen(&self) -> bool {
+        self.is_key_just_pressed(KeyCode::F11)
+    }
+
+    /// Debug command: regenerate the chunk the player is in
+    pub fn regenerate_chunk(&self) -> bool {
+        self.is_key_just_pressed(KeyCode::F6)
+    }
+
     pub fn escape(&self) -> bool {
         self.is_key_just_pressed(KeyCode::Escape)
     }
@@ -237,4 +274,52 @@ impl Default for InputManager {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_movement_after_capture_has_no_delta() {
+        let mut input = InputManager::new();
+        input.handle_mouse_movement(10.0, 10.0);
+        input.update();
+
+        input.set_mouse_captured(true);
+        input.handle_mouse_movement(500.0, 300.0);
+        assert_eq!(input.mouse_delta(), (0.0, 0.0));
+
+        input.update();
+        input.handle_mouse_movement(503.0, 296.0);
+        assert_eq!(input.mouse_delta(), (3.0, -4.0));
+    }
+
+    #[test]
+    fn very_first_movement_has_no_delta() {
+        let mut input = InputManager::new();
+        input.set_mouse_captured(true);
+
+        input.handle_mouse_movement(640.0, 360.0);
+
+        assert_eq!(input.mouse_delta(), (0.0, 0.0));
+    }
+
+    #[test]
+    fn movement_after_cursor_left_has_no_delta() {
+        // SAFETY: the dummy id is only compared, never handed back to the platform
+        let device_id = unsafe { winit::event::DeviceId::dummy() };
+        let moved = |x: f64, y: f64| WindowEvent::CursorMoved {
+            device_id,
+            position: winit::dpi::PhysicalPosition::new(x, y),
+        };
+        let mut input = InputManager::new();
+        input.handle_event(&moved(100.0, 100.0));
+        input.update();
+
+        input.handle_event(&WindowEvent::CursorLeft { device_id });
+        input.handle_event(&moved(700.0, 20.0));
+
+        assert_eq!(input.mouse_delta(), (0.0, 0.0));
+    }
+}
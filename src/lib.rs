@@ -0,0 +1,9 @@
+pub mod engine;
+pub mod game;
+pub mod world;
+pub mod rendering;
+pub mod input;
+pub mod audio;
+pub mod ui;
+pub mod networking;
+pub mod utils;
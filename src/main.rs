@@ -2,17 +2,7 @@ use anyhow::Result;
 use env_logger;
 use log::info;
 
-mod engine;
-mod game;
-mod world;
-mod rendering;
-mod input;
-mod audio;
-mod ui;
-mod networking;
-mod utils;
-
-use engine::Engine;
+use minecraft_clone::engine::Engine;
 
 fn main() -> Result<()> {
     // Initialize logging
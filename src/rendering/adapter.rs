@@ -0,0 +1,92 @@
+use anyhow::{bail, Result};
+use log::{info, warn};
+
+/// Which GPU to render with, and how to sample textures on it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GraphicsSettings {
+    /// Preferred kind of GPU on machines with more than one. The default leaves
+    /// the choice to wgpu.
+    pub power_preference: wgpu::PowerPreference,
+    /// Fall back to a software rasterizer when no hardware adapter works
+    pub allow_software_fallback: bool,
+    /// Anisotropic filtering level for the block atlas, clamped to what the
+    /// adapter supports. 1 turns it off.
+    pub anisotropy: u16,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            power_preference: wgpu::PowerPreference::default(),
+            allow_software_fallback: true,
+            anisotropy: 1,
+        }
+    }
+}
+
+/// Find an adapter that can draw to `surface` (any adapter without one): the
+/// preferred kind first, then the other kind of hardware adapter, then a software
+/// one if allowed
+pub async fn select_adapter(
+    instance: &wgpu::Instance,
+    surface: Option<&wgpu::Surface<'_>>,
+    settings: &GraphicsSettings,
+) -> Result<wgpu::Adapter> {
+    let fallback_preference = match settings.power_preference {
+        wgpu::PowerPreference::LowPower => wgpu::PowerPreference::HighPerformance,
+        _ => wgpu::PowerPreference::LowPower,
+    };
+    let mut attempts = vec![(settings.power_preference, false), (fallback_preference, false)];
+    if settings.allow_software_fallback {
+        attempts.push((wgpu::PowerPreference::None, true));
+    }
+
+    for (power_preference, force_fallback_adapter) in attempts {
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference,
+                compatible_surface: surface,
+                force_fallback_adapter,
+            })
+            .await;
+
+        match adapter {
+            Some(adapter) => {
+                let adapter_info = adapter.get_info();
+                info!(
+                    "Using {:?} adapter \"{}\" ({:?}, {:?})",
+                    power_preference, adapter_info.name, adapter_info.device_type, adapter_info.backend
+                );
+                return Ok(adapter);
+            }
+            None if force_fallback_adapter => warn!("No software graphics adapter available"),
+            None => warn!("No {:?} graphics adapter available", power_preference),
+        }
+    }
+
+    bail!(
+        "No graphics adapter can draw to this window (tried {:?} and {:?} hardware adapters{}). \
+         Check that graphics drivers with Vulkan, Metal, DirectX 12 or OpenGL support are installed.",
+        settings.power_preference,
+        fallback_preference,
+        if settings.allow_software_fallback { " and a software adapter" } else { "" },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selection_finds_an_adapter_or_explains_why_not() {
+        let instance = wgpu::Instance::default();
+
+        // Machines without a GPU or drivers can't get an adapter; they must still
+        // get an error that says what to check
+        if let Err(error) = pollster::block_on(select_adapter(&instance, None, &GraphicsSettings::default())) {
+            let message = error.to_string();
+            assert!(message.contains("No graphics adapter"), "unhelpful error: {message}");
+            assert!(message.contains("drivers"), "unhelpful error: {message}");
+        }
+    }
+}
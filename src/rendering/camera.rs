@@ -1,4 +1,36 @@
-use glam::{Mat4, Vec3};
+use glam::{Mat4, Vec3, Vec4};
+use crate::game::PhysicsConfig;
+
+/// How raw look input (mouse or controller) turns into camera rotation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LookSettings {
+    /// Degrees of rotation per unit of input
+    pub sensitivity: f32,
+    /// Exponent applied to each axis' input before scaling. 1.0 is linear; above 1
+    /// slow movements turn less for fine aim while fast flicks still turn far.
+    pub curve_exponent: f32,
+    /// Flip vertical look, so pushing up looks down
+    pub invert_y: bool,
+}
+
+impl Default for LookSettings {
+    fn default() -> Self {
+        Self {
+            sensitivity: 0.1,
+            curve_exponent: 1.0,
+            invert_y: false,
+        }
+    }
+}
+
+impl LookSettings {
+    /// Apply the curve and invert-Y to a look delta, before sensitivity scaling
+    pub fn shape(&self, x: f32, y: f32) -> (f32, f32) {
+        let curve = |value: f32| value.signum() * value.abs().powf(self.curve_exponent);
+        let y = if self.invert_y { -y } else { y };
+        (curve(x), curve(y))
+    }
+}
 
 /// 3D camera with perspective projection and FPS-style controls
 #[derive(Clone)]
@@ -7,13 +39,16 @@ pub struct Camera {
     yaw: f32,
     pitch: f32,
     fov: f32,
+    /// Narrow field of view used while the zoom key is held
+    zoom_fov: f32,
+    zoomed: bool,
     aspect: f32,
     near: f32,
     far: f32,
     
     // Movement speed
     move_speed: f32,
-    mouse_sensitivity: f32,
+    look: LookSettings,
     
     // Cached vectors
     front: Vec3,
@@ -29,11 +64,13 @@ impl Camera {
             yaw,
             pitch,
             fov: 70.0,
+            zoom_fov: 30.0,
+            zoomed: false,
             aspect,
             near: 0.1,
             far: 1000.0,
-            move_speed: 4.317, // Minecraft walking speed (blocks/second)
-            mouse_sensitivity: 0.1,
+            move_speed: PhysicsConfig::default().flying_speed,
+            look: LookSettings::default(),
             front: Vec3::ZERO,
             up: Vec3::ZERO,
             right: Vec3::ZERO,
@@ -49,12 +86,25 @@ impl Camera {
         proj * view
     }
 
+    /// View-projection with the camera's translation stripped, so geometry drawn with
+    /// it (the skybox) stays centered on the viewer
+    pub fn build_sky_view_projection_matrix(&self) -> Mat4 {
+        self.projection_matrix() * self.sky_view_matrix()
+    }
+
+    /// The view matrix's rotation alone, with a zero translation column
+    pub fn sky_view_matrix(&self) -> Mat4 {
+        let mut view = self.view_matrix();
+        view.w_axis = Vec4::W;
+        view
+    }
+
     pub fn view_matrix(&self) -> Mat4 {
         Mat4::look_at_rh(self.position, self.position + self.front, self.up)
     }
 
     pub fn projection_matrix(&self) -> Mat4 {
-        Mat4::perspective_rh(self.fov.to_radians(), self.aspect, self.near, self.far)
+        Mat4::perspective_rh(self.effective_fov().to_radians(), self.aspect, self.near, self.far)
     }
 
     pub fn process_keyboard(&mut self, direction: CameraMovement, delta_time: f32) {
@@ -70,9 +120,24 @@ impl Camera {
         }
     }
 
-    pub fn process_mouse_movement(&mut self, mut xoffset: f32, mut yoffset: f32, constrain_pitch: bool) {
-        xoffset *= self.mouse_sensitivity;
-        yoffset *= self.mouse_sensitivity;
+    /// Move by a whole frame's accumulated intent. `forward` and `right` are in [-1, 1];
+    /// their combined direction is normalized so diagonals aren't faster than a single
+    /// axis. Vertical movement (`up`) is applied separately.
+    pub fn process_movement(&mut self, forward: f32, right: f32, up: f32, delta_time: f32) {
+        let velocity = self.move_speed * delta_time;
+
+        let planar = (self.front * forward + self.right * right).normalize_or_zero();
+        self.position += planar * velocity;
+        self.position += self.world_up * up.clamp(-1.0, 1.0) * velocity;
+    }
+
+    /// Turn the camera by a look delta. Mouse and controller input both come
+    /// through here so they share the look settings.
+    pub fn process_mouse_movement(&mut self, xoffset: f32, yoffset: f32, constrain_pitch: bool) {
+        let (xoffset, yoffset) = self.look.shape(xoffset, yoffset);
+        let sensitivity = self.effective_mouse_sensitivity();
+        let xoffset = xoffset * sensitivity;
+        let yoffset = yoffset * sensitivity;
 
         self.yaw += xoffset;
         self.pitch += yoffset;
@@ -99,10 +164,22 @@ impl Camera {
         }
     }
 
+    pub fn aspect_ratio(&self) -> f32 {
+        self.aspect
+    }
+
     pub fn set_aspect_ratio(&mut self, aspect: f32) {
         self.aspect = aspect;
     }
 
+    /// Match the projection to a framebuffer size. A zero-sized (minimized) window
+    /// keeps the previous aspect ratio.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width > 0 && height > 0 {
+            self.aspect = width as f32 / height as f32;
+        }
+    }
+
     pub fn position(&self) -> Vec3 {
         self.position
     }
@@ -131,16 +208,56 @@ impl Camera {
         self.pitch
     }
 
+    /// Field of view from the settings, ignoring zoom
     pub fn fov(&self) -> f32 {
         self.fov
     }
 
+    /// Field of view actually rendered: the zoom FOV while zoomed
+    pub fn effective_fov(&self) -> f32 {
+        if self.zoomed {
+            self.zoom_fov.min(self.fov)
+        } else {
+            self.fov
+        }
+    }
+
+    pub fn zoom_fov(&self) -> f32 {
+        self.zoom_fov
+    }
+
+    pub fn set_zoom_fov(&mut self, fov: f32) {
+        self.zoom_fov = fov.clamp(1.0, 90.0);
+    }
+
+    pub fn is_zoomed(&self) -> bool {
+        self.zoomed
+    }
+
+    /// Temporarily narrow the view, e.g. while the zoom key is held
+    pub fn set_zoomed(&mut self, zoomed: bool) {
+        self.zoomed = zoomed;
+    }
+
+    /// Mouse sensitivity scaled down with the zoom so aiming stays precise
+    pub fn effective_mouse_sensitivity(&self) -> f32 {
+        self.look.sensitivity * self.effective_fov() / self.fov
+    }
+
     pub fn set_move_speed(&mut self, speed: f32) {
         self.move_speed = speed;
     }
 
     pub fn set_mouse_sensitivity(&mut self, sensitivity: f32) {
-        self.mouse_sensitivity = sensitivity;
+        self.look.sensitivity = sensitivity;
+    }
+
+    pub fn look_settings(&self) -> &LookSettings {
+        &self.look
+    }
+
+    pub fn set_look_settings(&mut self, look: LookSettings) {
+        self.look = look;
     }
 
     // Cast a ray from the camera for block interaction
@@ -187,4 +304,108 @@ impl Ray {
     pub fn point_at(&self, t: f32) -> Vec3 {
         self.origin + self.direction * t
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn horizontal_distance_after_one_second(forward: f32, right: f32) -> f32 {
+        let mut camera = Camera::new(Vec3::ZERO, 30.0, 0.0, 1.0);
+        camera.set_move_speed(4.0);
+        camera.process_movement(forward, right, 0.0, 1.0);
+        Vec3::new(camera.position().x, 0.0, camera.position().z).length()
+    }
+
+    #[test]
+    fn diagonal_movement_is_no_faster_than_straight() {
+        let straight = horizontal_distance_after_one_second(1.0, 0.0);
+        let diagonal = horizontal_distance_after_one_second(1.0, 1.0);
+
+        assert!((straight - 4.0).abs() < 1e-4);
+        assert!((diagonal - straight).abs() < 1e-4);
+    }
+
+    #[test]
+    fn vertical_movement_is_separate_from_horizontal() {
+        let mut camera = Camera::new(Vec3::ZERO, 0.0, 0.0, 1.0);
+        camera.set_move_speed(4.0);
+        camera.process_movement(1.0, 0.0, 1.0, 1.0);
+
+        assert!((camera.position().y - 4.0).abs() < 1e-4);
+        assert!((Vec3::new(camera.position().x, 0.0, camera.position().z).length() - 4.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn holding_zoom_narrows_fov_and_scales_sensitivity_until_released() {
+        let mut camera = Camera::new(Vec3::ZERO, 0.0, 0.0, 1.0);
+        camera.set_zoom_fov(35.0);
+        camera.set_mouse_sensitivity(0.2);
+
+        camera.set_zoomed(true);
+        assert_eq!(camera.effective_fov(), 35.0);
+        assert!((camera.effective_mouse_sensitivity() - 0.1).abs() < 1e-6);
+        assert_eq!(camera.projection_matrix(), Mat4::perspective_rh(35.0_f32.to_radians(), 1.0, camera.near, camera.far));
+
+        camera.set_zoomed(false);
+        assert_eq!(camera.effective_fov(), camera.fov());
+        assert!((camera.effective_mouse_sensitivity() - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn resizing_to_a_two_to_one_framebuffer_sets_aspect_two() {
+        let mut camera = Camera::new(Vec3::ZERO, 0.0, 0.0, 16.0 / 9.0);
+
+        camera.resize(1600, 800);
+        assert_eq!(camera.aspect_ratio(), 2.0);
+
+        // Minimizing reports a zero height; keep the last good aspect
+        camera.resize(1600, 0);
+        assert_eq!(camera.aspect_ratio(), 2.0);
+    }
+
+    #[test]
+    fn sky_view_drops_translation_but_keeps_rotation() {
+        let camera = Camera::new(Vec3::new(120.0, 75.0, -340.0), 37.0, -15.0, 16.0 / 9.0);
+
+        let sky = camera.sky_view_matrix();
+        let view = camera.view_matrix();
+
+        assert_eq!(sky.w_axis, Vec4::W);
+        assert_eq!(sky.x_axis, view.x_axis);
+        assert_eq!(sky.y_axis, view.y_axis);
+        assert_eq!(sky.z_axis, view.z_axis);
+        // Moving the camera leaves the skybox where it was on screen
+        let mut moved = camera.clone();
+        moved.position += Vec3::new(400.0, 10.0, 400.0);
+        assert!(moved.sky_view_matrix().abs_diff_eq(sky, 1e-5));
+    }
+
+    #[test]
+    fn invert_y_flips_the_pitch_change() {
+        let mut camera = Camera::new(Vec3::ZERO, 0.0, 0.0, 1.0);
+        camera.process_mouse_movement(0.0, 20.0, true);
+        let normal = camera.pitch;
+
+        let mut inverted = Camera::new(Vec3::ZERO, 0.0, 0.0, 1.0);
+        inverted.set_look_settings(LookSettings { invert_y: true, ..LookSettings::default() });
+        inverted.process_mouse_movement(0.0, 20.0, true);
+
+        assert!(normal > 0.0);
+        assert_eq!(inverted.pitch, -normal);
+    }
+
+    #[test]
+    fn power_curve_shrinks_small_inputs_and_keeps_large_ones_responsive() {
+        let linear = LookSettings::default();
+        let curved = LookSettings { curve_exponent: 1.5, ..LookSettings::default() };
+
+        let (small, _) = curved.shape(0.5, 0.0);
+        let (large, _) = curved.shape(40.0, 0.0);
+
+        assert!(small < linear.shape(0.5, 0.0).0);
+        assert!(large >= linear.shape(40.0, 0.0).0);
+        // The sign of the input is kept
+        assert_eq!(curved.shape(-4.0, 0.0).0, -curved.shape(4.0, 0.0).0);
+    }
+}
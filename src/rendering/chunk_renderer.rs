@@ -1,15 +1,112 @@
 use std::collections::HashMap;
-use crate::world::{World, ChunkCoordinate};
-use crate::rendering::vertex::{ChunkMesh, BlockVertex, Face};
-use crate::world::{Chunk, BlockType, CHUNK_SIZE};
+use glam::{IVec3, Vec3};
+use crate::world::{BlockChangeEvent, World, ChunkCoordinate};
+use crate::rendering::vertex::{ChunkMesh, BlockVertex};
+use crate::rendering::mesher::{build_chunk_mesh, build_coarse_chunk_mesh, build_surface_chunk_mesh, ChunkShell};
+use crate::rendering::RenderStats;
+use crate::world::CHUNK_SIZE;
 use wgpu::util::DeviceExt;
 
+/// Level of detail a chunk mesh is built at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshLod {
+    /// One quad per visible block face
+    Full,
+    /// 2x2x2 blocks merged into a single cell
+    Coarse,
+    /// Only the top face of each column, for the far ring seen from a distance
+    Surface,
+}
+
+impl MeshLod {
+    /// Full detail within half the render distance, coarse out to three quarters of
+    /// it and surface-only beyond that
+    pub fn select(chunk_coord: ChunkCoordinate, camera_chunk: ChunkCoordinate, render_distance: i32) -> Self {
+        let dx = chunk_coord.x - camera_chunk.x;
+        let dz = chunk_coord.z - camera_chunk.z;
+        let distance_squared = dx * dx + dz * dz;
+        let full = (render_distance / 2).max(1);
+        let coarse = (render_distance * 3 / 4).max(full);
+
+        if distance_squared <= full * full {
+            MeshLod::Full
+        } else if distance_squared <= coarse * coarse {
+            MeshLod::Coarse
+        } else {
+            MeshLod::Surface
+        }
+    }
+
+    /// Edge length in blocks of one mesh cell
+    pub fn cell_size(&self) -> usize {
+        match self {
+            MeshLod::Full | MeshLod::Surface => 1,
+            MeshLod::Coarse => 2,
+        }
+    }
+}
+
+/// Default GPU memory allowed for cached chunk meshes
+pub const DEFAULT_MESH_BUDGET_BYTES: usize = 256 * 1024 * 1024;
+
+/// The render pass operations the chunk draw loop uses. Kept behind a trait so the
+/// loop can be checked without a GPU.
+pub trait ChunkPass<'a> {
+    /// Bind the groups every chunk shares. Called once before any chunk is drawn.
+    fn bind_shared(&mut self);
+    /// Draw one chunk mesh, returning whether a draw call was issued
+    fn draw_mesh(&mut self, mesh: &'a ChunkMesh) -> bool;
+}
+
+/// A render pass with the bind groups its chunk draws share, bound in order from
+/// group 0
+pub struct SharedBindPass<'p, 'a> {
+    pass: &'p mut wgpu::RenderPass<'a>,
+    bind_groups: &'p [&'a wgpu::BindGroup],
+}
+
+impl<'p, 'a> SharedBindPass<'p, 'a> {
+    pub fn new(pass: &'p mut wgpu::RenderPass<'a>, bind_groups: &'p [&'a wgpu::BindGroup]) -> Self {
+        Self { pass, bind_groups }
+    }
+}
+
+impl<'a> ChunkPass<'a> for SharedBindPass<'_, 'a> {
+    fn bind_shared(&mut self) {
+        for (index, bind_group) in self.bind_groups.iter().enumerate() {
+            self.pass.set_bind_group(index as u32, bind_group, &[]);
+        }
+    }
+
+    fn draw_mesh(&mut self, mesh: &'a ChunkMesh) -> bool {
+        mesh.render(self.pass)
+    }
+}
+
+/// GPU mesh for a chunk along with the data it was built from
+struct CachedChunkMesh {
+    mesh: ChunkMesh,
+    shell: ChunkShell,
+    lod: MeshLod,
+    // Frame the chunk was last visible in
+    last_used: u64,
+    // Buffers were dropped to stay within budget; rebuilt when visible again
+    evicted: bool,
+    // Neighbors that weren't loaded at mesh time, so border faces toward them are missing
+    deferred_neighbors: Vec<ChunkCoordinate>,
+}
+
 /// Handles rendering of world chunks with frustum culling and mesh batching
 pub struct ChunkRenderer {
     // Cache of chunk meshes
-    chunk_meshes: HashMap<ChunkCoordinate, ChunkMesh>,
+    chunk_meshes: HashMap<ChunkCoordinate, CachedChunkMesh>,
     // Meshes that need to be updated
     dirty_chunks: Vec<ChunkCoordinate>,
+    // Chunk the camera is in, used to pick mesh LOD
+    camera_chunk: ChunkCoordinate,
+    // Frame counter for least-recently-used eviction
+    frame: u64,
+    mesh_budget: usize,
 }
 
 impl ChunkRenderer {
@@ -17,17 +114,164 @@ impl ChunkRenderer {
         Self {
             chunk_meshes: HashMap::new(),
             dirty_chunks: Vec::new(),
+            camera_chunk: ChunkCoordinate::new(0, 0),
+            frame: 0,
+            mesh_budget: DEFAULT_MESH_BUDGET_BYTES,
         }
     }
 
     pub fn update_chunk(&mut self, chunk_coord: ChunkCoordinate, device: &wgpu::Device, world: &World) {
         // Generate mesh for the chunk
         if let Some(chunk) = world.get_chunk(chunk_coord) {
-            let mut mesh = ChunkMesh::new();
-            self.generate_chunk_mesh(chunk_coord, chunk, world, &mut mesh);
+            let lod = MeshLod::select(chunk_coord, self.camera_chunk, world.render_distance());
+            let data = match lod {
+                MeshLod::Full => build_chunk_mesh(chunk, world),
+                MeshLod::Coarse => build_coarse_chunk_mesh(chunk, lod.cell_size()),
+                MeshLod::Surface => build_surface_chunk_mesh(chunk),
+            };
+            let mut mesh = ChunkMesh::from_data(data.vertices, data.indices);
             mesh.finalize(device);
-            self.chunk_meshes.insert(chunk_coord, mesh);
+            let deferred_neighbors = chunk_coord
+                .neighbors()
+                .into_iter()
+                .filter(|neighbor| !world.is_chunk_loaded(*neighbor))
+                .collect();
+            self.chunk_meshes.insert(chunk_coord, CachedChunkMesh {
+                mesh,
+                shell: data.shell,
+                lod,
+                last_used: self.frame,
+                evicted: false,
+                deferred_neighbors,
+            });
+        }
+    }
+
+    /// Per-frame bookkeeping: pick LODs, build missing or stale meshes, record which
+    /// chunks are in use and evict the least recently used ones over budget
+    pub fn prepare_frame(&mut self, device: &wgpu::Device, world: &World, camera_position: Vec3) {
+        self.frame += 1;
+        self.update_lod(camera_position, world);
+
+        // Newly loaded chunks, evicted chunks that came back into view and chunks whose
+        // deferred neighbors have since loaded need a mesh
+        for &chunk_coord in world.loaded_chunks() {
+            if self.needs_mesh(chunk_coord, world, camera_position) {
+                self.mark_chunk_dirty(chunk_coord);
+            }
         }
+        self.update_dirty_chunks(device, world);
+
+        let frame = self.frame;
+        for (chunk_coord, cached) in self.chunk_meshes.iter_mut() {
+            if world.is_chunk_loaded(*chunk_coord) && cached.shell.is_visible_from(*chunk_coord, camera_position) {
+                cached.last_used = frame;
+            }
+        }
+
+        self.evict_over_budget();
+    }
+
+    /// True if the chunk has no mesh yet, was evicted and is visible again, or was
+    /// meshed before a neighbor that has since loaded
+    fn needs_mesh(&self, chunk_coord: ChunkCoordinate, world: &World, camera_position: Vec3) -> bool {
+        match self.chunk_meshes.get(&chunk_coord) {
+            None => true,
+            Some(cached) => {
+                (cached.evicted && cached.shell.is_visible_from(chunk_coord, camera_position))
+                    || cached.deferred_neighbors.iter().any(|n| world.is_chunk_loaded(*n))
+            }
+        }
+    }
+
+    /// Drop mesh buffers, least recently used and then farthest first, until the cache
+    /// fits the budget. Chunks used this frame are never evicted.
+    fn evict_over_budget(&mut self) {
+        let mut usage = self.memory_usage();
+        if usage <= self.mesh_budget {
+            return;
+        }
+
+        let camera_chunk = self.camera_chunk;
+        let mut candidates: Vec<(u64, i32, ChunkCoordinate)> = self
+            .chunk_meshes
+            .iter()
+            .filter(|(_, cached)| !cached.evicted && cached.last_used < self.frame)
+            .map(|(coord, cached)| {
+                let dx = coord.x - camera_chunk.x;
+                let dz = coord.z - camera_chunk.z;
+                (cached.last_used, -(dx * dx + dz * dz), *coord)
+            })
+            .collect();
+        candidates.sort_unstable_by_key(|&(last_used, farthest, _)| (last_used, farthest));
+
+        for (_, _, chunk_coord) in candidates {
+            if usage <= self.mesh_budget {
+                break;
+            }
+            if let Some(cached) = self.chunk_meshes.get_mut(&chunk_coord) {
+                usage -= cached.mesh.memory_usage();
+                cached.mesh = ChunkMesh::new();
+                cached.evicted = true;
+            }
+        }
+    }
+
+    /// GPU bytes held by all resident chunk meshes
+    pub fn memory_usage(&self) -> usize {
+        self.chunk_meshes
+            .values()
+            .filter(|cached| !cached.evicted)
+            .map(|cached| cached.mesh.memory_usage())
+            .sum()
+    }
+
+    pub fn mesh_budget(&self) -> usize {
+        self.mesh_budget
+    }
+
+    pub fn set_mesh_budget(&mut self, bytes: usize) {
+        self.mesh_budget = bytes;
+    }
+
+    /// True if some border faces of the chunk are waiting on an unloaded neighbor
+    pub fn has_deferred_borders(&self, chunk_coord: ChunkCoordinate) -> bool {
+        self.chunk_meshes
+            .get(&chunk_coord)
+            .is_some_and(|cached| !cached.deferred_neighbors.is_empty())
+    }
+
+    pub fn is_chunk_resident(&self, chunk_coord: ChunkCoordinate) -> bool {
+        self.chunk_meshes
+            .get(&chunk_coord)
+            .is_some_and(|cached| !cached.evicted)
+    }
+
+    /// Track the camera and rebuild any chunk whose LOD band it has moved across
+    pub fn update_lod(&mut self, camera_position: Vec3, world: &World) {
+        let camera_chunk = ChunkCoordinate::new(
+            (camera_position.x / CHUNK_SIZE as f32).floor() as i32,
+            (camera_position.z / CHUNK_SIZE as f32).floor() as i32,
+        );
+        if camera_chunk == self.camera_chunk {
+            return;
+        }
+        self.camera_chunk = camera_chunk;
+
+        let render_distance = world.render_distance();
+        let stale: Vec<ChunkCoordinate> = self
+            .chunk_meshes
+            .iter()
+            .filter(|(coord, cached)| MeshLod::select(**coord, camera_chunk, render_distance) != cached.lod)
+            .map(|(coord, _)| *coord)
+            .collect();
+        for chunk_coord in stale {
+            self.mark_chunk_dirty(chunk_coord);
+        }
+    }
+
+    pub fn chunk_lod(&self, chunk_coord: ChunkCoordinate) -> Option<MeshLod> {
+        self.chunk_meshes.get(&chunk_coord).map(|cached| cached.lod)
     }
 
     pub fn mark_chunk_dirty(&mut self, chunk_coord: ChunkCoordinate) {
@@ -36,173 +280,91 @@ impl ChunkRenderer {
         }
     }
 
-    pub fn update_dirty_chunks(&mut self, device: &wgpu::Device, world: &World) {
-        let dirty_chunks = std::mem::take(&mut self.dirty_chunks);
-        for chunk_coord in dirty_chunks {
-            self.update_chunk(chunk_coord, device, world);
+    /// Rebuild the chunk containing a changed block, plus any neighbor whose border
+    /// faces touch it
+    pub fn mark_block_dirty(&mut self, pos: IVec3) {
+        let chunk_coord = ChunkCoordinate::containing(pos.x, pos.z);
+        self.mark_chunk_dirty(chunk_coord);
+
+        let edge = CHUNK_SIZE as i32 - 1;
+        let local_x = pos.x.rem_euclid(CHUNK_SIZE as i32);
+        let local_z = pos.z.rem_euclid(CHUNK_SIZE as i32);
+        if local_x == 0 {
+            self.mark_chunk_dirty(ChunkCoordinate::new(chunk_coord.x - 1, chunk_coord.z));
+        } else if local_x == edge {
+            self.mark_chunk_dirty(ChunkCoordinate::new(chunk_coord.x + 1, chunk_coord.z));
+        }
+        if local_z == 0 {
+            self.mark_chunk_dirty(ChunkCoordinate::new(chunk_coord.x, chunk_coord.z - 1));
+        } else if local_z == edge {
+            self.mark_chunk_dirty(ChunkCoordinate::new(chunk_coord.x, chunk_coord.z + 1));
         }
     }
 
-    pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, world: &World) {
-        // TODO: Implement frustum culling here
-        // For now, render all loaded chunks
-        for (chunk_coord, mesh) in &self.chunk_meshes {
-            if world.is_chunk_loaded(*chunk_coord) {
-                mesh.render(render_pass);
+    /// Rebuild every chunk the change relit, since block light is baked into the
+    /// mesh. That includes any neighbor whose border faces touch the block.
+    pub fn handle_block_change(&mut self, change: &BlockChangeEvent) {
+        self.mark_area_dirty(change.position, change.relight_radius());
+    }
+
+    /// Mark every chunk with a column within `radius` blocks (horizontally) of `center` dirty
+    pub fn mark_area_dirty(&mut self, center: IVec3, radius: i32) {
+        let min = ChunkCoordinate::containing(center.x - radius, center.z - radius);
+        let max = ChunkCoordinate::containing(center.x + radius, center.z + radius);
+        for x in min.x..=max.x {
+            for z in min.z..=max.z {
+                self.mark_chunk_dirty(ChunkCoordinate::new(x, z));
             }
         }
     }
 
-    fn generate_chunk_mesh(
-        &self,
-        chunk_coord: ChunkCoordinate,
-        chunk: &crate::world::Chunk,
-        world: &World,
-        mesh: &mut ChunkMesh,
-    ) {
-        use crate::rendering::vertex::Face;
-        use crate::world::{BlockType, CHUNK_SIZE, CHUNK_HEIGHT};
-
-        mesh.clear();
-
-        let chunk_world_x = chunk_coord.x * CHUNK_SIZE as i32;
-        let chunk_world_z = chunk_coord.z * CHUNK_SIZE as i32;
-
-        // Iterate through all blocks in the chunk
-        for y in 0..CHUNK_HEIGHT {
-            for z in 0..CHUNK_SIZE {
-                for x in 0..CHUNK_SIZE {
-                    let block = chunk.get_block(x, y, z);
-                    
-                    // Skip air blocks
-                    if block == BlockType::Air {
-                        continue;
-                    }
-
-                    let world_x = chunk_world_x + x as i32;
-                    let world_y = y as i32;
-                    let world_z = chunk_world_z + z as i32;
-
-                    // Check each face to see if it should be rendered
-                    for face in Face::all() {
-                        if self.should_render_face(
-                            world_x, world_y, world_z, face, chunk, world, chunk_coord
-                        ) {
-                            let texture_id = self.get_texture_id_for_block(block, face);
-                            let light_level = self.calculate_light_level(world_x, world_y, world_z, world);
-                            
-                            mesh.add_face(
-                                face,
-                                world_x as f32,
-                                world_y as f32,
-                                world_z as f32,
-                                texture_id,
-                                light_level,
-                            );
-                        }
-                    }
-                }
-            }
+    pub fn update_dirty_chunks(&mut self, device: &wgpu::Device, world: &World) {
+        let dirty_chunks = std::mem::take(&mut self.dirty_chunks);
+        for chunk_coord in dirty_chunks {
+            self.update_chunk(chunk_coord, device, world);
         }
     }
 
-    fn should_render_face(
-        &self,
-        world_x: i32,
-        world_y: i32,
-        world_z: i32,
-        face: Face,
-        chunk: &crate::world::Chunk,
+    /// Draw all visible chunk meshes, recording what was drawn and culled in `stats`.
+    ///
+    /// The caller binds the pipeline. Every chunk shares the camera and atlas, so they
+    /// are bound once up front and only vertex and index buffers change between draws.
+    pub fn render<'a, P: ChunkPass<'a>>(
+        &'a self,
+        render_pass: &mut P,
         world: &World,
-        chunk_coord: ChunkCoordinate,
-    ) -> bool {
-        use crate::world::{BlockType, CHUNK_SIZE, CHUNK_HEIGHT};
-
-        // Calculate adjacent block position
-        let (adj_x, adj_y, adj_z) = match face {
-            Face::Top => (world_x, world_y + 1, world_z),
-            Face::Bottom => (world_x, world_y - 1, world_z),
-            Face::Front => (world_x, world_y, world_z + 1),
-            Face::Back => (world_x, world_y, world_z - 1),
-            Face::Left => (world_x - 1, world_y, world_z),
-            Face::Right => (world_x + 1, world_y, world_z),
-        };
+        camera_position: Vec3,
+        stats: &mut RenderStats,
+    ) {
+        render_pass.bind_shared();
+        stats.atlas_binds += 1;
 
-        // Check if adjacent block is in the same chunk
-        let chunk_world_x = chunk_coord.x * CHUNK_SIZE as i32;
-        let chunk_world_z = chunk_coord.z * CHUNK_SIZE as i32;
-        
-        let adj_chunk_x = adj_x - chunk_world_x;
-        let adj_chunk_z = adj_z - chunk_world_z;
-
-        let adjacent_block = if adj_y < 0 || adj_y >= CHUNK_HEIGHT as i32 {
-            // Outside vertical bounds
-            if adj_y < 0 {
-                BlockType::Stone // Assume bedrock below
-            } else {
-                BlockType::Air // Air above
+        // TODO: Implement frustum culling here
+        for (chunk_coord, cached) in &self.chunk_meshes {
+            if !world.is_chunk_loaded(*chunk_coord) {
+                continue;
             }
-        } else if adj_chunk_x >= 0 && adj_chunk_x < CHUNK_SIZE as i32 && 
-                  adj_chunk_z >= 0 && adj_chunk_z < CHUNK_SIZE as i32 {
-            // Same chunk
-            chunk.get_block(adj_chunk_x as usize, adj_y as usize, adj_chunk_z as usize)
-        } else {
-            // Different chunk - query world
-            world.get_block_at(adj_x, adj_y, adj_z).unwrap_or(BlockType::Air)
-        };
+            if self.is_chunk_occluded(*chunk_coord, camera_position) {
+                stats.chunks_culled += 1;
+                continue;
+            }
+            if render_pass.draw_mesh(&cached.mesh) {
+                stats.chunks_drawn += 1;
+                stats.draw_calls += 1;
+                stats.triangles += cached.mesh.index_count / 3;
+            }
+        }
+    }
+
+    /// True if the chunk is enclosed so that nothing in it can be seen from the camera
+    pub fn is_chunk_occluded(&self, chunk_coord: ChunkCoordinate, camera_position: Vec3) -> bool {
+        self.chunk_meshes
+            .get(&chunk_coord)
+            .is_some_and(|cached| !cached.shell.is_visible_from(chunk_coord, camera_position))
+    }
 
-        // Render face if adjacent block is transparent (air)
-        adjacent_block == BlockType::Air
-    }
-
-    fn get_texture_id_for_block(&self, block: BlockType, face: Face) -> u32 {
-        use crate::world::BlockType;
-        
-        match block {
-            BlockType::Air => 0, // Should not be rendered
-            BlockType::Stone => match face {
-                _ => 1, // Stone texture
-            },
-            BlockType::Dirt => match face {
-                _ => 2, // Dirt texture
-            },
-            BlockType::Grass => match face {
-                Face::Top => 3,    // Grass top
-                Face::Bottom => 2, // Dirt bottom
-                _ => 4,            // Grass side
-            },
-            BlockType::Sand => match face {
-                _ => 5, // Sand texture
-            },
-            BlockType::Wood => {
-                match face {
-                    Face::Top | Face::Bottom => 6, // Wood rings
-                    _ => 5, // Bark texture
-                }
-            },
-            BlockType::Leaves => match face {
-                _ => 8, // Leaves texture
-            },
-            BlockType::Water => match face {
-                _ => 9, // Water texture
-            },
-            BlockType::Cobblestone => match face {
-                _ => 10, // Cobblestone texture
-            },
-            BlockType::Log => {
-                match face {
-                    Face::Top | Face::Bottom => 6, // Wood rings
-                    _ => 5, // Bark texture
-                }
-            },
-            _ => 0, // Default stone texture for all other blocks
-        }
-    }
-
-    fn calculate_light_level(&self, _x: i32, _y: i32, _z: i32, _world: &World) -> f32 {
-        // TODO: Implement proper lighting calculation
-        // For now, return full brightness
-        1.0
+    pub fn chunk_shell(&self, chunk_coord: ChunkCoordinate) -> Option<&ChunkShell> {
+        self.chunk_meshes.get(&chunk_coord).map(|cached| &cached.shell)
     }
 
     pub fn remove_chunk(&mut self, chunk_coord: ChunkCoordinate) {
@@ -213,4 +375,193 @@ impl ChunkRenderer {
         self.chunk_meshes.clear();
         self.dirty_chunks.clear();
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::testing::flat_world;
+    use crate::world::BlockType;
+
+    fn renderer_with_budget(mesh_budget: usize) -> ChunkRenderer {
+        ChunkRenderer {
+            chunk_meshes: HashMap::new(),
+            dirty_chunks: Vec::new(),
+            camera_chunk: ChunkCoordinate::new(0, 0),
+            frame: 10,
+            mesh_budget,
+        }
+    }
+
+    /// Cache a mesh of `quads` CPU-side quads without touching the GPU
+    fn cache_mesh(renderer: &mut ChunkRenderer, chunk_coord: ChunkCoordinate, quads: usize, last_used: u64) {
+        let mut mesh = ChunkMesh::new();
+        mesh.vertices = vec![bytemuck::Zeroable::zeroed(); quads * 4];
+        mesh.indices = vec![0; quads * 6];
+        mesh.index_count = (quads * 6) as u32;
+        renderer.chunk_meshes.insert(chunk_coord, CachedChunkMesh {
+            mesh,
+            shell: ChunkShell::default(),
+            lod: MeshLod::Full,
+            last_used,
+            evicted: false,
+            deferred_neighbors: Vec::new(),
+        });
+    }
+
+    #[test]
+    fn exceeding_the_budget_evicts_the_least_recently_used_chunk() {
+        let quad_bytes = 4 * std::mem::size_of::<BlockVertex>() + 6 * std::mem::size_of::<u32>();
+        let mut renderer = renderer_with_budget(2 * quad_bytes);
+        let stale = ChunkCoordinate::new(1, 0);
+        let recent = ChunkCoordinate::new(5, 0);
+        let current = ChunkCoordinate::new(9, 0);
+        cache_mesh(&mut renderer, stale, 1, 3);
+        cache_mesh(&mut renderer, recent, 1, 8);
+        cache_mesh(&mut renderer, current, 1, 10);
+
+        renderer.evict_over_budget();
+
+        assert!(!renderer.is_chunk_resident(stale));
+        assert!(renderer.is_chunk_resident(recent));
+        assert!(renderer.is_chunk_resident(current));
+        assert_eq!(renderer.memory_usage(), 2 * quad_bytes);
+    }
+
+    #[test]
+    fn chunks_used_this_frame_are_kept_even_over_budget() {
+        let mut renderer = renderer_with_budget(0);
+        let current = ChunkCoordinate::new(0, 0);
+        cache_mesh(&mut renderer, current, 4, 10);
+
+        renderer.evict_over_budget();
+
+        assert!(renderer.is_chunk_resident(current));
+    }
+
+    #[test]
+    fn lod_is_full_nearby_and_coarser_further_out() {
+        let camera = ChunkCoordinate::new(3, -2);
+        let render_distance = 16;
+
+        assert_eq!(MeshLod::select(camera, camera, render_distance), MeshLod::Full);
+        assert_eq!(MeshLod::select(ChunkCoordinate::new(11, -2), camera, render_distance), MeshLod::Full);
+        assert_eq!(MeshLod::select(ChunkCoordinate::new(12, -2), camera, render_distance), MeshLod::Coarse);
+        assert_eq!(MeshLod::select(ChunkCoordinate::new(3, 10), camera, render_distance), MeshLod::Coarse);
+        assert_eq!(MeshLod::select(ChunkCoordinate::new(3, 11), camera, render_distance), MeshLod::Surface);
+    }
+
+    #[test]
+    fn tiny_render_distance_keeps_neighbors_at_full_detail() {
+        let camera = ChunkCoordinate::new(0, 0);
+        assert_eq!(MeshLod::select(ChunkCoordinate::new(1, 0), camera, 1), MeshLod::Full);
+    }
+
+    #[derive(Default)]
+    struct CountingPass {
+        shared_binds: u32,
+        draws: u32,
+    }
+
+    impl<'a> ChunkPass<'a> for CountingPass {
+        fn bind_shared(&mut self) {
+            self.shared_binds += 1;
+        }
+
+        fn draw_mesh(&mut self, mesh: &'a ChunkMesh) -> bool {
+            self.draws += 1;
+            mesh.index_count > 0
+        }
+    }
+
+    #[test]
+    fn atlas_is_bound_once_per_frame_not_per_chunk() {
+        let world = flat_world(64);
+        let mut renderer = renderer_with_budget(DEFAULT_MESH_BUDGET_BYTES);
+        for &chunk_coord in world.loaded_chunks() {
+            cache_mesh(&mut renderer, chunk_coord, 2, 10);
+            renderer.chunk_meshes.get_mut(&chunk_coord).unwrap().shell = ChunkShell::open();
+        }
+
+        let mut pass = CountingPass::default();
+        let mut stats = RenderStats::default();
+        renderer.render(&mut pass, &world, Vec3::new(8.0, 80.0, 8.0), &mut stats);
+
+        assert_eq!(pass.shared_binds, 1);
+        assert_eq!(stats.atlas_binds, 1);
+        assert_eq!(pass.draws, 9);
+        assert_eq!(stats.draw_calls, 9);
+        assert_eq!(stats.triangles, 9 * 4);
+    }
+
+    #[test]
+    fn two_meshes_report_two_draw_calls_and_their_summed_triangles() {
+        let world = flat_world(64);
+        let mut renderer = renderer_with_budget(DEFAULT_MESH_BUDGET_BYTES);
+        for (chunk_coord, quads) in [(ChunkCoordinate::new(0, 0), 3), (ChunkCoordinate::new(1, 0), 5)] {
+            cache_mesh(&mut renderer, chunk_coord, quads, 10);
+            renderer.chunk_meshes.get_mut(&chunk_coord).unwrap().shell = ChunkShell::open();
+        }
+
+        let mut pass = CountingPass::default();
+        let mut stats = RenderStats::default();
+        renderer.render(&mut pass, &world, Vec3::new(8.0, 80.0, 8.0), &mut stats);
+
+        assert_eq!(stats.chunks_drawn, 2);
+        assert_eq!(stats.draw_calls, 2);
+        assert_eq!(stats.triangles, (3 + 5) * 2);
+    }
+
+    #[test]
+    fn chunk_with_deferred_borders_remeshes_once_the_neighbor_loads() {
+        let mut world = flat_world(64);
+        let mut renderer = renderer_with_budget(DEFAULT_MESH_BUDGET_BYTES);
+        let chunk_coord = ChunkCoordinate::new(1, 0);
+        let unloaded = ChunkCoordinate::new(2, 0);
+        cache_mesh(&mut renderer, chunk_coord, 1, 10);
+        renderer.chunk_meshes.get_mut(&chunk_coord).unwrap().deferred_neighbors = vec![unloaded];
+        let camera = Vec3::new(8.0, 80.0, 8.0);
+
+        assert!(renderer.has_deferred_borders(chunk_coord));
+        assert!(!renderer.needs_mesh(chunk_coord, &world, camera));
+
+        world.load_chunk_now(unloaded);
+        assert!(renderer.needs_mesh(chunk_coord, &world, camera));
+    }
+
+    #[test]
+    fn placing_a_torch_marks_every_chunk_in_its_light_radius_dirty() {
+        let mut renderer = renderer_with_budget(DEFAULT_MESH_BUDGET_BYTES);
+        let change = BlockChangeEvent {
+            position: IVec3::new(8, 70, 8),
+            old: BlockType::Air,
+            new: BlockType::Torch,
+        };
+
+        renderer.handle_block_change(&change);
+
+        // A light of 14 from the middle of chunk (0, 0) reaches one chunk each way
+        let mut dirty = renderer.dirty_chunks.clone();
+        dirty.sort_by_key(|coord| (coord.x, coord.z));
+        let expected: Vec<_> = (-1..=1)
+            .flat_map(|x| (-1..=1).map(move |z| ChunkCoordinate::new(x, z)))
+            .collect();
+        assert_eq!(dirty, expected);
+    }
+
+    #[test]
+    fn chunk_edited_directly_is_queued_for_remeshing_once() {
+        let mut world = flat_world(10);
+        let coord = ChunkCoordinate::new(1, 0);
+        world.get_chunk_mut(coord).unwrap().set_block(2, 11, 2, BlockType::Glass);
+        let mut renderer = renderer_with_budget(DEFAULT_MESH_BUDGET_BYTES);
+
+        for edited in world.take_remesh_chunks() {
+            renderer.mark_chunk_dirty(edited);
+        }
+
+        assert_eq!(renderer.dirty_chunks, vec![coord]);
+        assert!(!world.get_chunk(coord).unwrap().needs_remesh());
+        assert!(world.take_remesh_chunks().is_empty());
+    }
+}
@@ -0,0 +1,282 @@
+// Entities drawn as solid colored boxes in world coordinates, shaded per face
+// like terrain. Stands in for proper entity models.
+use bytemuck::{Pod, Zeroable};
+use glam::Vec3;
+use crate::rendering::shader;
+use crate::rendering::vertex::{BOTTOM_SHADE, EAST_WEST_SHADE, NORTH_SOUTH_SHADE, TOP_SHADE};
+use crate::rendering::Texture;
+use crate::world::{Entity, EntityKind, PrimedTnt, World};
+
+/// Most boxes drawn in one frame; any beyond this are skipped
+pub const MAX_ENTITY_BOXES: usize = 256;
+/// Vertices for the 6 faces of a box
+const BOX_VERTEX_COUNT: usize = 36;
+
+/// Primed TNT is drawn a little smaller than a block so it doesn't z-fight with terrain
+const TNT_HALF_SIZE: f32 = 0.49;
+const TNT_COLOR: [f32; 3] = [0.8, 0.2, 0.15];
+/// TNT turns white during the bright phase of its blink
+const TNT_FLASH_COLOR: [f32; 3] = [1.0, 1.0, 1.0];
+const PIG_COLOR: [f32; 3] = [0.94, 0.6, 0.6];
+const ITEM_COLOR: [f32; 3] = [0.85, 0.75, 0.45];
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Pod, Zeroable)]
+pub struct EntityVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 3],
+}
+
+impl EntityVertex {
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3];
+
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<EntityVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+/// An axis-aligned box drawn in a single color
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntityBox {
+    pub min: Vec3,
+    pub max: Vec3,
+    pub color: [f32; 3],
+}
+
+/// Box for a lit TNT block, blinking white as its fuse burns
+pub fn primed_tnt_box(tnt: &PrimedTnt) -> EntityBox {
+    EntityBox {
+        min: tnt.position - Vec3::splat(TNT_HALF_SIZE),
+        max: tnt.position + Vec3::splat(TNT_HALF_SIZE),
+        color: if tnt.is_flashing() { TNT_FLASH_COLOR } else { TNT_COLOR },
+    }
+}
+
+/// Box for an entity's collision box, colored by its kind
+pub fn entity_box(entity: &dyn Entity) -> Option<EntityBox> {
+    let color = match entity.kind() {
+        EntityKind::Pig => PIG_COLOR,
+        EntityKind::Item => ITEM_COLOR,
+        // The player is drawn from the camera, not as a box
+        EntityKind::Player => return None,
+    };
+    let aabb = entity.aabb();
+    Some(EntityBox {
+        min: aabb.min,
+        max: aabb.max,
+        color,
+    })
+}
+
+/// Every box to draw for the world's entities this frame
+pub fn entity_boxes(world: &World) -> Vec<EntityBox> {
+    world
+        .entities()
+        .iter()
+        .filter_map(|entity| entity_box(entity.as_ref()))
+        .chain(world.primed_tnt().iter().map(primed_tnt_box))
+        .collect()
+}
+
+/// Triangles covering every face of a box, each face darkened by its direction
+pub fn entity_box_vertices(entity_box: &EntityBox) -> Vec<EntityVertex> {
+    let corner = |index: usize| {
+        let pick = |bit: usize, axis: usize| if index & bit != 0 { entity_box.max[axis] } else { entity_box.min[axis] };
+        [pick(1, 0), pick(2, 1), pick(4, 2)]
+    };
+    // Each face holds one axis bit fixed; its four corners vary the other two
+    let faces = [1usize, 2, 4].into_iter().flat_map(|fixed| {
+        let [u, v] = match fixed {
+            1 => [2, 4],
+            2 => [1, 4],
+            _ => [1, 2],
+        };
+        [0, fixed].map(move |side| (fixed, side, [side, side | u, side | u | v, side, side | u | v, side | v]))
+    });
+    faces
+        .flat_map(|(fixed, side, corners)| {
+            let shade = match (fixed, side != 0) {
+                (2, true) => TOP_SHADE,
+                (2, false) => BOTTOM_SHADE,
+                (1, _) => EAST_WEST_SHADE,
+                _ => NORTH_SOUTH_SHADE,
+            };
+            let color = entity_box.color.map(|channel| channel * shade);
+            corners.map(|index| EntityVertex { position: corner(index), color })
+        })
+        .collect()
+}
+
+/// Draws entities as shaded boxes
+pub struct EntityRenderer {
+    pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    vertex_count: u32,
+}
+
+impl EntityRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+        format: wgpu::TextureFormat,
+    ) -> anyhow::Result<Self> {
+        let shader = shader::validated(device, || {
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Entity Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("shaders/entity.wgsl").into()),
+            })
+        })?;
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Entity Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = shader::validated(device, || {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Entity Pipeline"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[EntityVertex::desc()],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(wgpu::BlendState::REPLACE),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    // Box faces aren't wound consistently
+                    cull_mode: None,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: Texture::DEPTH_FORMAT,
+                    depth_write_enabled: true,
+                    depth_compare: wgpu::CompareFunction::Less,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+            })
+        })?;
+        let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Entity Vertex Buffer"),
+            size: (MAX_ENTITY_BOXES * BOX_VERTEX_COUNT * std::mem::size_of::<EntityVertex>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Ok(Self {
+            pipeline,
+            vertex_buffer,
+            vertex_count: 0,
+        })
+    }
+
+    /// Upload this frame's boxes for the world's entities
+    pub fn prepare(&mut self, queue: &wgpu::Queue, world: &World) {
+        let vertices: Vec<EntityVertex> = entity_boxes(world)
+            .iter()
+            .take(MAX_ENTITY_BOXES)
+            .flat_map(entity_box_vertices)
+            .collect();
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+        self.vertex_count = vertices.len() as u32;
+    }
+
+    /// Draw the prepared boxes. Expects the camera bind group at group 0.
+    pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) -> bool {
+        if self.vertex_count == 0 {
+            return false;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.draw(0..self.vertex_count, 0..1);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::IVec3;
+    use crate::world::testing::flat_world;
+    use crate::world::{BlockType, ItemEntity, Pig};
+
+    #[test]
+    fn primed_tnt_blinks_white() {
+        let mut tnt = PrimedTnt::new(IVec3::new(3, 10, -2), 4.0);
+        assert!(tnt.is_flashing());
+        assert_eq!(primed_tnt_box(&tnt).color, TNT_FLASH_COLOR);
+
+        tnt.fuse = 3.8;
+        assert!(!tnt.is_flashing());
+        assert_eq!(primed_tnt_box(&tnt).color, TNT_COLOR);
+    }
+
+    #[test]
+    fn primed_tnt_box_fills_its_block() {
+        let tnt_box = primed_tnt_box(&PrimedTnt::new(IVec3::new(3, 10, -2), 4.0));
+        let center = (tnt_box.min + tnt_box.max) / 2.0;
+        assert_eq!(center, Vec3::new(3.5, 10.5, -1.5));
+        assert!(tnt_box.max.x - tnt_box.min.x > 0.9);
+    }
+
+    #[test]
+    fn pigs_and_items_get_boxes() {
+        let mut world = flat_world(10);
+        world.spawn_entity(Box::new(Pig::new(Vec3::new(0.5, 11.0, 0.5), 1)));
+        world.spawn_entity(Box::new(ItemEntity::new(Vec3::new(3.5, 11.0, 0.5), BlockType::Dirt, 1)));
+
+        let boxes = entity_boxes(&world);
+
+        assert_eq!(boxes.len(), 2);
+        assert_eq!(boxes[0].color, PIG_COLOR);
+        assert_eq!(boxes[0].min.y, 11.0);
+        assert_eq!(boxes[1].color, ITEM_COLOR);
+    }
+
+    #[test]
+    fn box_vertices_cover_the_box_with_shaded_faces() {
+        let entity_box = EntityBox {
+            min: Vec3::new(1.0, 2.0, 3.0),
+            max: Vec3::new(2.0, 4.0, 3.5),
+            color: [1.0, 1.0, 1.0],
+        };
+        let vertices = entity_box_vertices(&entity_box);
+        assert_eq!(vertices.len(), BOX_VERTEX_COUNT);
+
+        for vertex in &vertices {
+            let position = Vec3::from(vertex.position);
+            assert!(position == position.clamp(entity_box.min, entity_box.max));
+        }
+
+        // The top is the brightest face and the bottom the darkest
+        let brightness = |y: f32| {
+            vertices
+                .chunks(6)
+                .find(|face| face.iter().all(|vertex| vertex.position[1] == y))
+                .map(|face| face[0].color[0])
+                .unwrap()
+        };
+        assert_eq!(brightness(4.0), TOP_SHADE);
+        assert_eq!(brightness(2.0), BOTTOM_SHADE);
+    }
+}
@@ -0,0 +1,844 @@
+// Chunk meshing: turns chunk blocks into vertex and index data. Nothing here touches
+// the GPU; the chunk renderer uploads the result.
+
+use glam::{IVec3, Vec3};
+use crate::rendering::vertex::{BlockVertex, Face, FaceLight};
+use crate::world::{BlockType, Chunk, ChunkCoordinate, DoorFacing, DoorState, World, CHUNK_HEIGHT, CHUNK_SIZE, MAX_POWER, POWERED_BIT, wire_power};
+
+/// Which boundary planes of a chunk the camera could see into or onto.
+///
+/// A plane is sealed when every block on it is opaque and none of them emitted an
+/// outward face, so the chunk contributes nothing visible through that plane.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChunkShell {
+    open: [bool; 6],
+}
+
+impl ChunkShell {
+    fn index(face: Face) -> usize {
+        match face {
+            Face::Top => 0,
+            Face::Bottom => 1,
+            Face::Front => 2,
+            Face::Back => 3,
+            Face::Left => 4,
+            Face::Right => 5,
+        }
+    }
+
+    pub fn is_open(&self, face: Face) -> bool {
+        self.open[Self::index(face)]
+    }
+
+    fn mark_open(&mut self, face: Face) {
+        self.open[Self::index(face)] = true;
+    }
+
+    /// True if every boundary plane is sealed
+    pub fn is_fully_sealed(&self) -> bool {
+        !self.open.iter().any(|&open| open)
+    }
+
+    /// Conservative visibility test: the chunk is hidden only if every plane facing the
+    /// camera is sealed. A camera inside the chunk's bounds always sees it.
+    pub fn is_visible_from(&self, chunk_coord: ChunkCoordinate, camera_position: Vec3) -> bool {
+        let (min_x, min_z) = chunk_coord.world_position();
+        let min = Vec3::new(min_x as f32, 0.0, min_z as f32);
+        let max = min + Vec3::new(CHUNK_SIZE as f32, CHUNK_HEIGHT as f32, CHUNK_SIZE as f32);
+
+        let facing = [
+            (camera_position.x < min.x, Face::Left),
+            (camera_position.x > max.x, Face::Right),
+            (camera_position.y < min.y, Face::Bottom),
+            (camera_position.y > max.y, Face::Top),
+            (camera_position.z < min.z, Face::Back),
+            (camera_position.z > max.z, Face::Front),
+        ];
+
+        // Inside the chunk: nothing faces away, so never cull
+        if !facing.iter().any(|&(faces_camera, _)| faces_camera) {
+            return true;
+        }
+
+        facing
+            .iter()
+            .any(|&(faces_camera, face)| faces_camera && self.is_open(face))
+    }
+}
+
+impl ChunkShell {
+    /// A shell that never culls
+    pub(crate) fn open() -> Self {
+        Self { open: [true; 6] }
+    }
+}
+
+/// Blocks and light just outside the chunk being meshed. The world provides this in
+/// game; anything else holding neighboring chunks can stand in for it.
+pub trait MeshNeighbors {
+    /// Block at a world position, or `None` if its chunk isn't loaded
+    fn block_at(&self, pos: IVec3) -> Option<BlockType>;
+    /// Sky light (0-15) at a world position, if loaded
+    fn sky_light_at(&self, pos: IVec3) -> Option<u8>;
+    /// Red, green and blue block light (0-15 each) at a world position, if loaded
+    fn block_light_at(&self, pos: IVec3) -> Option<[u8; 3]>;
+}
+
+impl MeshNeighbors for World {
+    fn block_at(&self, pos: IVec3) -> Option<BlockType> {
+        World::block_at(self, pos)
+    }
+
+    fn sky_light_at(&self, pos: IVec3) -> Option<u8> {
+        World::sky_light_at(self, pos)
+    }
+
+    fn block_light_at(&self, pos: IVec3) -> Option<[u8; 3]> {
+        World::block_light_at(self, pos)
+    }
+}
+
+/// CPU-side mesh for one chunk, ready to be uploaded
+#[derive(Debug, Clone, Default)]
+pub struct MeshData {
+    pub vertices: Vec<BlockVertex>,
+    pub indices: Vec<u32>,
+    /// Boundary planes the mesh can be seen through, for occlusion culling
+    pub shell: ChunkShell,
+}
+
+impl MeshData {
+    pub fn add_face(&mut self, face: Face, x: f32, y: f32, z: f32, texture_id: u32, light: FaceLight) {
+        let start_vertex = self.vertices.len() as u32;
+        let block = block_of(Vec3::new(x, y, z));
+        let face_vertices = face.vertices(x, y, z, texture_id, light).map(|vertex| vertex.with_block(block));
+        self.vertices.extend_from_slice(&face_vertices);
+        self.indices.extend_from_slice(&face.indices(start_vertex));
+    }
+
+    /// Add a face covering a cube of `size` blocks with its corner at `origin`, used
+    /// for coarse LOD meshes
+    pub fn add_scaled_face(&mut self, face: Face, origin: Vec3, size: f32, texture_id: u32, light: FaceLight) {
+        let start_vertex = self.vertices.len() as u32;
+        let face_vertices = face
+            .vertices(origin.x, origin.y, origin.z, texture_id, light)
+            .map(|vertex| vertex.scaled_from(origin.to_array(), size).with_block(block_of(origin)));
+        self.vertices.extend_from_slice(&face_vertices);
+        self.indices.extend_from_slice(&face.indices(start_vertex));
+    }
+
+    /// Add two quads crossing diagonally through the block at `origin`, each wound
+    /// both ways so it shows from either side under back-face culling. The top edge
+    /// sways in the wind.
+    pub fn add_cross(&mut self, origin: Vec3, texture_id: u32, light: FaceLight) {
+        let block = block_of(origin);
+        for (start, end) in [(Vec3::ZERO, Vec3::new(1.0, 0.0, 1.0)), (Vec3::Z, Vec3::X)] {
+            let along = end - start;
+            let normal = Vec3::new(along.z, 0.0, -along.x).normalize().to_array();
+            let (bottom_start, bottom_end) = (origin + start, origin + end);
+            let start_vertex = self.vertices.len() as u32;
+            self.vertices.extend_from_slice(&[
+                BlockVertex::new(bottom_start.to_array(), [0.0, 0.0], normal, texture_id, light).with_block(block),
+                BlockVertex::new((bottom_start + Vec3::Y).to_array(), [0.0, 1.0], normal, texture_id, light)
+                    .with_sway(1.0)
+                    .with_block(block),
+                BlockVertex::new((bottom_end + Vec3::Y).to_array(), [1.0, 1.0], normal, texture_id, light)
+                    .with_sway(1.0)
+                    .with_block(block),
+                BlockVertex::new(bottom_end.to_array(), [1.0, 0.0], normal, texture_id, light).with_block(block),
+            ]);
+            let front = Face::Front.indices(start_vertex);
+            let mut back = front;
+            back.reverse();
+            self.indices.extend_from_slice(&front);
+            self.indices.extend_from_slice(&back);
+        }
+    }
+
+    /// Add all six faces of the box from `min` to `max`, for blocks that aren't
+    /// full cubes
+    pub fn add_box(&mut self, min: Vec3, max: Vec3, texture_id: u32, light: FaceLight) {
+        let size = (max - min).to_array();
+        let block = block_of(min);
+        for face in Face::all() {
+            let start_vertex = self.vertices.len() as u32;
+            let face_vertices = face
+                .vertices(min.x, min.y, min.z, texture_id, light.shaded(face.shade()))
+                .map(|vertex| vertex.scaled_from_axes(min.to_array(), size).with_block(block));
+            self.vertices.extend_from_slice(&face_vertices);
+            self.indices.extend_from_slice(&face.indices(start_vertex));
+        }
+    }
+
+    /// Number of quads in the mesh
+    pub fn face_count(&self) -> usize {
+        self.indices.len() / 6
+    }
+}
+
+/// Block containing `corner`, the minimum corner of a shape drawn for it
+fn block_of(corner: Vec3) -> [i32; 3] {
+    corner.floor().as_ivec3().to_array()
+}
+
+/// Build the full-detail mesh for a chunk: one quad per visible block face.
+///
+/// Border faces look into `neighbors`; a face toward a chunk that isn't loaded is
+/// left out until the chunk is re-meshed, instead of drawing a wall that vanishes.
+pub fn build_chunk_mesh(chunk: &Chunk, neighbors: &impl MeshNeighbors) -> MeshData {
+    let mut mesh = MeshData::default();
+    let (chunk_world_x, chunk_world_z) = chunk.coordinate.world_position();
+
+    for y in 0..CHUNK_HEIGHT {
+        for z in 0..CHUNK_SIZE {
+            for x in 0..CHUNK_SIZE {
+                let block = chunk.get_block(x, y, z);
+                let boundary_faces = boundary_faces(x, y, z);
+
+                // Anything see-through on the boundary lets the camera look inside
+                if block == BlockType::Air || block.is_transparent() {
+                    for &face in boundary_faces.iter().flatten() {
+                        mesh.shell.mark_open(face);
+                    }
+                }
+
+                if block == BlockType::Air {
+                    continue;
+                }
+
+                let pos = IVec3::new(chunk_world_x + x as i32, y as i32, chunk_world_z + z as i32);
+                let state = chunk.get_block_state(x, y, z);
+                if block.is_cross_shaped() {
+                    let texture_id = texture_id_for_block(block, state, Face::Front);
+                    mesh.add_cross(pos.as_vec3(), texture_id, cell_light(chunk, x, y, z));
+                    continue;
+                }
+                let boxes = match block {
+                    // Slabs and stairs are drawn as the boxes they collide with
+                    BlockType::Slab | BlockType::Stairs => block.collision_boxes(state),
+                    _ => block_shape(block, state).into_iter().collect(),
+                };
+                if !boxes.is_empty() {
+                    let mut light = cell_light(chunk, x, y, z);
+                    if block == BlockType::RedstoneWire {
+                        light = redstone_tint(light, wire_power(state));
+                    }
+                    let texture_id = texture_id_for_block(block, 0, Face::Front);
+                    for (min, max) in boxes {
+                        mesh.add_box(pos.as_vec3() + min, pos.as_vec3() + max, texture_id, light);
+                    }
+                    continue;
+                }
+                for face in Face::all() {
+                    if !should_render_face(block, pos, face, chunk, neighbors) {
+                        continue;
+                    }
+                    if boundary_faces.contains(&Some(face)) {
+                        mesh.shell.mark_open(face);
+                    }
+
+                    let texture_id = texture_id_for_block(block, state, face);
+                    let light = calculate_light(pos, face, chunk, neighbors);
+                    mesh.add_face(face, pos.x as f32, pos.y as f32, pos.z as f32, texture_id, light);
+                }
+            }
+        }
+    }
+
+    mesh
+}
+
+/// Build a mesh with `cell`-sized cubes of blocks merged into one.
+///
+/// Faces on the chunk boundary are always emitted so a coarse chunk never shows
+/// a gap next to a neighbor meshed at a different LOD.
+pub fn build_coarse_chunk_mesh(chunk: &Chunk, cell: usize) -> MeshData {
+    let mut mesh = MeshData::default();
+    let (chunk_world_x, chunk_world_z) = chunk.coordinate.world_position();
+
+    for y in (0..CHUNK_HEIGHT).step_by(cell) {
+        for z in (0..CHUNK_SIZE).step_by(cell) {
+            for x in (0..CHUNK_SIZE).step_by(cell) {
+                let block = coarse_cell_block(chunk, x, y, z, cell);
+                if block == BlockType::Air {
+                    continue;
+                }
+
+                for face in Face::all() {
+                    let [nx, ny, nz] = face.normal().map(|n| n as i32 * cell as i32);
+                    let (adj_x, adj_y, adj_z) = (x as i32 + nx, y as i32 + ny, z as i32 + nz);
+
+                    let exposed = if adj_y < 0 {
+                        false
+                    } else if adj_y >= CHUNK_HEIGHT as i32
+                        || adj_x < 0 || adj_x >= CHUNK_SIZE as i32
+                        || adj_z < 0 || adj_z >= CHUNK_SIZE as i32
+                    {
+                        true
+                    } else {
+                        coarse_cell_block(chunk, adj_x as usize, adj_y as usize, adj_z as usize, cell)
+                            == BlockType::Air
+                    };
+
+                    if exposed {
+                        mesh.add_scaled_face(
+                            face,
+                            Vec3::new((chunk_world_x + x as i32) as f32, y as f32, (chunk_world_z + z as i32) as f32),
+                            cell as f32,
+                            texture_id_for_block(block, 0, face),
+                            FaceLight::SKY.shaded(face.shade()),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    // Coarse chunks are distant and cheap; never cull them
+    mesh.shell = ChunkShell::open();
+    mesh
+}
+
+/// Build a mesh of just the top face of the highest opaque block (or water) in each
+/// column, scanning down from the chunk's height map. Sides and anything under an
+/// overhang are left out, which is only passable far away where terrain is seen
+/// from above.
+pub fn build_surface_chunk_mesh(chunk: &Chunk) -> MeshData {
+    let mut mesh = MeshData::default();
+    let (chunk_world_x, chunk_world_z) = chunk.coordinate.world_position();
+
+    for z in 0..CHUNK_SIZE {
+        for x in 0..CHUNK_SIZE {
+            // Grass, flowers and the like count towards the height map but would
+            // cover the ground with their own texture
+            let surface = (0..chunk.get_height_at(x, z)).rev().find(|&y| {
+                let block = chunk.get_block(x, y, z);
+                !block.is_transparent() || block == BlockType::Water
+            });
+            let Some(y) = surface else {
+                continue;
+            };
+            let block = chunk.get_block(x, y, z);
+            mesh.add_scaled_face(
+                Face::Top,
+                Vec3::new((chunk_world_x + x as i32) as f32, y as f32, (chunk_world_z + z as i32) as f32),
+                1.0,
+                texture_id_for_block(block, chunk.get_block_state(x, y, z), Face::Top),
+                FaceLight::SKY.shaded(Face::Top.shade()),
+            );
+        }
+    }
+
+    mesh.shell = ChunkShell::open();
+    mesh
+}
+
+/// The block a coarse cell is drawn as: the first opaque block, scanning from the
+/// top so surfaces keep their top material. Grass, flowers and other see-through
+/// blocks only stand in for cells with nothing opaque in them.
+fn coarse_cell_block(chunk: &Chunk, x: usize, y: usize, z: usize, cell: usize) -> BlockType {
+    let mut see_through = BlockType::Air;
+    for cy in (y..(y + cell).min(CHUNK_HEIGHT)).rev() {
+        for cz in z..(z + cell).min(CHUNK_SIZE) {
+            for cx in x..(x + cell).min(CHUNK_SIZE) {
+                let block = chunk.get_block(cx, cy, cz);
+                if !block.is_transparent() {
+                    return block;
+                }
+                if see_through == BlockType::Air {
+                    see_through = block;
+                }
+            }
+        }
+    }
+    see_through
+}
+
+/// The chunk boundary planes a local block position lies on
+fn boundary_faces(x: usize, y: usize, z: usize) -> [Option<Face>; 3] {
+    let x_face = match x {
+        0 => Some(Face::Left),
+        x if x == CHUNK_SIZE - 1 => Some(Face::Right),
+        _ => None,
+    };
+    let y_face = match y {
+        0 => Some(Face::Bottom),
+        y if y == CHUNK_HEIGHT - 1 => Some(Face::Top),
+        _ => None,
+    };
+    let z_face = match z {
+        0 => Some(Face::Back),
+        z if z == CHUNK_SIZE - 1 => Some(Face::Front),
+        _ => None,
+    };
+    [x_face, y_face, z_face]
+}
+
+/// Light inside a see-through block's own cell, for shapes drawn within it
+fn cell_light(chunk: &Chunk, x: usize, y: usize, z: usize) -> FaceLight {
+    FaceLight {
+        sky: chunk.get_sky_light(x, y, z) as f32 / 15.0,
+        block: chunk.get_block_light_rgb(x, y, z).map(|channel| channel as f32 / 15.0),
+    }
+}
+
+/// Corners, within the block, of blocks drawn as a single box smaller than a full
+/// cube; `None` for full cubes
+fn block_shape(block: BlockType, state: u8) -> Option<(Vec3, Vec3)> {
+    let pixel = 1.0 / 16.0;
+    let powered = state & POWERED_BIT != 0;
+    match block {
+        BlockType::Door => Some(door_panel_bounds(DoorState::from_state(state))),
+        // Plates and buttons sink a little while pressed
+        BlockType::PressurePlate => {
+            let height = if powered { 0.5 * pixel } else { pixel };
+            Some((Vec3::new(pixel, 0.0, pixel), Vec3::new(1.0 - pixel, height, 1.0 - pixel)))
+        }
+        BlockType::Button => {
+            let height = if powered { pixel } else { 2.0 * pixel };
+            Some((Vec3::new(5.0 * pixel, 0.0, 6.0 * pixel), Vec3::new(11.0 * pixel, height, 10.0 * pixel)))
+        }
+        BlockType::RedstoneWire => Some((Vec3::ZERO, Vec3::new(1.0, pixel, 1.0))),
+        // Just the base; the handle isn't drawn yet
+        BlockType::Lever => Some((Vec3::new(5.0 * pixel, 0.0, 4.0 * pixel), Vec3::new(11.0 * pixel, 3.0 * pixel, 12.0 * pixel))),
+        _ => None,
+    }
+}
+
+/// Redden a wire's light with its signal strength, from a dull glow when unpowered
+/// to bright red at full power
+fn redstone_tint(light: FaceLight, power: u8) -> FaceLight {
+    let glow = 0.3 + 0.7 * power as f32 / MAX_POWER as f32;
+    let [red, green, blue] = light.block;
+    FaceLight { block: [red.max(glow), green, blue], ..light }
+}
+
+/// Thickness of a door panel, in blocks
+const DOOR_THICKNESS: f32 = 3.0 / 16.0;
+
+/// Corners of a door half's panel within its block: a thin slab against the side
+/// the door lies on, which moves to the hinge side when the door opens
+fn door_panel_bounds(door: DoorState) -> (Vec3, Vec3) {
+    let far = 1.0 - DOOR_THICKNESS;
+    match door.panel_side() {
+        DoorFacing::North => (Vec3::new(0.0, 0.0, far), Vec3::ONE),
+        DoorFacing::South => (Vec3::ZERO, Vec3::new(1.0, 1.0, DOOR_THICKNESS)),
+        DoorFacing::East => (Vec3::new(far, 0.0, 0.0), Vec3::ONE),
+        DoorFacing::West => (Vec3::ZERO, Vec3::new(DOOR_THICKNESS, 1.0, 1.0)),
+    }
+}
+
+/// Local coordinates of a world position if it lies inside `chunk`
+fn local_position(chunk: &Chunk, pos: IVec3) -> Option<(usize, usize, usize)> {
+    let (chunk_world_x, chunk_world_z) = chunk.coordinate.world_position();
+    let (x, z) = (pos.x - chunk_world_x, pos.z - chunk_world_z);
+    let in_chunk = (0..CHUNK_SIZE as i32).contains(&x)
+        && (0..CHUNK_HEIGHT as i32).contains(&pos.y)
+        && (0..CHUNK_SIZE as i32).contains(&z);
+    in_chunk.then_some((x as usize, pos.y as usize, z as usize))
+}
+
+fn should_render_face(
+    block: BlockType,
+    pos: IVec3,
+    face: Face,
+    chunk: &Chunk,
+    neighbors: &impl MeshNeighbors,
+) -> bool {
+    let adjacent = pos + IVec3::from(face.normal().map(|n| n as i32));
+
+    if adjacent.y < 0 {
+        // Nothing can look up through the world floor, whatever sits on it
+        return false;
+    }
+
+    let adjacent_block = if adjacent.y >= CHUNK_HEIGHT as i32 {
+        // Nothing is stored above the chunk, which is at or above any build limit
+        BlockType::Air
+    } else if let Some((x, y, z)) = local_position(chunk, adjacent) {
+        chunk.get_block(x, y, z)
+    } else {
+        match neighbors.block_at(adjacent) {
+            Some(block) => block,
+            None => return false,
+        }
+    };
+
+    face_visible(block, adjacent_block)
+}
+
+/// Whether a face of `block` touching `neighbor` can be seen. Faces behind
+/// see-through blocks are drawn, except between two of the same kind (glass
+/// against glass, leaves against leaves), which merge into one volume.
+fn face_visible(block: BlockType, neighbor: BlockType) -> bool {
+    if neighbor == BlockType::Air {
+        return true;
+    }
+    neighbor.is_transparent() && neighbor != block
+}
+
+/// Atlas texture for a block face. `state` picks the variant for blocks that
+/// have several looks, such as flowers.
+pub fn texture_id_for_block(block: BlockType, state: u8, face: Face) -> u32 {
+    match block {
+        BlockType::Air => 0, // Should not be rendered
+        BlockType::Stone => match face {
+            _ => 1, // Stone texture
+        },
+        BlockType::Dirt => match face {
+            _ => 2, // Dirt texture
+        },
+        BlockType::Grass => match face {
+            Face::Top => 3,    // Grass top
+            Face::Bottom => 2, // Dirt bottom
+            _ => 4,            // Grass side
+        },
+        BlockType::Sand => match face {
+            _ => 5, // Sand texture
+        },
+        BlockType::Wood => {
+            match face {
+                Face::Top | Face::Bottom => 6, // Wood rings
+                _ => 5, // Bark texture
+            }
+        },
+        BlockType::Leaves => match face {
+            _ => 8, // Leaves texture
+        },
+        BlockType::Water => match face {
+            _ => 9, // Water texture
+        },
+        BlockType::Cobblestone => match face {
+            _ => 10, // Cobblestone texture
+        },
+        BlockType::Log => {
+            match face {
+                Face::Top | Face::Bottom => 6, // Wood rings
+                _ => 5, // Bark texture
+            }
+        },
+        BlockType::Snow => 11, // Snow texture
+        BlockType::Ice => 12,  // Ice texture
+        BlockType::Slab => 1,
+        BlockType::Stairs => 10,
+        BlockType::Tnt => match face {
+            Face::Top => 14,    // TNT top
+            Face::Bottom => 15, // TNT bottom
+            _ => 13,            // TNT side
+        },
+        BlockType::Flower => 16 + state.min(4) as u32,    // One texture per flower kind
+        BlockType::TallGrass => 21 + state.min(1) as u32, // Grass, fern
+        _ => 0, // Default stone texture for all other blocks
+    }
+}
+
+/// Light for a face, taken from the open block in front of it and scaled by the
+/// face's directional shade. Sky and block light stay separate; the shader combines
+/// them with the current daylight.
+fn calculate_light(pos: IVec3, face: Face, chunk: &Chunk, neighbors: &impl MeshNeighbors) -> FaceLight {
+    let adjacent = pos + IVec3::from(face.normal().map(|n| n as i32));
+
+    let (sky, block) = match local_position(chunk, adjacent) {
+        Some((x, y, z)) => (chunk.get_sky_light(x, y, z), chunk.get_block_light_rgb(x, y, z)),
+        // Outside the world (above the build limit) is open sky
+        None => (
+            neighbors.sky_light_at(adjacent).unwrap_or(15),
+            neighbors.block_light_at(adjacent).unwrap_or([0; 3]),
+        ),
+    };
+
+    FaceLight {
+        sky: sky as f32 / 15.0,
+        block: block.map(|channel| channel as f32 / 15.0),
+    }
+    .shaded(face.shade())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every block outside the chunk is `0`, with no light
+    struct FilledNeighbors(BlockType);
+
+    impl MeshNeighbors for FilledNeighbors {
+        fn block_at(&self, _pos: IVec3) -> Option<BlockType> {
+            Some(self.0)
+        }
+
+        fn sky_light_at(&self, _pos: IVec3) -> Option<u8> {
+            Some(0)
+        }
+
+        fn block_light_at(&self, _pos: IVec3) -> Option<[u8; 3]> {
+            Some([0; 3])
+        }
+    }
+
+    /// Nothing outside the chunk is loaded
+    struct Unloaded;
+
+    impl MeshNeighbors for Unloaded {
+        fn block_at(&self, _pos: IVec3) -> Option<BlockType> {
+            None
+        }
+
+        fn sky_light_at(&self, _pos: IVec3) -> Option<u8> {
+            None
+        }
+
+        fn block_light_at(&self, _pos: IVec3) -> Option<[u8; 3]> {
+            None
+        }
+    }
+
+    /// A chunk at the origin filled with `block` from the floor up to and including `top`
+    fn filled_chunk(block: BlockType, top: usize) -> Chunk {
+        let mut chunk = Chunk::new(ChunkCoordinate::new(0, 0));
+        for y in 0..=top {
+            for z in 0..CHUNK_SIZE {
+                for x in 0..CHUNK_SIZE {
+                    chunk.set_block(x, y, z, block);
+                }
+            }
+        }
+        chunk
+    }
+
+    #[test]
+    fn enclosed_chunk_is_hidden_but_surface_chunk_is_not() {
+        let neighbors = FilledNeighbors(BlockType::Stone);
+        let camera = Vec3::new(-20.0, 100.0, 8.0);
+
+        let enclosed = build_chunk_mesh(&filled_chunk(BlockType::Stone, CHUNK_HEIGHT - 1), &neighbors);
+        assert!(!enclosed.shell.is_open(Face::Left));
+        assert!(!enclosed.shell.is_visible_from(ChunkCoordinate::new(0, 0), camera));
+
+        let surface = build_chunk_mesh(&filled_chunk(BlockType::Stone, 64), &neighbors);
+        assert!(surface.shell.is_open(Face::Top));
+        assert!(surface.shell.is_visible_from(ChunkCoordinate::new(0, 0), camera));
+    }
+
+    #[test]
+    fn sky_exposed_face_keeps_sky_and_block_light_apart() {
+        let mut chunk = Chunk::new(ChunkCoordinate::new(0, 0));
+        chunk.set_block(8, 10, 8, BlockType::Stone);
+        chunk.set_block(9, 11, 8, BlockType::Torch);
+        chunk.calculate_lighting();
+
+        let mesh = build_chunk_mesh(&chunk, &FilledNeighbors(BlockType::Air));
+        let top: Vec<_> = mesh
+            .vertices
+            .iter()
+            .filter(|vertex| vertex.normal() == Face::Top.normal() && vertex.position()[1] == 11.0)
+            .filter(|vertex| vertex.position()[0] <= 9.0)
+            .collect();
+
+        assert_eq!(top.len(), 4);
+        let torch = BlockType::Torch.light_color().map(|channel| channel.saturating_sub(1) as f32 / 15.0);
+        for vertex in top {
+            assert_eq!(vertex.light(), FaceLight { sky: 1.0, block: torch }.shaded(Face::Top.shade()));
+        }
+    }
+
+    #[test]
+    fn faces_between_identical_see_through_blocks_are_culled() {
+        assert!(!face_visible(BlockType::Glass, BlockType::Glass));
+        assert!(!face_visible(BlockType::Leaves, BlockType::Leaves));
+        assert!(face_visible(BlockType::Glass, BlockType::Air));
+        assert!(face_visible(BlockType::Stone, BlockType::Leaves));
+        assert!(face_visible(BlockType::Stone, BlockType::Glass));
+        assert!(!face_visible(BlockType::Glass, BlockType::Stone));
+    }
+
+    #[test]
+    fn glass_pair_drops_its_inner_faces_but_stone_shows_behind_leaves() {
+        let air = FilledNeighbors(BlockType::Air);
+
+        let mut glass = Chunk::new(ChunkCoordinate::new(0, 0));
+        glass.set_block(4, 10, 4, BlockType::Glass);
+        glass.set_block(5, 10, 4, BlockType::Glass);
+        assert_eq!(build_chunk_mesh(&glass, &air).vertices.len(), 10 * 4);
+
+        let mut hedge = Chunk::new(ChunkCoordinate::new(0, 0));
+        hedge.set_block(4, 10, 4, BlockType::Stone);
+        hedge.set_block(5, 10, 4, BlockType::Leaves);
+        let mesh = build_chunk_mesh(&hedge, &air);
+        let stone_faces_leaves = mesh
+            .vertices
+            .iter()
+            .filter(|vertex| vertex.normal() == Face::Right.normal() && vertex.position()[0] == 5.0)
+            .count();
+        assert_eq!(stone_faces_leaves, 4);
+    }
+
+    #[test]
+    fn top_face_is_brightest_and_bottom_darkest() {
+        let mut chunk = filled_chunk(BlockType::Stone, 10);
+        chunk.set_block(8, 20, 8, BlockType::Stone);
+        chunk.calculate_lighting();
+
+        let mesh = build_chunk_mesh(&chunk, &FilledNeighbors(BlockType::Air));
+        let face_light = |face: Face| {
+            let lights: Vec<f32> = mesh
+                .vertices
+                .iter()
+                .filter(|vertex| vertex.normal() == face.normal() && (20.0..=21.0).contains(&vertex.position()[1]))
+                .map(|vertex| vertex.light().sky)
+                .collect();
+            assert_eq!(lights.len(), 4, "{face:?} face");
+            lights[0]
+        };
+
+        let top = face_light(Face::Top);
+        let bottom = face_light(Face::Bottom);
+        for side in [Face::Front, Face::Back] {
+            assert!(top > face_light(side) && face_light(side) > bottom, "{side:?} face");
+        }
+    }
+
+    #[test]
+    fn border_faces_wait_for_unloaded_neighbors() {
+        // A block in the chunk's corner touches the west and north borders
+        let mut chunk = Chunk::new(ChunkCoordinate::new(0, 0));
+        chunk.set_block(0, 10, 0, BlockType::Stone);
+
+        let loaded = build_chunk_mesh(&chunk, &FilledNeighbors(BlockType::Air));
+        let unloaded = build_chunk_mesh(&chunk, &Unloaded);
+
+        assert_eq!(loaded.vertices.len(), 6 * 4);
+        assert_eq!(unloaded.vertices.len(), 4 * 4);
+    }
+
+    #[test]
+    fn coarse_cell_is_drawn_as_its_top_opaque_block() {
+        let mut chunk = filled_chunk(BlockType::Dirt, 64);
+        chunk.set_block(0, 64, 0, BlockType::Grass);
+        chunk.set_block(1, 65, 1, BlockType::TallGrass);
+        chunk.set_block(0, 65, 0, BlockType::Leaves);
+
+        // Grass at y = 64 sits under tall grass and leaves in the cell from y = 64
+        assert_eq!(coarse_cell_block(&chunk, 0, 64, 0, 2), BlockType::Grass);
+    }
+
+    #[test]
+    fn coarse_cell_of_only_see_through_blocks_keeps_them() {
+        let mut chunk = filled_chunk(BlockType::Dirt, 63);
+        chunk.set_block(1, 64, 0, BlockType::Water);
+
+        assert_eq!(coarse_cell_block(&chunk, 0, 64, 0, 2), BlockType::Water);
+        assert_eq!(coarse_cell_block(&chunk, 0, 66, 0, 2), BlockType::Air);
+    }
+
+    #[test]
+    fn solid_cube_culls_interior_faces_and_hollow_cube_shows_its_cavity() {
+        let air = FilledNeighbors(BlockType::Air);
+        let mut solid = Chunk::new(ChunkCoordinate::new(0, 0));
+        for y in 10..14 {
+            for z in 4..8 {
+                for x in 4..8 {
+                    solid.set_block(x, y, z, BlockType::Stone);
+                }
+            }
+        }
+        let mut hollow = solid.clone();
+        for y in 11..13 {
+            for z in 5..7 {
+                for x in 5..7 {
+                    hollow.set_block(x, y, z, BlockType::Air);
+                }
+            }
+        }
+
+        // Sixteen faces on each side of the 4x4x4 cube, plus four on each side of the 2x2x2 cavity
+        assert_eq!(build_chunk_mesh(&solid, &air).face_count(), 6 * 16);
+        assert_eq!(build_chunk_mesh(&hollow, &air).face_count(), 6 * 16 + 6 * 4);
+    }
+
+    #[test]
+    fn single_block_emits_six_faces() {
+        let mut chunk = Chunk::new(ChunkCoordinate::new(0, 0));
+        chunk.set_block(8, 40, 8, BlockType::Stone);
+
+        let mesh = build_chunk_mesh(&chunk, &FilledNeighbors(BlockType::Air));
+
+        assert_eq!(mesh.face_count(), 6);
+        assert_eq!(mesh.vertices.len(), 6 * 4);
+        for face in Face::all() {
+            assert_eq!(mesh.vertices.iter().filter(|vertex| vertex.normal() == face.normal()).count(), 4, "{face:?} face");
+        }
+    }
+
+    #[test]
+    fn cross_shaped_plant_emits_two_quads_instead_of_a_cube() {
+        let mut chunk = Chunk::new(ChunkCoordinate::new(0, 0));
+        chunk.set_block(8, 40, 8, BlockType::Flower);
+
+        let mesh = build_chunk_mesh(&chunk, &FilledNeighbors(BlockType::Air));
+
+        assert_eq!(mesh.vertices.len(), 8);
+        // Each quad is wound both ways so it shows from either side
+        assert_eq!(mesh.indices.len(), 2 * 2 * 6);
+    }
+
+    #[test]
+    fn vertices_of_partial_blocks_name_their_block() {
+        let mut chunk = Chunk::new(ChunkCoordinate::new(1, 0));
+        chunk.set_block(3, 20, 4, BlockType::Slab);
+        chunk.set_block(5, 20, 4, BlockType::Slab);
+        chunk.set_block_state(5, 20, 4, 1);
+        chunk.set_block(7, 20, 4, BlockType::TallGrass);
+
+        let mesh = build_chunk_mesh(&chunk, &FilledNeighbors(BlockType::Air));
+
+        let mut blocks: Vec<[i32; 3]> = mesh.vertices.iter().map(|vertex| vertex.block()).collect();
+        blocks.dedup();
+        assert_eq!(blocks, vec![[19, 20, 4], [21, 20, 4], [23, 20, 4]]);
+    }
+
+    #[test]
+    fn floor_block_has_no_bottom_face_but_a_floating_one_does() {
+        let air = FilledNeighbors(BlockType::Air);
+        let bottom_faces = |chunk: &Chunk| {
+            build_chunk_mesh(chunk, &air)
+                .vertices
+                .iter()
+                .filter(|vertex| vertex.normal() == Face::Bottom.normal())
+                .count()
+                / 4
+        };
+
+        let mut floor = Chunk::new(ChunkCoordinate::new(0, 0));
+        floor.set_block(8, 0, 8, BlockType::Stone);
+        assert_eq!(bottom_faces(&floor), 0);
+
+        let mut floating = Chunk::new(ChunkCoordinate::new(0, 0));
+        floating.set_block(8, 5, 8, BlockType::Stone);
+        assert_eq!(bottom_faces(&floating), 1);
+    }
+
+    #[test]
+    fn surface_mesh_has_at_most_one_top_face_per_column() {
+        let chunk = filled_chunk(BlockType::Stone, 30);
+
+        let mesh = build_surface_chunk_mesh(&chunk);
+
+        assert_eq!(mesh.vertices.len(), CHUNK_SIZE * CHUNK_SIZE * 4);
+        assert!(mesh.vertices.iter().all(|vertex| vertex.normal() == Face::Top.normal()));
+    }
+
+    #[test]
+    fn surface_mesh_draws_the_ground_under_plants() {
+        let mut chunk = Chunk::new(ChunkCoordinate::new(0, 0));
+        chunk.set_block(3, 40, 3, BlockType::Grass);
+        chunk.set_block(3, 41, 3, BlockType::Flower);
+        chunk.set_block(5, 20, 5, BlockType::Water);
+
+        let mesh = build_surface_chunk_mesh(&chunk);
+
+        let mut tops: Vec<f32> = mesh.vertices.iter().map(|vertex| vertex.position()[1]).collect();
+        tops.dedup();
+        assert_eq!(mesh.vertices.len(), 2 * 4);
+        assert!(tops.contains(&41.0) && !tops.contains(&42.0), "tops at {tops:?}");
+    }
+}
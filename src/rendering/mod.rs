@@ -1,20 +1,39 @@
 use anyhow::Result;
+use std::sync::Arc;
+use std::time::Instant;
 use wgpu::util::DeviceExt;
 use winit::{dpi::PhysicalSize, window::Window};
 
 pub mod camera;
+mod adapter;
 mod texture;
 mod vertex;
 mod shader;
 mod skybox;
 mod chunk_renderer;
-
-pub use camera::Camera;
-pub use texture::{Texture, TextureAtlas};
-pub use vertex::{Vertex, BlockVertex};
-pub use chunk_renderer::ChunkRenderer;
-
-use crate::world::World;
+mod mesher;
+mod picking;
+mod weather;
+mod target;
+mod entity_renderer;
+
+pub use adapter::{select_adapter, GraphicsSettings};
+pub use camera::{Camera, LookSettings};
+pub use texture::{atlas_tile_uv, clamp_anisotropy, Texture, TextureAtlas, MAX_ANISOTROPY};
+pub use vertex::{Vertex, BlockVertex, ChunkMesh, Face};
+pub use chunk_renderer::{ChunkPass, ChunkRenderer, SharedBindPass};
+pub use mesher::{build_chunk_mesh, texture_id_for_block, ChunkShell, MeshData, MeshNeighbors};
+pub use picking::{decode_pick_id, encode_pick_id, BlockPick};
+pub use weather::{weather_particles, Precipitation, WeatherParticle, MAX_WEATHER_PARTICLES};
+pub use target::{breaking_overlay, target_outline, TargetVertex};
+pub use entity_renderer::{entity_box, entity_box_vertices, entity_boxes, primed_tnt_box, EntityBox, EntityVertex, MAX_ENTITY_BOXES};
+
+use picking::PickPass;
+use skybox::Skybox;
+use weather::WeatherRenderer;
+use target::TargetRenderer;
+use entity_renderer::EntityRenderer;
+use crate::world::{BlockChangeEvent, ChunkCoordinate, World, CHUNK_SIZE};
 use crate::game::GameManager;
 use crate::ui::UIManager;
 
@@ -30,10 +49,89 @@ pub struct Renderer {
     texture_atlas: TextureAtlas,
     chunk_renderer: ChunkRenderer,
     skybox_pipeline: wgpu::RenderPipeline,
+    skybox: Skybox,
     camera: Camera,
     camera_uniform: CameraUniform,
     camera_buffer: wgpu::Buffer,
     camera_bind_group: wgpu::BindGroup,
+    render_stats: RenderStats,
+    last_frame: Option<Instant>,
+    /// Start of the clock that drives plant sway and falling weather
+    start_time: Instant,
+    /// Width in blocks of the band at the edge of render distance where chunks fade out
+    fade_band: f32,
+    /// Whether the device can filter textures anisotropically
+    anisotropy_supported: bool,
+    picking: PickPass,
+    weather: WeatherRenderer,
+    /// Outline of the targeted block and the breaking overlay
+    target: TargetRenderer,
+    entities: EntityRenderer,
+    /// Run the GPU pick pass each frame; off by default since it costs a readback
+    gpu_picking: bool,
+    /// Color the frame is cleared to before the skybox and terrain are drawn
+    clear_color: wgpu::Color,
+    /// Minimum brightness of any face, 0-1, so unlit caves aren't pure black
+    ambient_light: f32,
+}
+
+/// Default width of the render-distance fade band, one chunk
+pub const DEFAULT_FADE_BAND: f32 = CHUNK_SIZE as f32;
+
+/// Sway clock wraps after this many seconds to keep f32 precision; a whole number of
+/// sway periods (π seconds in block.wgsl) so plants don't jump when it does
+const SWAY_TIME_WRAP: f32 = std::f32::consts::PI * 512.0;
+
+/// Default minimum brightness of a face with no sky or block light
+pub const DEFAULT_AMBIENT_LIGHT: f32 = 0.1;
+
+/// Default clear color, the daytime sky blue
+pub const DEFAULT_CLEAR_COLOR: wgpu::Color = wgpu::Color { r: 0.5, g: 0.8, b: 1.0, a: 1.0 };
+
+/// Opacity of geometry at a horizontal `distance` from the camera: 1.0 up to the
+/// start of the fade band, ramping linearly to 0.0 at `edge`. Mirrors `distance_fade`
+/// in block.wgsl.
+pub fn distance_fade(distance: f32, edge: f32, band: f32) -> f32 {
+    if band <= 0.0 {
+        return if distance <= edge { 1.0 } else { 0.0 };
+    }
+    ((edge - distance) / band).clamp(0.0, 1.0)
+}
+
+/// 4x4 ordered dither thresholds used to fade terrain without blending. Mirrors
+/// `fade_threshold` in block.wgsl.
+const FADE_DITHER: [u8; 16] = [0, 8, 2, 10, 12, 4, 14, 6, 3, 11, 1, 9, 15, 7, 13, 5];
+
+/// Whether a terrain pixel with the given `fade` survives the dithered edge fade.
+/// Across each 4x4 block of pixels the share that survives follows `fade`.
+pub fn fade_keeps_pixel(fade: f32, pixel_x: u32, pixel_y: u32) -> bool {
+    let index = (pixel_y % 4 * 4 + pixel_x % 4) as usize;
+    let threshold = (FADE_DITHER[index] as f32 + 0.5) / 16.0;
+    fade >= threshold
+}
+
+/// Light multiplier for a face's texture color: block light or daylight-scaled sky
+/// light, whichever is brighter, lifted so nothing drops below `ambient`. Mirrors
+/// `light_color` in block.wgsl.
+pub fn face_light_color(block_light: [f32; 3], sky_light: f32, daylight: f32, ambient: f32) -> [f32; 3] {
+    let sky = sky_light * daylight;
+    block_light.map(|channel| ambient + (1.0 - ambient) * channel.max(sky))
+}
+
+/// Render counters for the most recent frame, for profiling and the debug overlay
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderStats {
+    /// Chunk meshes that issued a draw
+    pub chunks_drawn: u32,
+    /// Loaded chunks skipped because nothing in them could be seen
+    pub chunks_culled: u32,
+    /// Draws of world geometry: chunks, entities, the block outline and weather.
+    /// The skybox is drawn every frame regardless, so it isn't counted.
+    pub draw_calls: u32,
+    pub triangles: u32,
+    pub atlas_binds: u32,
+    /// Time since the previous frame started, in milliseconds
+    pub frame_time_ms: f32,
 }
 
 #[repr(C)]
@@ -41,6 +139,13 @@ pub struct Renderer {
 struct CameraUniform {
     view_proj: [[f32; 4]; 4],
     view_pos: [f32; 4],
+    /// x: daylight multiplier applied to sky light, y: ambient light floor,
+    /// z: animation clock in seconds, w: padding
+    sky_light: [f32; 4],
+    /// x: distance where geometry is fully faded, y: fade band width, zw: padding
+    fade: [f32; 4],
+    /// View-projection without translation, for the skybox
+    sky_view_proj: [[f32; 4]; 4],
 }
 
 impl CameraUniform {
@@ -48,17 +153,25 @@ impl CameraUniform {
         Self {
             view_proj: glam::Mat4::IDENTITY.to_cols_array_2d(),
             view_pos: [0.0; 4],
+            sky_light: [1.0, DEFAULT_AMBIENT_LIGHT, 0.0, 0.0],
+            fade: [f32::MAX, 0.0, 0.0, 0.0],
+            sky_view_proj: glam::Mat4::IDENTITY.to_cols_array_2d(),
         }
     }
 
     fn update_view_proj(&mut self, camera: &Camera) {
         self.view_proj = camera.build_view_projection_matrix().to_cols_array_2d();
         self.view_pos = [camera.position().x, camera.position().y, camera.position().z, 1.0];
+        self.sky_view_proj = camera.build_sky_view_projection_matrix().to_cols_array_2d();
     }
 }
 
 impl Renderer {
-    pub async fn new(window: &Window) -> Result<Self> {
+    pub async fn new(window: Arc<Window>) -> Result<Self> {
+        Self::with_settings(window, GraphicsSettings::default()).await
+    }
+
+    pub async fn with_settings(window: Arc<Window>, settings: GraphicsSettings) -> Result<Self> {
         let size = window.inner_size();
 
         // Create wgpu instance
@@ -69,18 +182,11 @@ impl Renderer {
             gles_minor_version: wgpu::Gles3MinorVersion::Automatic,
         });
 
-        // Create surface - use window handle to avoid lifetime issues
+        // The surface keeps its own handle to the window, which lets it be stored as 'static
         let surface = instance.create_surface(window)?;
 
         // Get adapter
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-            .ok_or_else(|| anyhow::anyhow!("Failed to create adapter"))?;
+        let adapter = select_adapter(&instance, Some(&surface), &settings).await?;
 
         // Get device and queue
         let (device, queue) = adapter
@@ -93,6 +199,10 @@ impl Renderer {
                 None,
             )
             .await?;
+        let anisotropy_supported = adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::ANISOTROPIC_FILTERING);
 
         // Configure surface
         let surface_caps = surface.get_capabilities(&adapter);
@@ -158,13 +268,19 @@ impl Renderer {
         });
 
         // Load shaders
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/block.wgsl").into()),
-        });
+        let shader = shader::validated(&device, || {
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("shaders/block.wgsl").into()),
+            })
+        })?;
 
         // Create texture atlas
-        let texture_atlas = TextureAtlas::new(&device, &queue)?;
+        let texture_atlas = TextureAtlas::new(
+            &device,
+            &queue,
+            clamp_anisotropy(settings.anisotropy, anisotropy_supported),
+        )?;
 
         // Create render pipeline
         let render_pipeline_layout =
@@ -174,98 +290,124 @@ impl Renderer {
                 push_constant_ranges: &[],
             });
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                buffers: &[BlockVertex::desc()],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: Some(wgpu::DepthStencilState {
-                format: Texture::DEPTH_FORMAT,
-                depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::Less,
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
-            }),
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-        });
+        let render_pipeline = shader::validated(&device, || {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Render Pipeline"),
+                layout: Some(&render_pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[BlockVertex::desc()],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: config.format,
+                        // Terrain is opaque; the edge fade discards pixels in a dither
+                        // pattern rather than blending, so depth stays correct
+                        blend: Some(wgpu::BlendState::REPLACE),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: Some(wgpu::Face::Back),
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: Texture::DEPTH_FORMAT,
+                    depth_write_enabled: true,
+                    depth_compare: wgpu::CompareFunction::Less,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+            })
+        })?;
 
         // Create depth texture
         let depth_texture = Texture::create_depth_texture(&device, &config, "depth_texture");
 
         // Create chunk renderer
         let chunk_renderer = ChunkRenderer::new(&device, &render_pipeline_layout);
-
-        // Create skybox pipeline (simplified for now)
-        let skybox_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Skybox Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                buffers: &[BlockVertex::desc()],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                unclipped_depth: false,
-                polygon_mode: wgpu::PolygonMode::Fill,
-                conservative: false,
-            },
-            depth_stencil: Some(wgpu::DepthStencilState {
-                format: texture::Texture::DEPTH_FORMAT,
-                depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::Less,
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
-            }),
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-        });
+        let picking = PickPass::new(&device, &camera_bind_group_layout)?;
+        let weather = WeatherRenderer::new(&device, &camera_bind_group_layout, config.format)?;
+        let target = TargetRenderer::new(&device, &camera_bind_group_layout, config.format)?;
+        let entities = EntityRenderer::new(&device, &camera_bind_group_layout, config.format)?;
+
+        // Create skybox pipeline. The sky is drawn first, from inside the cube, at the
+        // far plane and without touching depth, so everything else draws over it
+        let sky_shader = shader::validated(&device, || {
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Sky Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("shaders/sky.wgsl").into()),
+            })
+        })?;
+        // The sky only reads the camera, so it is drawn without the atlas bound
+        let skybox_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Skybox Pipeline Layout"),
+                bind_group_layouts: &[&camera_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let skybox_pipeline = shader::validated(&device, || {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Skybox Render Pipeline"),
+                layout: Some(&skybox_pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &sky_shader,
+                    entry_point: "vs_main",
+                    buffers: &[BlockVertex::desc()],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &sky_shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: config.format,
+                        blend: Some(wgpu::BlendState::REPLACE),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    // Seen from inside, so the cube's outward faces are back faces
+                    cull_mode: None,
+                    unclipped_depth: false,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    conservative: false,
+                },
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: texture::Texture::DEPTH_FORMAT,
+                    depth_write_enabled: false,
+                    depth_compare: wgpu::CompareFunction::Always,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+            })
+        })?;
+        let skybox = Skybox::new(&device);
 
         Ok(Self {
             surface,
@@ -278,10 +420,23 @@ impl Renderer {
             texture_atlas,
             chunk_renderer,
             skybox_pipeline,
+            skybox,
             camera,
             camera_uniform,
             camera_buffer,
             camera_bind_group,
+            render_stats: RenderStats::default(),
+            last_frame: None,
+            start_time: Instant::now(),
+            fade_band: DEFAULT_FADE_BAND,
+            clear_color: DEFAULT_CLEAR_COLOR,
+            ambient_light: DEFAULT_AMBIENT_LIGHT,
+            anisotropy_supported,
+            picking,
+            weather,
+            target,
+            entities,
+            gpu_picking: false,
         })
     }
 
@@ -294,10 +449,19 @@ impl Renderer {
             
             // Recreate depth texture
             self.depth_texture = texture::Texture::create_depth_texture(&self.device, &self.config, "depth_texture");
+
+            // Keep the projection from stretching
+            self.camera.resize(new_size.width, new_size.height);
         }
         Ok(())
     }
 
+    /// Configure the surface again with the current settings, even if the size
+    /// hasn't changed. Needed after the surface is lost or outdated.
+    pub fn reconfigure(&mut self) {
+        self.surface.configure(&self.device, &self.config);
+    }
+
     pub fn size(&self) -> PhysicalSize<u32> {
         self.size
     }
@@ -318,19 +482,46 @@ impl Renderer {
         game_manager: &GameManager,
         ui_manager: &mut UIManager,
     ) -> Result<()> {
+        let frame_start = Instant::now();
+        let frame_time_ms = self
+            .last_frame
+            .map_or(0.0, |last| frame_start.duration_since(last).as_secs_f32() * 1000.0);
+        self.last_frame = Some(frame_start);
+
         let output = self.surface.get_current_texture()?;
         let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
 
-        // Prepare UI and get primitives
-        let primitives = ui_manager.prepare(window);
+        // Prepare UI and get primitives, showing the previous frame's stats
+        let looking_at = game_manager.targeted_block().map(|(_, block, _)| block);
+        let primitives = ui_manager.prepare(window, &self.render_stats, world, game_manager.player(), looking_at);
         let screen_descriptor = egui_wgpu::ScreenDescriptor {
             size_in_pixels: [self.config.width, self.config.height],
             pixels_per_point: window.scale_factor() as f32,
         };
 
+        // Upload the camera and bring chunk meshes up to date before recording the pass
+        self.camera_uniform.update_view_proj(camera);
+        let animation_time = self.start_time.elapsed().as_secs_f32() % SWAY_TIME_WRAP;
+        self.camera_uniform.sky_light = [world.daylight(), self.ambient_light, animation_time, 0.0];
+        let fade_edge = (world.render_distance() * CHUNK_SIZE as i32) as f32;
+        self.camera_uniform.fade = [fade_edge, self.fade_band, 0.0, 0.0];
+        self.queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[self.camera_uniform]));
+        self.chunk_renderer.prepare_frame(&self.device, world, camera.position());
+        self.weather.prepare(&self.queue, world, camera.position(), animation_time);
+        self.entities.prepare(&self.queue, world);
+        let breaking = game_manager.breaking_target().map(|block| (block, game_manager.breaking_progress()));
+        self.target.prepare(&self.queue, game_manager.targeted_block().map(|(block, _, _)| block), breaking);
+        if self.gpu_picking {
+            self.picking.collect(&self.device);
+        }
+
         let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Render Encoder"),
         });
+        let mut render_stats = RenderStats {
+            frame_time_ms,
+            ..RenderStats::default()
+        };
 
         // Main render pass
         {
@@ -340,12 +531,7 @@ impl Renderer {
                     view: &view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.5,
-                            g: 0.8,
-                            b: 1.0,
-                            a: 1.0,
-                        }),
+                        load: wgpu::LoadOp::Clear(self.clear_color),
                         store: wgpu::StoreOp::Store,
                     },
                 })],
@@ -361,20 +547,136 @@ impl Renderer {
                 occlusion_query_set: None,
             });
 
-            // Render world chunks
+            // Render the sky, then world chunks. The chunk renderer binds the camera and
+            // atlas once for all chunks
+            render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+            render_pass.set_pipeline(&self.skybox_pipeline);
+            self.skybox.render(&mut render_pass);
+
             render_pass.set_pipeline(&self.render_pipeline);
-            // TODO: Implement actual chunk rendering
+            let shared = [&self.camera_bind_group, self.texture_atlas.bind_group()];
+            self.chunk_renderer.render(
+                &mut SharedBindPass::new(&mut render_pass, &shared),
+                world,
+                camera.position(),
+                &mut render_stats,
+            );
+            if self.entities.render(&mut render_pass) {
+                render_stats.draw_calls += 1;
+            }
+            render_stats.draw_calls += self.target.render(&mut render_pass);
+
+            // Weather goes last so it blends over the terrain behind it
+            if self.weather.render(&mut render_pass) {
+                render_stats.draw_calls += 1;
+            }
+        }
+        self.render_stats = render_stats;
+
+        let pick_recorded = self.gpu_picking && self.picking.prepare(&self.device, &self.config);
+        if pick_recorded {
+            self.picking.encode(&mut encoder, &self.camera_bind_group, &self.chunk_renderer, world, camera.position());
         }
 
         // Render UI
         ui_manager.render(&mut encoder, &view, primitives, &screen_descriptor, &self.device, &self.queue);
 
         self.queue.submit(std::iter::once(encoder.finish()));
+        if pick_recorded {
+            self.picking.request_readback();
+        }
         output.present();
 
         Ok(())
     }
 
+    /// Rebuild a chunk's mesh, and its neighbors' whose border faces depend on it,
+    /// after the chunk was replaced wholesale
+    pub fn remesh_chunk(&mut self, chunk_coord: ChunkCoordinate) {
+        self.chunk_renderer.mark_chunk_dirty(chunk_coord);
+        for neighbor in chunk_coord.neighbors() {
+            self.chunk_renderer.mark_chunk_dirty(neighbor);
+        }
+    }
+
+    /// Rebuild a chunk's mesh before the next frame
+    pub fn mark_chunk_dirty(&mut self, chunk_coord: ChunkCoordinate) {
+        self.chunk_renderer.mark_chunk_dirty(chunk_coord);
+    }
+
+    /// Rebuild the meshes affected by a block change before the next frame
+    pub fn handle_block_change(&mut self, change: &BlockChangeEvent) {
+        self.chunk_renderer.handle_block_change(change);
+    }
+
+    pub fn fade_band(&self) -> f32 {
+        self.fade_band
+    }
+
+    /// Set how wide (in blocks) the fade at the edge of render distance is; 0 gives
+    /// a hard cutoff
+    pub fn set_fade_band(&mut self, band: f32) {
+        self.fade_band = band.max(0.0);
+    }
+
+    pub fn clear_color(&self) -> wgpu::Color {
+        self.clear_color
+    }
+
+    pub fn set_clear_color(&mut self, color: wgpu::Color) {
+        self.clear_color = color;
+    }
+
+    pub fn ambient_light(&self) -> f32 {
+        self.ambient_light
+    }
+
+    /// Set the minimum brightness of unlit faces, 0-1. 0 lets caves go fully black.
+    pub fn set_ambient_light(&mut self, ambient: f32) {
+        self.ambient_light = ambient.clamp(0.0, 1.0);
+    }
+
+    /// Anisotropic filtering level the block atlas is sampled with; 1 means off
+    pub fn anisotropy(&self) -> u16 {
+        self.texture_atlas.anisotropy()
+    }
+
+    /// Request 2x, 4x, 8x or 16x anisotropic filtering for the block atlas, or 1 to
+    /// turn it off. Returns the level actually used, which is limited by the device.
+    pub fn set_anisotropy(&mut self, requested: u16) -> u16 {
+        let anisotropy = clamp_anisotropy(requested, self.anisotropy_supported);
+        self.texture_atlas.set_anisotropy(&self.device, anisotropy);
+        anisotropy
+    }
+
+    pub fn is_gpu_picking(&self) -> bool {
+        self.gpu_picking
+    }
+
+    /// Turn the GPU pick pass on or off. It identifies the exact block face under
+    /// the crosshair but reads back from the GPU every frame.
+    pub fn set_gpu_picking(&mut self, enabled: bool) {
+        self.gpu_picking = enabled;
+    }
+
+    /// Block face under the crosshair from the GPU pick pass, about a frame behind.
+    /// `None` while picking is off or nothing is targeted.
+    pub fn gpu_pick(&self) -> Option<BlockPick> {
+        if self.gpu_picking {
+            self.picking.last_pick()
+        } else {
+            None
+        }
+    }
+
+    pub fn render_stats(&self) -> RenderStats {
+        self.render_stats
+    }
+
+    pub fn texture_atlas(&self) -> &TextureAtlas {
+        &self.texture_atlas
+    }
+
     pub fn device(&self) -> &wgpu::Device {
         &self.device
     }
@@ -386,4 +688,76 @@ impl Renderer {
     pub fn surface_format(&self) -> wgpu::TextureFormat {
         self.config.format
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fade_is_opaque_inside_the_band_and_ramps_to_zero_at_the_edge() {
+        let (edge, band) = (128.0, 16.0);
+
+        assert_eq!(distance_fade(0.0, edge, band), 1.0);
+        assert_eq!(distance_fade(edge - band, edge, band), 1.0);
+        assert_eq!(distance_fade(edge - band / 2.0, edge, band), 0.5);
+        assert_eq!(distance_fade(edge, edge, band), 0.0);
+        assert_eq!(distance_fade(edge + 10.0, edge, band), 0.0);
+    }
+
+    #[test]
+    fn zero_band_is_a_hard_cutoff() {
+        assert_eq!(distance_fade(127.9, 128.0, 0.0), 1.0);
+        assert_eq!(distance_fade(128.1, 128.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn dithered_fade_keeps_a_matching_share_of_pixels() {
+        let kept = |fade: f32| {
+            (0..4).flat_map(|y| (0..4).map(move |x| (x, y))).filter(|&(x, y)| fade_keeps_pixel(fade, x, y)).count()
+        };
+
+        assert_eq!(kept(1.0), 16);
+        assert_eq!(kept(0.5), 8);
+        assert_eq!(kept(0.25), 4);
+        assert_eq!(kept(0.0), 0);
+        // The pattern repeats every four pixels
+        assert_eq!(fade_keeps_pixel(0.5, 1, 2), fade_keeps_pixel(0.5, 5, 10));
+    }
+
+    #[test]
+    fn moving_the_camera_changes_the_uploaded_view_projection() {
+        let mut camera = Camera::new(glam::Vec3::new(0.0, 70.0, 0.0), -90.0, 0.0, 16.0 / 9.0);
+        let mut uniform = CameraUniform::new();
+        uniform.update_view_proj(&camera);
+        let before = uniform.view_proj;
+
+        camera.process_movement(1.0, 0.0, 0.0, 0.5);
+        uniform.update_view_proj(&camera);
+
+        assert_ne!(uniform.view_proj, before);
+        assert_eq!(uniform.view_proj, camera.build_view_projection_matrix().to_cols_array_2d());
+    }
+
+    #[test]
+    fn uploaded_bytes_start_with_the_camera_matrix() {
+        let camera = Camera::new(glam::Vec3::new(3.0, 80.0, -7.0), 45.0, -20.0, 4.0 / 3.0);
+        let mut uniform = CameraUniform::new();
+        uniform.update_view_proj(&camera);
+
+        let uploaded = bytemuck::bytes_of(&uniform);
+        let matrix = camera.build_view_projection_matrix().to_cols_array();
+        assert_eq!(&uploaded[..64], bytemuck::cast_slice::<f32, u8>(&matrix));
+    }
+
+    #[test]
+    fn unlit_faces_are_lifted_to_the_ambient_floor() {
+        let unlit = face_light_color([0.0; 3], 0.0, 1.0, DEFAULT_AMBIENT_LIGHT);
+        assert!(unlit.iter().all(|&channel| channel >= DEFAULT_AMBIENT_LIGHT));
+
+        // A zero floor lets caves go fully black
+        assert_eq!(face_light_color([0.0; 3], 0.0, 1.0, 0.0), [0.0; 3]);
+        // Full light is unaffected by the floor
+        assert_eq!(face_light_color([1.0; 3], 0.0, 1.0, 0.3), [1.0; 3]);
+    }
+}
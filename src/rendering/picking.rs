@@ -0,0 +1,334 @@
+// GPU block picking: renders block face ids to an offscreen integer target and reads
+// back the pixel under the crosshair. Costs a readback, so it is off by default.
+
+use anyhow::Result;
+use glam::{IVec3, Vec3};
+use std::sync::{Arc, Mutex};
+
+use crate::rendering::vertex::{BlockVertex, Face, Vertex};
+use crate::rendering::{shader, ChunkRenderer, RenderStats, SharedBindPass, Texture};
+use crate::world::{World, CHUNK_HEIGHT};
+
+/// Set on every id that refers to a block; a cleared pixel reads back as 0
+const PICK_HIT_BIT: u32 = 1 << 31;
+
+/// Largest horizontal distance from the origin, in blocks, an id can encode
+const PICK_RANGE: i32 = 127;
+
+/// Copies to a buffer must have rows aligned to this many bytes
+const READBACK_ROW_BYTES: u32 = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+/// A block face found by picking
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockPick {
+    pub position: IVec3,
+    pub face: Face,
+}
+
+/// Pack a block position and face into a pick id. The position is stored relative
+/// to `origin` (the camera's block), so only blocks within 127 blocks horizontally
+/// and inside the world's height fit; others return `None`. Mirrors `fs_main` in
+/// pick.wgsl.
+pub fn encode_pick_id(position: IVec3, face: Face, origin: IVec3) -> Option<u32> {
+    let offset = position - origin;
+    if offset.x.abs() > PICK_RANGE || offset.z.abs() > PICK_RANGE {
+        return None;
+    }
+    if position.y < 0 || position.y >= CHUNK_HEIGHT as i32 {
+        return None;
+    }
+
+    let face_index = Face::all().iter().position(|&f| f == face)? as u32;
+    Some(
+        PICK_HIT_BIT
+            | face_index << 24
+            | (position.y as u32) << 16
+            | ((offset.z + 128) as u32) << 8
+            | (offset.x + 128) as u32,
+    )
+}
+
+/// Unpack an id written by `encode_pick_id` or the pick shader. 0 (nothing drawn)
+/// and malformed ids give `None`.
+pub fn decode_pick_id(id: u32, origin: IVec3) -> Option<BlockPick> {
+    if id & PICK_HIT_BIT == 0 {
+        return None;
+    }
+
+    let face = *Face::all().get(((id >> 24) & 0x7f) as usize)?;
+    let y = ((id >> 16) & 0xff) as i32;
+    let dz = ((id >> 8) & 0xff) as i32 - 128;
+    let dx = (id & 0xff) as i32 - 128;
+    Some(BlockPick {
+        position: IVec3::new(origin.x + dx, y, origin.z + dz),
+        face,
+    })
+}
+
+/// Offscreen targets the pick pass draws into, sized to the surface
+struct PickTargets {
+    ids: wgpu::Texture,
+    ids_view: wgpu::TextureView,
+    depth: Texture,
+    width: u32,
+    height: u32,
+}
+
+/// Renders pick ids and reads back the center pixel one frame later
+pub struct PickPass {
+    pipeline: wgpu::RenderPipeline,
+    targets: Option<PickTargets>,
+    readback: wgpu::Buffer,
+    /// Set by the map callback: whether mapping the readback buffer succeeded
+    map_result: Arc<Mutex<Option<bool>>>,
+    /// Origin of the pick whose copy is in flight
+    pending_origin: Option<IVec3>,
+    last_pick: Option<BlockPick>,
+}
+
+impl PickPass {
+    pub fn new(device: &wgpu::Device, camera_bind_group_layout: &wgpu::BindGroupLayout) -> Result<Self> {
+        let shader = shader::validated(device, || {
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Pick Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("shaders/pick.wgsl").into()),
+            })
+        })?;
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Pick Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = shader::validated(device, || {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Pick Pipeline"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[BlockVertex::desc()],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: wgpu::TextureFormat::R32Uint,
+                        // Integer targets can't blend
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: Some(wgpu::Face::Back),
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: Texture::DEPTH_FORMAT,
+                    depth_write_enabled: true,
+                    depth_compare: wgpu::CompareFunction::Less,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+            })
+        })?;
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Pick Readback Buffer"),
+            size: READBACK_ROW_BYTES as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Ok(Self {
+            pipeline,
+            targets: None,
+            readback,
+            map_result: Arc::new(Mutex::new(None)),
+            pending_origin: None,
+            last_pick: None,
+        })
+    }
+
+    /// Block face under the crosshair as of the last completed readback
+    pub fn last_pick(&self) -> Option<BlockPick> {
+        self.last_pick
+    }
+
+    /// Pick up a finished readback, if any, without blocking
+    pub fn collect(&mut self, device: &wgpu::Device) {
+        let origin = match self.pending_origin {
+            Some(origin) => origin,
+            None => return,
+        };
+        device.poll(wgpu::Maintain::Poll);
+        let mapped = match self.map_result.lock().unwrap().take() {
+            Some(mapped) => mapped,
+            None => return,
+        };
+        self.pending_origin = None;
+        if !mapped {
+            return;
+        }
+
+        let id = {
+            let data = self.readback.slice(..).get_mapped_range();
+            u32::from_le_bytes([data[0], data[1], data[2], data[3]])
+        };
+        self.readback.unmap();
+        self.last_pick = decode_pick_id(id, origin);
+    }
+
+    /// Get ready to pick this frame, sizing the offscreen targets to the surface.
+    /// Returns false while the previous readback is still in flight; otherwise call
+    /// `encode`.
+    pub fn prepare(&mut self, device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> bool {
+        if self.pending_origin.is_some() {
+            return false;
+        }
+
+        let stale = self
+            .targets
+            .as_ref()
+            .is_none_or(|targets| targets.width != config.width || targets.height != config.height);
+        if stale {
+            self.targets = Some(Self::create_targets(device, config));
+        }
+        true
+    }
+
+    /// Draw pick ids for the center pixel and copy it out. Call after `prepare`
+    /// returned true, then `request_readback` after submitting.
+    pub fn encode(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        camera_bind_group: &wgpu::BindGroup,
+        chunk_renderer: &ChunkRenderer,
+        world: &World,
+        camera_position: Vec3,
+    ) {
+        let targets = self.targets.as_ref().expect("PickPass::prepare creates the targets");
+        let (center_x, center_y) = (targets.width / 2, targets.height / 2);
+
+        {
+            let mut pick_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Pick Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &targets.ids_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &targets.depth.view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            // Only the crosshair pixel is read, so skip shading everything else
+            pick_pass.set_scissor_rect(center_x, center_y, 1, 1);
+            pick_pass.set_pipeline(&self.pipeline);
+            chunk_renderer.render(
+                &mut SharedBindPass::new(&mut pick_pass, &[camera_bind_group]),
+                world,
+                camera_position,
+                &mut RenderStats::default(),
+            );
+        }
+
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: &targets.ids,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x: center_x, y: center_y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &self.readback,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(READBACK_ROW_BYTES),
+                    rows_per_image: Some(1),
+                },
+            },
+            wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+        );
+        self.pending_origin = Some(camera_position.floor().as_ivec3());
+    }
+
+    /// Start mapping the copied pixel; `collect` reads it once the GPU is done
+    pub fn request_readback(&self) {
+        let map_result = Arc::clone(&self.map_result);
+        self.readback.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            *map_result.lock().unwrap() = Some(result.is_ok());
+        });
+    }
+
+    fn create_targets(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> PickTargets {
+        let ids = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Pick Id Texture"),
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R32Uint,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let ids_view = ids.create_view(&wgpu::TextureViewDescriptor::default());
+
+        PickTargets {
+            ids,
+            ids_view,
+            depth: Texture::create_depth_texture(device, config, "pick_depth_texture"),
+            width: config.width,
+            height: config.height,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pick_ids_round_trip_within_range() {
+        let origin = IVec3::new(-1000, 70, 5000);
+        for (offset, face) in [
+            (IVec3::new(0, 0, 0), Face::Top),
+            (IVec3::new(-PICK_RANGE, -70, PICK_RANGE), Face::Left),
+            (IVec3::new(PICK_RANGE, CHUNK_HEIGHT as i32 - 71, -PICK_RANGE), Face::Bottom),
+        ] {
+            let position = origin + offset;
+            let id = encode_pick_id(position, face, origin).unwrap();
+            assert_eq!(decode_pick_id(id, origin), Some(BlockPick { position, face }));
+        }
+    }
+
+    #[test]
+    fn out_of_range_blocks_and_cleared_pixels_have_no_pick() {
+        let origin = IVec3::new(0, 70, 0);
+        assert_eq!(encode_pick_id(IVec3::new(PICK_RANGE + 1, 70, 0), Face::Top, origin), None);
+        assert_eq!(encode_pick_id(IVec3::new(0, -1, 0), Face::Top, origin), None);
+        assert_eq!(decode_pick_id(0, origin), None);
+    }
+}
@@ -1,7 +1,19 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
 use std::collections::HashMap;
 use std::path::Path;
 
+/// Run `create` inside a validation error scope, turning a wgpu validation error
+/// (such as a WGSL compile error) into an `Err` instead of letting the device's
+/// uncaptured error handler abort the process
+pub fn validated<T>(device: &wgpu::Device, create: impl FnOnce() -> T) -> Result<T> {
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let value = create();
+    match pollster::block_on(device.pop_error_scope()) {
+        Some(error) => Err(anyhow!("{}", error)),
+        None => Ok(value),
+    }
+}
+
 /// Shader manager for loading, compiling, and hot-reloading shaders
 pub struct ShaderManager {
     shaders: HashMap<String, wgpu::ShaderModule>,
@@ -16,11 +28,16 @@ impl ShaderManager {
         }
     }
 
+    /// Compile a WGSL shader and store it under `name`. On a compile error the
+    /// message is returned and any shader already loaded under the name is kept.
     pub fn load_shader(&mut self, name: &str, source: &str) -> Result<&wgpu::ShaderModule> {
-        let shader_module = self.device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some(name),
-            source: wgpu::ShaderSource::Wgsl(source.into()),
-        });
+        let shader_module = validated(&self.device, || {
+            self.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(name),
+                source: wgpu::ShaderSource::Wgsl(source.into()),
+            })
+        })
+        .with_context(|| format!("Failed to compile shader '{}'", name))?;
 
         self.shaders.insert(name.to_string(), shader_module);
         Ok(self.shaders.get(name).unwrap())
@@ -35,6 +52,8 @@ impl ShaderManager {
         self.shaders.get(name)
     }
 
+    /// Recompile a shader from new source. A broken edit returns the compile error
+    /// and leaves the previous working shader in place.
     pub fn reload_shader(&mut self, name: &str, source: &str) -> Result<()> {
         self.load_shader(name, source)?;
         Ok(())
@@ -45,4 +64,33 @@ impl ShaderManager {
         // Placeholder for hot-reload functionality
         Ok(Vec::new())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A headless device, or `None` on machines without any adapter
+    fn headless_device() -> Option<wgpu::Device> {
+        let instance = wgpu::Instance::default();
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))?;
+        let (device, _queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None)).ok()?;
+        Some(device)
+    }
+
+    #[test]
+    fn broken_wgsl_is_an_error_and_keeps_the_previous_shader() {
+        // Nothing to compile against without an adapter
+        let Some(device) = headless_device() else {
+            return;
+        };
+        let mut shaders = ShaderManager::new(device);
+        let working = "@compute @workgroup_size(1) fn main() {}";
+        shaders.load_shader("test", working).unwrap();
+
+        let error = shaders.reload_shader("test", "@compute fn main() { let x: u32 = ; }").unwrap_err();
+
+        assert!(format!("{:#}", error).contains("Failed to compile shader 'test'"));
+        assert!(shaders.get_shader("test").is_some());
+    }
+}
@@ -4,7 +4,9 @@ struct VertexInput {
     @location(1) tex_coords: vec2<f32>,
     @location(2) normal: vec3<f32>,
     @location(3) texture_id: u32,
-    @location(4) light_level: f32,
+    @location(4) sky_light: f32,
+    @location(5) block_light: vec3<f32>,
+    @location(6) sway: f32,
 }
 
 // Vertex shader outputs / Fragment shader inputs
@@ -14,13 +16,20 @@ struct VertexOutput {
     @location(1) world_normal: vec3<f32>,
     @location(2) world_position: vec3<f32>,
     @location(3) texture_id: u32,
-    @location(4) light_level: f32,
+    @location(4) sky_light: f32,
+    @location(5) block_light: vec3<f32>,
 }
 
 // Uniform buffer for camera
 struct CameraUniform {
     view_proj: mat4x4<f32>,
     view_pos: vec4<f32>,
+    // x: daylight multiplier for sky light, y: ambient floor, z: seconds for plant sway
+    sky_light: vec4<f32>,
+    // x: distance where geometry is fully faded, y: fade band width
+    fade: vec4<f32>,
+    // view_proj without the camera translation, for the sky
+    sky_view_proj: mat4x4<f32>,
 }
 
 @group(0) @binding(0)
@@ -32,17 +41,47 @@ var texture_atlas: texture_2d<f32>;
 @group(1) @binding(1)
 var texture_sampler: sampler;
 
+// Opacity at a horizontal distance from the camera, ramping to zero over the band
+// ending at the edge of render distance. Mirrors `distance_fade` in rendering/mod.rs.
+fn distance_fade(distance: f32, edge: f32, band: f32) -> f32 {
+    if band <= 0.0 {
+        return select(0.0, 1.0, distance <= edge);
+    }
+    return clamp((edge - distance) / band, 0.0, 1.0);
+}
+
+// Ordered dither threshold for a pixel, in (0, 1). Mirrors `fade_keeps_pixel` in
+// rendering/mod.rs.
+fn fade_threshold(pixel: vec2<f32>) -> f32 {
+    var dither = array<f32, 16>(
+        0.0, 8.0, 2.0, 10.0,
+        12.0, 4.0, 14.0, 6.0,
+        3.0, 11.0, 1.0, 9.0,
+        15.0, 7.0, 13.0, 5.0,
+    );
+    let index = (u32(pixel.y) % 4u) * 4u + u32(pixel.x) % 4u;
+    return (dither[index] + 0.5) / 16.0;
+}
+
 // Vertex shader
 @vertex
 fn vs_main(input: VertexInput) -> VertexOutput {
     var out: VertexOutput;
+
+    // Plant tops lean back and forth, out of phase across the world so a field
+    // doesn't move in lockstep. Period is π seconds; see SWAY_TIME_WRAP.
+    var position = input.position;
+    let phase = camera.sky_light.z * 2.0 + position.x * 0.7 + position.z * 0.4;
+    position.x = position.x + input.sway * 0.06 * sin(phase);
+    position.z = position.z + input.sway * 0.04 * sin(phase * 0.5 + 1.3);
     
-    out.clip_position = camera.view_proj * vec4<f32>(input.position, 1.0);
+    out.clip_position = camera.view_proj * vec4<f32>(position, 1.0);
     out.tex_coords = input.tex_coords;
     out.world_normal = input.normal;
     out.world_position = input.position;
     out.texture_id = input.texture_id;
-    out.light_level = input.light_level;
+    out.sky_light = input.sky_light;
+    out.block_light = input.block_light;
     
     return out;
 }
@@ -66,14 +105,13 @@ fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
     // Sample the texture
     var color = textureSample(texture_atlas, texture_sampler, atlas_coords);
     
-    // Basic lighting calculation
-    let light_dir = normalize(vec3<f32>(0.5, 1.0, 0.3)); // Sun direction
-    let ambient = 0.3;
-    let diffuse = max(dot(input.world_normal, light_dir), 0.0) * 0.7;
-    let lighting = ambient + diffuse;
-    
-    // Apply lighting and block light level
-    color = color * lighting * input.light_level;
+    // Combine colored block light with sky light dimmed by the time of day, keeping
+    // the ambient floor as a minimum brightness so unlit caves aren't pure black.
+    // Directional face shading is already baked into both by the mesher.
+    let sky = input.sky_light * camera.sky_light.x;
+    let ambient = camera.sky_light.y;
+    let light_color = ambient + (1.0 - ambient) * max(input.block_light, vec3<f32>(sky));
+    color = vec4<f32>(color.rgb * light_color, color.a);
     
     // Fog calculation
     let distance = length(camera.view_pos.xyz - input.world_position);
@@ -83,6 +121,13 @@ fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
     let fog_color = vec3<f32>(0.5, 0.8, 1.0); // Sky blue
     
     color = vec4<f32>(mix(color.rgb, fog_color, fog_factor), color.a);
+
+    // Fade out the outermost ring of chunks instead of cutting them off, dropping
+    // more pixels further out so the terrain stays opaque and depth-sorted
+    let horizontal = length(camera.view_pos.xz - input.world_position.xz);
+    if distance_fade(horizontal, camera.fade.x, camera.fade.y) < fade_threshold(input.clip_position.xy) {
+        discard;
+    }
     
     return color;
 }
\ No newline at end of file
@@ -0,0 +1,39 @@
+// Entities: solid boxes in world coordinates, with per-face shading baked into
+// the vertex colors
+
+// Same layout as in block.wgsl
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+    view_pos: vec4<f32>,
+    // x: daylight multiplier for sky light, y: ambient floor
+    sky_light: vec4<f32>,
+    fade: vec4<f32>,
+    sky_view_proj: mat4x4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec3<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec3<f32>,
+}
+
+@vertex
+fn vs_main(input: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4<f32>(input.position, 1.0);
+    // Entities are lit by daylight, never darker than the ambient floor
+    out.color = input.color * max(camera.sky_light.x, camera.sky_light.y);
+    return out;
+}
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(input.color, 1.0);
+}
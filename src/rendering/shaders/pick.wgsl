@@ -0,0 +1,64 @@
+// Block picking: writes an id for the block face under each pixel to an R32Uint
+// target. The id layout mirrors `encode_pick_id` in rendering/picking.rs.
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(2) normal: vec3<f32>,
+    @location(7) block: vec3<i32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) @interpolate(flat) block: vec3<i32>,
+    @location(1) world_normal: vec3<f32>,
+}
+
+// Same layout as in block.wgsl; only view_proj and view_pos are used here
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+    view_pos: vec4<f32>,
+    sky_light: vec4<f32>,
+    fade: vec4<f32>,
+    sky_view_proj: mat4x4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@vertex
+fn vs_main(input: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4<f32>(input.position, 1.0);
+    out.block = input.block;
+    out.world_normal = input.normal;
+    return out;
+}
+
+// Index of a face in `Face::all()` order: top, bottom, front, back, left, right
+fn face_index(normal: vec3<f32>) -> u32 {
+    if normal.y > 0.5 { return 0u; }
+    if normal.y < -0.5 { return 1u; }
+    if normal.z > 0.5 { return 2u; }
+    if normal.z < -0.5 { return 3u; }
+    if normal.x < -0.5 { return 4u; }
+    return 5u;
+}
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) u32 {
+    // The mesher tags each vertex with its block, since faces of slabs, stairs and
+    // plants don't lie on the block's boundary
+    let block = input.block;
+    let origin = vec3<i32>(floor(camera.view_pos.xyz));
+    let offset = block - origin;
+
+    if abs(offset.x) > 127 || abs(offset.z) > 127 || block.y < 0 || block.y > 255 {
+        return 0u;
+    }
+
+    return (1u << 31u)
+        | (face_index(input.world_normal) << 24u)
+        | (u32(block.y) << 16u)
+        | (u32(offset.z + 128) << 8u)
+        | u32(offset.x + 128);
+}
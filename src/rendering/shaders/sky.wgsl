@@ -0,0 +1,45 @@
+// Sky: a cube centered on the camera, shaded with a vertical gradient that dims
+// with the time of day
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) direction: vec3<f32>,
+}
+
+// Same layout as in block.wgsl
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+    view_pos: vec4<f32>,
+    // x: daylight multiplier for sky light
+    sky_light: vec4<f32>,
+    fade: vec4<f32>,
+    // view_proj without the camera translation
+    sky_view_proj: mat4x4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@vertex
+fn vs_main(input: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    let clip = camera.sky_view_proj * vec4<f32>(input.position, 1.0);
+    // Pin the sky to the far plane so it is never clipped and sits behind everything
+    out.clip_position = clip.xyww;
+    out.direction = input.position;
+    return out;
+}
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    // Horizon matches the fog color in block.wgsl so distant terrain blends into it
+    let horizon = vec3<f32>(0.5, 0.8, 1.0);
+    let zenith = vec3<f32>(0.25, 0.5, 0.95);
+    let height = clamp(normalize(input.direction).y, 0.0, 1.0);
+    let color = mix(horizon, zenith, height) * camera.sky_light.x;
+    return vec4<f32>(color, 1.0);
+}
@@ -0,0 +1,64 @@
+// Weather: rain streaks and snow flakes drawn as instanced quads around the camera,
+// each turned to face it about the vertical axis
+
+// Same layout as in block.wgsl
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+    view_pos: vec4<f32>,
+    // x: daylight multiplier for sky light
+    sky_light: vec4<f32>,
+    fade: vec4<f32>,
+    sky_view_proj: mat4x4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct InstanceInput {
+    // Bottom center of the quad
+    @location(0) position: vec3<f32>,
+    // Width and height
+    @location(1) size: vec2<f32>,
+    @location(2) color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    // x from -0.5 to 0.5 across the quad
+    @location(1) across: f32,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, instance: InstanceInput) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-0.5, 0.0),
+        vec2<f32>(0.5, 0.0),
+        vec2<f32>(0.5, 1.0),
+        vec2<f32>(-0.5, 0.0),
+        vec2<f32>(0.5, 1.0),
+        vec2<f32>(-0.5, 1.0),
+    );
+    let corner = corners[index];
+
+    // Horizontal direction across the view; nudged so a particle directly above or
+    // below the camera doesn't normalize a zero vector
+    let to_camera = camera.view_pos.xyz - instance.position;
+    let right = normalize(vec3<f32>(to_camera.z, 0.0, -to_camera.x) + vec3<f32>(1e-4, 0.0, 0.0));
+    let position = instance.position
+        + right * corner.x * instance.size.x
+        + vec3<f32>(0.0, corner.y * instance.size.y, 0.0);
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4<f32>(position, 1.0);
+    out.color = vec4<f32>(instance.color.rgb * camera.sky_light.x, instance.color.a);
+    out.across = corner.x;
+    return out;
+}
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    // Soften the long edges
+    let edge = 1.0 - abs(input.across) * 2.0;
+    return vec4<f32>(input.color.rgb, input.color.a * edge);
+}
@@ -1,5 +1,5 @@
 use wgpu::util::DeviceExt;
-use crate::rendering::vertex::{Vertex, BlockVertex};
+use crate::rendering::vertex::{Vertex, BlockVertex, FaceLight};
 
 /// Skybox renderer for drawing the sky background
 pub struct Skybox {
@@ -10,7 +10,8 @@ pub struct Skybox {
 
 impl Skybox {
     pub fn new(device: &wgpu::Device) -> Self {
-        // Create a large cube that surrounds the world
+        // A unit cube around the camera; sky.wgsl pushes it to the far plane, so its
+        // size doesn't matter as long as it clears the near plane
         let vertices = Self::create_skybox_vertices();
         let indices = Self::create_skybox_indices();
 
@@ -34,46 +35,46 @@ impl Skybox {
     }
 
     fn create_skybox_vertices() -> Vec<BlockVertex> {
-        let size = 500.0; // Large cube
+        let size = 1.0;
         let texture_id = 255; // Special texture ID for sky
-        let light_level = 1.0;
+        let light = FaceLight::SKY;
 
         vec![
             // Front face
-            BlockVertex::new([-size, -size,  size], [0.0, 0.0], [0.0, 0.0, 1.0], texture_id, light_level),
-            BlockVertex::new([ size, -size,  size], [1.0, 0.0], [0.0, 0.0, 1.0], texture_id, light_level),
-            BlockVertex::new([ size,  size,  size], [1.0, 1.0], [0.0, 0.0, 1.0], texture_id, light_level),
-            BlockVertex::new([-size,  size,  size], [0.0, 1.0], [0.0, 0.0, 1.0], texture_id, light_level),
+            BlockVertex::new([-size, -size,  size], [0.0, 0.0], [0.0, 0.0, 1.0], texture_id, light),
+            BlockVertex::new([ size, -size,  size], [1.0, 0.0], [0.0, 0.0, 1.0], texture_id, light),
+            BlockVertex::new([ size,  size,  size], [1.0, 1.0], [0.0, 0.0, 1.0], texture_id, light),
+            BlockVertex::new([-size,  size,  size], [0.0, 1.0], [0.0, 0.0, 1.0], texture_id, light),
 
             // Back face
-            BlockVertex::new([ size, -size, -size], [0.0, 0.0], [0.0, 0.0, -1.0], texture_id, light_level),
-            BlockVertex::new([-size, -size, -size], [1.0, 0.0], [0.0, 0.0, -1.0], texture_id, light_level),
-            BlockVertex::new([-size,  size, -size], [1.0, 1.0], [0.0, 0.0, -1.0], texture_id, light_level),
-            BlockVertex::new([ size,  size, -size], [0.0, 1.0], [0.0, 0.0, -1.0], texture_id, light_level),
+            BlockVertex::new([ size, -size, -size], [0.0, 0.0], [0.0, 0.0, -1.0], texture_id, light),
+            BlockVertex::new([-size, -size, -size], [1.0, 0.0], [0.0, 0.0, -1.0], texture_id, light),
+            BlockVertex::new([-size,  size, -size], [1.0, 1.0], [0.0, 0.0, -1.0], texture_id, light),
+            BlockVertex::new([ size,  size, -size], [0.0, 1.0], [0.0, 0.0, -1.0], texture_id, light),
 
             // Left face
-            BlockVertex::new([-size, -size, -size], [0.0, 0.0], [-1.0, 0.0, 0.0], texture_id, light_level),
-            BlockVertex::new([-size, -size,  size], [1.0, 0.0], [-1.0, 0.0, 0.0], texture_id, light_level),
-            BlockVertex::new([-size,  size,  size], [1.0, 1.0], [-1.0, 0.0, 0.0], texture_id, light_level),
-            BlockVertex::new([-size,  size, -size], [0.0, 1.0], [-1.0, 0.0, 0.0], texture_id, light_level),
+            BlockVertex::new([-size, -size, -size], [0.0, 0.0], [-1.0, 0.0, 0.0], texture_id, light),
+            BlockVertex::new([-size, -size,  size], [1.0, 0.0], [-1.0, 0.0, 0.0], texture_id, light),
+            BlockVertex::new([-size,  size,  size], [1.0, 1.0], [-1.0, 0.0, 0.0], texture_id, light),
+            BlockVertex::new([-size,  size, -size], [0.0, 1.0], [-1.0, 0.0, 0.0], texture_id, light),
 
             // Right face
-            BlockVertex::new([ size, -size,  size], [0.0, 0.0], [1.0, 0.0, 0.0], texture_id, light_level),
-            BlockVertex::new([ size, -size, -size], [1.0, 0.0], [1.0, 0.0, 0.0], texture_id, light_level),
-            BlockVertex::new([ size,  size, -size], [1.0, 1.0], [1.0, 0.0, 0.0], texture_id, light_level),
-            BlockVertex::new([ size,  size,  size], [0.0, 1.0], [1.0, 0.0, 0.0], texture_id, light_level),
+            BlockVertex::new([ size, -size,  size], [0.0, 0.0], [1.0, 0.0, 0.0], texture_id, light),
+            BlockVertex::new([ size, -size, -size], [1.0, 0.0], [1.0, 0.0, 0.0], texture_id, light),
+            BlockVertex::new([ size,  size, -size], [1.0, 1.0], [1.0, 0.0, 0.0], texture_id, light),
+            BlockVertex::new([ size,  size,  size], [0.0, 1.0], [1.0, 0.0, 0.0], texture_id, light),
 
             // Top face
-            BlockVertex::new([-size,  size,  size], [0.0, 0.0], [0.0, 1.0, 0.0], texture_id, light_level),
-            BlockVertex::new([ size,  size,  size], [1.0, 0.0], [0.0, 1.0, 0.0], texture_id, light_level),
-            BlockVertex::new([ size,  size, -size], [1.0, 1.0], [0.0, 1.0, 0.0], texture_id, light_level),
-            BlockVertex::new([-size,  size, -size], [0.0, 1.0], [0.0, 1.0, 0.0], texture_id, light_level),
+            BlockVertex::new([-size,  size,  size], [0.0, 0.0], [0.0, 1.0, 0.0], texture_id, light),
+            BlockVertex::new([ size,  size,  size], [1.0, 0.0], [0.0, 1.0, 0.0], texture_id, light),
+            BlockVertex::new([ size,  size, -size], [1.0, 1.0], [0.0, 1.0, 0.0], texture_id, light),
+            BlockVertex::new([-size,  size, -size], [0.0, 1.0], [0.0, 1.0, 0.0], texture_id, light),
 
             // Bottom face
-            BlockVertex::new([-size, -size, -size], [0.0, 0.0], [0.0, -1.0, 0.0], texture_id, light_level),
-            BlockVertex::new([ size, -size, -size], [1.0, 0.0], [0.0, -1.0, 0.0], texture_id, light_level),
-            BlockVertex::new([ size, -size,  size], [1.0, 1.0], [0.0, -1.0, 0.0], texture_id, light_level),
-            BlockVertex::new([-size, -size,  size], [0.0, 1.0], [0.0, -1.0, 0.0], texture_id, light_level),
+            BlockVertex::new([-size, -size, -size], [0.0, 0.0], [0.0, -1.0, 0.0], texture_id, light),
+            BlockVertex::new([ size, -size, -size], [1.0, 0.0], [0.0, -1.0, 0.0], texture_id, light),
+            BlockVertex::new([ size, -size,  size], [1.0, 1.0], [0.0, -1.0, 0.0], texture_id, light),
+            BlockVertex::new([-size, -size,  size], [0.0, 1.0], [0.0, -1.0, 0.0], texture_id, light),
         ]
     }
 
@@ -0,0 +1,246 @@
+// Block target: the outline around the block under the crosshair and the
+// darkening over a block being broken. Geometry is built straight from the
+// block's world position, so it doesn't matter which chunk the block is in.
+use bytemuck::{Pod, Zeroable};
+use glam::{IVec3, Vec3};
+use crate::rendering::shader;
+use crate::rendering::Texture;
+
+/// How far the outline sits outside the block, so it isn't hidden by the faces
+const OUTLINE_OFFSET: f32 = 0.002;
+/// How far the breaking overlay sits outside the block's faces
+const OVERLAY_OFFSET: f32 = 0.001;
+/// Overlay opacity just before the block breaks
+const MAX_OVERLAY_ALPHA: f32 = 0.6;
+
+const OUTLINE_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+
+/// Vertices for the 12 edges and the 6 faces of a box
+const OUTLINE_VERTEX_COUNT: usize = 24;
+const OVERLAY_VERTEX_COUNT: usize = 36;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Pod, Zeroable)]
+pub struct TargetVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
+}
+
+impl TargetVertex {
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x4];
+
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<TargetVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+/// Corner of the box around `block` grown by `offset`, picked by the low three bits
+/// of `index` (x, y, z)
+fn corner(block: IVec3, offset: f32, index: usize) -> Vec3 {
+    let pick = |bit: usize| if index & bit != 0 { 1.0 + offset } else { -offset };
+    block.as_vec3() + Vec3::new(pick(1), pick(2), pick(4))
+}
+
+/// Line list outlining the block at world position `block`
+pub fn target_outline(block: IVec3) -> Vec<TargetVertex> {
+    // Corners differing in exactly one bit share an edge
+    let edges = (0..8usize).flat_map(|from| {
+        [1, 2, 4]
+            .into_iter()
+            .filter(move |bit| from & bit == 0)
+            .map(move |bit| (from, from | bit))
+    });
+    edges
+        .flat_map(|(from, to)| [from, to])
+        .map(|index| TargetVertex {
+            position: corner(block, OUTLINE_OFFSET, index).to_array(),
+            color: OUTLINE_COLOR,
+        })
+        .collect()
+}
+
+/// Triangles covering every face of the block at world position `block`, darker
+/// the further `progress` (0-1) it is towards breaking
+pub fn breaking_overlay(block: IVec3, progress: f32) -> Vec<TargetVertex> {
+    let color = [0.0, 0.0, 0.0, progress.clamp(0.0, 1.0) * MAX_OVERLAY_ALPHA];
+    // Each face holds one axis bit fixed; its four corners vary the other two
+    let faces = [1usize, 2, 4].into_iter().flat_map(|fixed| {
+        let [u, v] = match fixed {
+            1 => [2, 4],
+            2 => [1, 4],
+            _ => [1, 2],
+        };
+        [0, fixed].map(move |side| [side, side | u, side | u | v, side, side | u | v, side | v])
+    });
+    faces
+        .flatten()
+        .map(|index| TargetVertex {
+            position: corner(block, OVERLAY_OFFSET, index).to_array(),
+            color,
+        })
+        .collect()
+}
+
+/// Draws the block target outline and the breaking overlay
+pub struct TargetRenderer {
+    outline_pipeline: wgpu::RenderPipeline,
+    overlay_pipeline: wgpu::RenderPipeline,
+    outline_buffer: wgpu::Buffer,
+    overlay_buffer: wgpu::Buffer,
+    outline_vertices: u32,
+    overlay_vertices: u32,
+}
+
+impl TargetRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+        format: wgpu::TextureFormat,
+    ) -> anyhow::Result<Self> {
+        let shader = shader::validated(device, || {
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Target Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("shaders/target.wgsl").into()),
+            })
+        })?;
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Target Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = |label: &str, topology: wgpu::PrimitiveTopology| {
+            shader::validated(device, || {
+                device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some(label),
+                    layout: Some(&layout),
+                    vertex: wgpu::VertexState {
+                        module: &shader,
+                        entry_point: "vs_main",
+                        buffers: &[TargetVertex::desc()],
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &shader,
+                        entry_point: "fs_main",
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format,
+                            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    }),
+                    primitive: wgpu::PrimitiveState {
+                        topology,
+                        strip_index_format: None,
+                        front_face: wgpu::FrontFace::Ccw,
+                        // The overlay is seen from outside, but its faces aren't wound consistently
+                        cull_mode: None,
+                        polygon_mode: wgpu::PolygonMode::Fill,
+                        unclipped_depth: false,
+                        conservative: false,
+                    },
+                    // Hidden by terrain in front, without hiding anything itself
+                    depth_stencil: Some(wgpu::DepthStencilState {
+                        format: Texture::DEPTH_FORMAT,
+                        depth_write_enabled: false,
+                        depth_compare: wgpu::CompareFunction::LessEqual,
+                        stencil: wgpu::StencilState::default(),
+                        bias: wgpu::DepthBiasState::default(),
+                    }),
+                    multisample: wgpu::MultisampleState::default(),
+                    multiview: None,
+                })
+            })
+        };
+        let outline_pipeline = pipeline("Target Outline Pipeline", wgpu::PrimitiveTopology::LineList)?;
+        let overlay_pipeline = pipeline("Breaking Overlay Pipeline", wgpu::PrimitiveTopology::TriangleList)?;
+
+        let buffer = |label: &str, count: usize| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: (count * std::mem::size_of::<TargetVertex>()) as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        };
+
+        Ok(Self {
+            outline_pipeline,
+            overlay_pipeline,
+            outline_buffer: buffer("Target Outline Buffer", OUTLINE_VERTEX_COUNT),
+            overlay_buffer: buffer("Breaking Overlay Buffer", OVERLAY_VERTEX_COUNT),
+            outline_vertices: 0,
+            overlay_vertices: 0,
+        })
+    }
+
+    /// Upload this frame's outline around `target` and overlay over `breaking`, a
+    /// block and how far it is towards breaking
+    pub fn prepare(&mut self, queue: &wgpu::Queue, target: Option<IVec3>, breaking: Option<(IVec3, f32)>) {
+        let outline = target.map(target_outline).unwrap_or_default();
+        queue.write_buffer(&self.outline_buffer, 0, bytemuck::cast_slice(&outline));
+        self.outline_vertices = outline.len() as u32;
+
+        let overlay = breaking
+            .filter(|&(_, progress)| progress > 0.0)
+            .map(|(block, progress)| breaking_overlay(block, progress))
+            .unwrap_or_default();
+        queue.write_buffer(&self.overlay_buffer, 0, bytemuck::cast_slice(&overlay));
+        self.overlay_vertices = overlay.len() as u32;
+    }
+
+    /// Draw the prepared geometry, returning the number of draw calls. Expects the
+    /// camera bind group at group 0.
+    pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) -> u32 {
+        let mut draw_calls = 0;
+        if self.overlay_vertices > 0 {
+            render_pass.set_pipeline(&self.overlay_pipeline);
+            render_pass.set_vertex_buffer(0, self.overlay_buffer.slice(..));
+            render_pass.draw(0..self.overlay_vertices, 0..1);
+            draw_calls += 1;
+        }
+        if self.outline_vertices > 0 {
+            render_pass.set_pipeline(&self.outline_pipeline);
+            render_pass.set_vertex_buffer(0, self.outline_buffer.slice(..));
+            render_pass.draw(0..self.outline_vertices, 0..1);
+            draw_calls += 1;
+        }
+        draw_calls
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(vertices: &[TargetVertex]) -> (Vec3, Vec3) {
+        vertices.iter().fold((Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)), |(min, max), vertex| {
+            let position = Vec3::from(vertex.position);
+            (min.min(position), max.max(position))
+        })
+    }
+
+    #[test]
+    fn overlay_is_built_at_the_target_in_a_neighboring_chunk() {
+        // Blocks touching chunk borders, on both sides and at negative coordinates
+        for block in [IVec3::new(-1, 64, 16), IVec3::new(0, 64, 15), IVec3::new(-17, 3, -33)] {
+            let overlay = breaking_overlay(block, 0.5);
+            let outline = target_outline(block);
+
+            assert_eq!(overlay.len(), OVERLAY_VERTEX_COUNT);
+            assert_eq!(outline.len(), OUTLINE_VERTEX_COUNT);
+            let (min, max) = bounds(&overlay);
+            assert!(min.abs_diff_eq(block.as_vec3() - OVERLAY_OFFSET, 1e-4), "{block}: overlay from {min}");
+            assert!(max.abs_diff_eq(block.as_vec3() + 1.0 + OVERLAY_OFFSET, 1e-4), "{block}: overlay to {max}");
+            let (min, max) = bounds(&outline);
+            assert!(min.abs_diff_eq(block.as_vec3() - OUTLINE_OFFSET, 1e-4), "{block}: outline from {min}");
+            assert!(max.abs_diff_eq(block.as_vec3() + 1.0 + OUTLINE_OFFSET, 1e-4), "{block}: outline to {max}");
+        }
+    }
+}
@@ -172,16 +172,42 @@ impl Texture {
     }
 }
 
+/// Highest anisotropic filtering level wgpu accepts
+pub const MAX_ANISOTROPY: u16 = 16;
+
+/// Anisotropy level to actually use for a requested setting: 1 (off) if the device
+/// can't filter anisotropically, otherwise the nearest supported level (1, 2, 4, 8
+/// or 16) not above the request
+pub fn clamp_anisotropy(requested: u16, supported: bool) -> u16 {
+    if !supported || requested <= 1 {
+        return 1;
+    }
+    let requested = requested.min(MAX_ANISOTROPY);
+    1 << (15 - requested.leading_zeros())
+}
+
+/// Minimum and maximum UV corners of a tile in an atlas `atlas_size` tiles across,
+/// matching the lookup in block.wgsl
+pub fn atlas_tile_uv(texture_id: u32, atlas_size: u32) -> ([f32; 2], [f32; 2]) {
+    let tile = 1.0 / atlas_size as f32;
+    let x = (texture_id % atlas_size) as f32 * tile;
+    let y = (texture_id / atlas_size) as f32 * tile;
+    ([x, y], [x + tile, y + tile])
+}
+
 /// Texture atlas for efficiently rendering multiple block textures
 pub struct TextureAtlas {
     texture: Texture,
     bind_group_layout: wgpu::BindGroupLayout,
     bind_group: wgpu::BindGroup,
     atlas_size: u32, // Number of textures per row/column
+    anisotropy: u16,
 }
 
 impl TextureAtlas {
-    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Self> {
+    /// Create the atlas, sampled with `anisotropy` (already clamped with
+    /// `clamp_anisotropy`)
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, anisotropy: u16) -> Result<Self> {
         // Create bind group layout
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             entries: &[
@@ -207,10 +233,21 @@ impl TextureAtlas {
 
         // For now, create a placeholder atlas
         // TODO: Load actual block textures
-        let atlas = Self::create_default_atlas(device, queue)?;
+        let atlas = Self::create_default_atlas(device, queue, anisotropy)?;
+        let bind_group = Self::create_bind_group(device, &bind_group_layout, &atlas);
 
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &bind_group_layout,
+        Ok(Self {
+            texture: atlas,
+            bind_group_layout,
+            bind_group,
+            atlas_size: 16, // 16x16 atlas
+            anisotropy,
+        })
+    }
+
+    fn create_bind_group(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, atlas: &Texture) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
@@ -222,20 +259,68 @@ impl TextureAtlas {
                 },
             ],
             label: Some("texture_atlas_bind_group"),
-        });
+        })
+    }
 
-        Ok(Self {
-            texture: atlas,
-            bind_group_layout,
-            bind_group,
-            atlas_size: 16, // 16x16 atlas
+    /// Sampler for the atlas. Without anisotropy, magnification stays nearest so the
+    /// pixel art is crisp up close while mipmaps smooth it out at a distance. wgpu
+    /// only allows anisotropy with every filter linear, so enabling it trades the
+    /// crisp close-up look for sharper terrain at grazing angles.
+    fn create_sampler(device: &wgpu::Device, anisotropy: u16) -> wgpu::Sampler {
+        let mag_filter = if anisotropy > 1 {
+            wgpu::FilterMode::Linear
+        } else {
+            wgpu::FilterMode::Nearest
+        };
+        device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Block Atlas Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            anisotropy_clamp: anisotropy.max(1),
+            ..Default::default()
         })
     }
 
-    fn create_default_atlas(device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Texture> {
+    /// Change the anisotropic filtering level (already clamped with `clamp_anisotropy`)
+    pub fn set_anisotropy(&mut self, device: &wgpu::Device, anisotropy: u16) {
+        if anisotropy == self.anisotropy {
+            return;
+        }
+        self.anisotropy = anisotropy;
+        self.texture.sampler = Self::create_sampler(device, anisotropy);
+        self.bind_group = Self::create_bind_group(device, &self.bind_group_layout, &self.texture);
+    }
+
+    pub fn anisotropy(&self) -> u16 {
+        self.anisotropy
+    }
+
+    /// Halve an RGBA image in each dimension by averaging 2x2 blocks
+    fn downsample(data: &[u8], size: u32) -> Vec<u8> {
+        let half = size / 2;
+        let mut out = vec![0u8; (half * half * 4) as usize];
+        for y in 0..half {
+            for x in 0..half {
+                for channel in 0..4 {
+                    let sum: u32 = [(0, 0), (1, 0), (0, 1), (1, 1)]
+                        .iter()
+                        .map(|&(dx, dy)| data[(((y * 2 + dy) * size + x * 2 + dx) * 4 + channel) as usize] as u32)
+                        .sum();
+                    out[((y * half + x) * 4 + channel) as usize] = (sum / 4) as u8;
+                }
+            }
+        }
+        out
+    }
+
+    fn create_default_atlas(device: &wgpu::Device, queue: &wgpu::Queue, anisotropy: u16) -> Result<Texture> {
         // Create a simple atlas with different colored blocks for now
         let atlas_size = 16; // 16x16 texture atlas
-        let texture_size = 16; // Each texture is 16x16 pixels
+        let texture_size: u32 = 16; // Each texture is 16x16 pixels
         let total_size = atlas_size * texture_size;
         
         let mut data = vec![0u8; (total_size * total_size * 4) as usize];
@@ -268,6 +353,8 @@ impl TextureAtlas {
             }
         }
 
+        // Stop mipmapping once each tile is a single pixel, so tiles never bleed together
+        let mip_level_count = texture_size.trailing_zeros() + 1;
         let size = wgpu::Extent3d {
             width: total_size,
             height: total_size,
@@ -276,7 +363,7 @@ impl TextureAtlas {
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("Block Texture Atlas"),
             size,
-            mip_level_count: 1,
+            mip_level_count,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::Rgba8UnormSrgb,
@@ -284,32 +371,36 @@ impl TextureAtlas {
             view_formats: &[],
         });
 
-        queue.write_texture(
-            wgpu::ImageCopyTexture {
-                aspect: wgpu::TextureAspect::All,
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-            },
-            &data,
-            wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: Some(4 * total_size),
-                rows_per_image: Some(total_size),
-            },
-            size,
-        );
+        let mut level_data = data;
+        let mut level_size = total_size;
+        for mip_level in 0..mip_level_count {
+            if mip_level > 0 {
+                level_data = Self::downsample(&level_data, level_size);
+                level_size /= 2;
+            }
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    aspect: wgpu::TextureAspect::All,
+                    texture: &texture,
+                    mip_level,
+                    origin: wgpu::Origin3d::ZERO,
+                },
+                &level_data,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * level_size),
+                    rows_per_image: Some(level_size),
+                },
+                wgpu::Extent3d {
+                    width: level_size,
+                    height: level_size,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
 
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Nearest,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        });
+        let sampler = Self::create_sampler(device, anisotropy);
 
         Ok(Texture { texture, view, sampler })
     }
@@ -325,4 +416,22 @@ impl TextureAtlas {
     pub fn atlas_size(&self) -> u32 {
         self.atlas_size
     }
-}
\ No newline at end of file
+
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.texture.view
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anisotropy_is_clamped_to_what_the_device_supports() {
+        assert_eq!(clamp_anisotropy(8, true), 8);
+        assert_eq!(clamp_anisotropy(64, true), MAX_ANISOTROPY);
+        assert_eq!(clamp_anisotropy(6, true), 4);
+        assert_eq!(clamp_anisotropy(0, true), 1);
+        assert_eq!(clamp_anisotropy(16, false), 1);
+    }
+}
@@ -6,6 +6,36 @@ pub trait Vertex {
     fn desc() -> wgpu::VertexBufferLayout<'static>;
 }
 
+/// Directional shade multipliers applied to each face's light. Tops are brightest,
+/// bottoms darkest, and the north/south sides a little brighter than east/west.
+pub const TOP_SHADE: f32 = 1.0;
+pub const NORTH_SOUTH_SHADE: f32 = 0.8;
+pub const EAST_WEST_SHADE: f32 = 0.6;
+pub const BOTTOM_SHADE: f32 = 0.5;
+
+/// Light reaching a face, kept split so the sky part can be scaled by time of day
+/// in the shader without rebuilding meshes
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct FaceLight {
+    /// Sky light, 0-1
+    pub sky: f32,
+    /// Red, green and blue block light, 0-1
+    pub block: [f32; 3],
+}
+
+impl FaceLight {
+    /// Fully open to the sky with no block light
+    pub const SKY: FaceLight = FaceLight { sky: 1.0, block: [0.0; 3] };
+
+    /// Scale both sky and block light, e.g. by a face's directional shade
+    pub fn shaded(self, factor: f32) -> FaceLight {
+        FaceLight {
+            sky: self.sky * factor,
+            block: self.block.map(|channel| channel * factor),
+        }
+    }
+}
+
 /// Vertex for rendering blocks with texture coordinates and lighting
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
@@ -14,7 +44,12 @@ pub struct BlockVertex {
     tex_coords: [f32; 2],
     normal: [f32; 3],
     texture_id: u32,
-    light_level: f32,
+    sky_light: f32,
+    block_light: [f32; 3],
+    /// How strongly the vertex sways in the wind, 0-1; only plant tops move
+    sway: f32,
+    /// World position of the block the vertex belongs to, for the pick pass
+    block: [i32; 3],
 }
 
 impl BlockVertex {
@@ -23,16 +58,59 @@ impl BlockVertex {
         tex_coords: [f32; 2],
         normal: [f32; 3],
         texture_id: u32,
-        light_level: f32,
+        light: FaceLight,
     ) -> Self {
         Self {
             position,
             tex_coords,
             normal,
             texture_id,
-            light_level,
+            sky_light: light.sky,
+            block_light: light.block,
+            sway: 0.0,
+            block: [0; 3],
         }
     }
+
+    pub fn position(&self) -> [f32; 3] {
+        self.position
+    }
+
+    pub fn normal(&self) -> [f32; 3] {
+        self.normal
+    }
+
+    /// Sky and block light, as passed to the shader
+    pub fn light(&self) -> FaceLight {
+        FaceLight { sky: self.sky_light, block: self.block_light }
+    }
+
+    pub fn block(&self) -> [i32; 3] {
+        self.block
+    }
+
+    pub fn with_sway(mut self, sway: f32) -> Self {
+        self.sway = sway;
+        self
+    }
+
+    pub fn with_block(mut self, block: [i32; 3]) -> Self {
+        self.block = block;
+        self
+    }
+
+    /// Move the vertex away from `origin` by `factor`, scaling the shape it belongs to
+    pub fn scaled_from(self, origin: [f32; 3], factor: f32) -> Self {
+        self.scaled_from_axes(origin, [factor; 3])
+    }
+
+    /// Like `scaled_from`, with a separate factor per axis
+    pub fn scaled_from_axes(mut self, origin: [f32; 3], factors: [f32; 3]) -> Self {
+        for ((position, origin), factor) in self.position.iter_mut().zip(origin).zip(factors) {
+            *position = origin + (*position - origin) * factor;
+        }
+        self
+    }
 }
 
 impl Vertex for BlockVertex {
@@ -66,12 +144,30 @@ impl Vertex for BlockVertex {
                     shader_location: 3,
                     format: wgpu::VertexFormat::Uint32,
                 },
-                // Light level
+                // Sky light
                 VertexAttribute {
                     offset: (mem::size_of::<[f32; 8]>() + mem::size_of::<u32>()) as wgpu::BufferAddress,
                     shader_location: 4,
                     format: wgpu::VertexFormat::Float32,
                 },
+                // Block light color
+                VertexAttribute {
+                    offset: (mem::size_of::<[f32; 9]>() + mem::size_of::<u32>()) as wgpu::BufferAddress,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                // Sway
+                VertexAttribute {
+                    offset: (mem::size_of::<[f32; 12]>() + mem::size_of::<u32>()) as wgpu::BufferAddress,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Float32,
+                },
+                // Block position
+                VertexAttribute {
+                    offset: (mem::size_of::<[f32; 13]>() + mem::size_of::<u32>()) as wgpu::BufferAddress,
+                    shader_location: 7,
+                    format: wgpu::VertexFormat::Sint32x3,
+                },
             ],
         }
     }
@@ -100,56 +196,82 @@ impl Face {
         }
     }
 
-    pub fn vertices(&self, x: f32, y: f32, z: f32, texture_id: u32, light_level: f32) -> [BlockVertex; 4] {
+    /// The face a ray moving along `axis` (0 = x, 1 = y, 2 = z) crosses when it
+    /// enters a block, i.e. the face on the side it comes from
+    pub fn entered_along(axis: usize, positive: bool) -> Face {
+        match (axis, positive) {
+            (0, true) => Face::Left,
+            (0, false) => Face::Right,
+            (1, true) => Face::Bottom,
+            (1, false) => Face::Top,
+            (_, true) => Face::Back,
+            (_, false) => Face::Front,
+        }
+    }
+
+    /// Fixed directional shading, so blocks read as solid even in flat light
+    pub fn shade(&self) -> f32 {
+        match self {
+            Face::Top => TOP_SHADE,
+            Face::Front | Face::Back => NORTH_SOUTH_SHADE,
+            Face::Left | Face::Right => EAST_WEST_SHADE,
+            Face::Bottom => BOTTOM_SHADE,
+        }
+    }
+
+    pub fn vertices(&self, x: f32, y: f32, z: f32, texture_id: u32, light: FaceLight) -> [BlockVertex; 4] {
         let normal = self.normal();
         match self {
             Face::Top => [
-                BlockVertex::new([x, y + 1.0, z], [0.0, 0.0], normal, texture_id, light_level),
-                BlockVertex::new([x + 1.0, y + 1.0, z], [1.0, 0.0], normal, texture_id, light_level),
-                BlockVertex::new([x + 1.0, y + 1.0, z + 1.0], [1.0, 1.0], normal, texture_id, light_level),
-                BlockVertex::new([x, y + 1.0, z + 1.0], [0.0, 1.0], normal, texture_id, light_level),
+                BlockVertex::new([x, y + 1.0, z], [0.0, 0.0], normal, texture_id, light),
+                BlockVertex::new([x + 1.0, y + 1.0, z], [1.0, 0.0], normal, texture_id, light),
+                BlockVertex::new([x + 1.0, y + 1.0, z + 1.0], [1.0, 1.0], normal, texture_id, light),
+                BlockVertex::new([x, y + 1.0, z + 1.0], [0.0, 1.0], normal, texture_id, light),
             ],
             Face::Bottom => [
-                BlockVertex::new([x, y, z + 1.0], [0.0, 0.0], normal, texture_id, light_level),
-                BlockVertex::new([x + 1.0, y, z + 1.0], [1.0, 0.0], normal, texture_id, light_level),
-                BlockVertex::new([x + 1.0, y, z], [1.0, 1.0], normal, texture_id, light_level),
-                BlockVertex::new([x, y, z], [0.0, 1.0], normal, texture_id, light_level),
+                BlockVertex::new([x, y, z + 1.0], [0.0, 0.0], normal, texture_id, light),
+                BlockVertex::new([x + 1.0, y, z + 1.0], [1.0, 0.0], normal, texture_id, light),
+                BlockVertex::new([x + 1.0, y, z], [1.0, 1.0], normal, texture_id, light),
+                BlockVertex::new([x, y, z], [0.0, 1.0], normal, texture_id, light),
             ],
             Face::Front => [
-                BlockVertex::new([x, y, z + 1.0], [0.0, 0.0], normal, texture_id, light_level),
-                BlockVertex::new([x, y + 1.0, z + 1.0], [0.0, 1.0], normal, texture_id, light_level),
-                BlockVertex::new([x + 1.0, y + 1.0, z + 1.0], [1.0, 1.0], normal, texture_id, light_level),
-                BlockVertex::new([x + 1.0, y, z + 1.0], [1.0, 0.0], normal, texture_id, light_level),
+                BlockVertex::new([x, y, z + 1.0], [0.0, 0.0], normal, texture_id, light),
+                BlockVertex::new([x, y + 1.0, z + 1.0], [0.0, 1.0], normal, texture_id, light),
+                BlockVertex::new([x + 1.0, y + 1.0, z + 1.0], [1.0, 1.0], normal, texture_id, light),
+                BlockVertex::new([x + 1.0, y, z + 1.0], [1.0, 0.0], normal, texture_id, light),
             ],
             Face::Back => [
-                BlockVertex::new([x + 1.0, y, z], [0.0, 0.0], normal, texture_id, light_level),
-                BlockVertex::new([x + 1.0, y + 1.0, z], [0.0, 1.0], normal, texture_id, light_level),
-                BlockVertex::new([x, y + 1.0, z], [1.0, 1.0], normal, texture_id, light_level),
-                BlockVertex::new([x, y, z], [1.0, 0.0], normal, texture_id, light_level),
+                BlockVertex::new([x + 1.0, y, z], [0.0, 0.0], normal, texture_id, light),
+                BlockVertex::new([x + 1.0, y + 1.0, z], [0.0, 1.0], normal, texture_id, light),
+                BlockVertex::new([x, y + 1.0, z], [1.0, 1.0], normal, texture_id, light),
+                BlockVertex::new([x, y, z], [1.0, 0.0], normal, texture_id, light),
             ],
             Face::Left => [
-                BlockVertex::new([x, y, z], [0.0, 0.0], normal, texture_id, light_level),
-                BlockVertex::new([x, y + 1.0, z], [0.0, 1.0], normal, texture_id, light_level),
-                BlockVertex::new([x, y + 1.0, z + 1.0], [1.0, 1.0], normal, texture_id, light_level),
-                BlockVertex::new([x, y, z + 1.0], [1.0, 0.0], normal, texture_id, light_level),
+                BlockVertex::new([x, y, z], [0.0, 0.0], normal, texture_id, light),
+                BlockVertex::new([x, y + 1.0, z], [0.0, 1.0], normal, texture_id, light),
+                BlockVertex::new([x, y + 1.0, z + 1.0], [1.0, 1.0], normal, texture_id, light),
+                BlockVertex::new([x, y, z + 1.0], [1.0, 0.0], normal, texture_id, light),
             ],
             Face::Right => [
-                BlockVertex::new([x + 1.0, y, z + 1.0], [0.0, 0.0], normal, texture_id, light_level),
-                BlockVertex::new([x + 1.0, y + 1.0, z + 1.0], [0.0, 1.0], normal, texture_id, light_level),
-                BlockVertex::new([x + 1.0, y + 1.0, z], [1.0, 1.0], normal, texture_id, light_level),
-                BlockVertex::new([x + 1.0, y, z], [1.0, 0.0], normal, texture_id, light_level),
+                BlockVertex::new([x + 1.0, y, z + 1.0], [0.0, 0.0], normal, texture_id, light),
+                BlockVertex::new([x + 1.0, y + 1.0, z + 1.0], [0.0, 1.0], normal, texture_id, light),
+                BlockVertex::new([x + 1.0, y + 1.0, z], [1.0, 1.0], normal, texture_id, light),
+                BlockVertex::new([x + 1.0, y, z], [1.0, 0.0], normal, texture_id, light),
             ],
         }
     }
 
+    /// Two triangles covering the quad from `vertices`. The quad corners run clockwise
+    /// seen from outside, so the triangles walk them backwards to wind counter-clockwise
+    /// around `normal()`, matching the pipeline's front face and back-face culling.
     pub fn indices(&self, start_vertex: u32) -> [u32; 6] {
         [
             start_vertex,
-            start_vertex + 1,
             start_vertex + 2,
+            start_vertex + 1,
             start_vertex,
-            start_vertex + 2,
             start_vertex + 3,
+            start_vertex + 2,
         ]
     }
 
@@ -158,7 +280,8 @@ impl Face {
     }
 }
 
-/// Mesh data for a chunk
+/// Chunk mesh uploaded to the GPU. The vertex and index data are kept alongside
+/// the buffers to account for memory use.
 pub struct ChunkMesh {
     pub vertices: Vec<BlockVertex>,
     pub indices: Vec<u32>,
@@ -178,20 +301,15 @@ impl ChunkMesh {
         }
     }
 
-    pub fn clear(&mut self) {
-        self.vertices.clear();
-        self.indices.clear();
-        self.index_count = 0;
-    }
-
-    pub fn add_face(&mut self, face: Face, x: f32, y: f32, z: f32, texture_id: u32, light_level: f32) {
-        let start_vertex = self.vertices.len() as u32;
-        let face_vertices = face.vertices(x, y, z, texture_id, light_level);
-        let face_indices = face.indices(start_vertex);
-
-        self.vertices.extend_from_slice(&face_vertices);
-        self.indices.extend_from_slice(&face_indices);
-        self.index_count += 6;
+    /// Wrap mesh data built on the CPU; call `finalize` to create the buffers
+    pub fn from_data(vertices: Vec<BlockVertex>, indices: Vec<u32>) -> Self {
+        Self {
+            index_count: indices.len() as u32,
+            vertices,
+            indices,
+            vertex_buffer: None,
+            index_buffer: None,
+        }
     }
 
     pub fn finalize(&mut self, device: &wgpu::Device) {
@@ -212,11 +330,21 @@ impl ChunkMesh {
         }
     }
 
-    pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+    /// Bytes of vertex and index data this mesh uploads to the GPU
+    pub fn memory_usage(&self) -> usize {
+        self.vertices.len() * std::mem::size_of::<BlockVertex>()
+            + self.indices.len() * std::mem::size_of::<u32>()
+    }
+
+    /// Draw the mesh, returning whether a draw call was issued
+    pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) -> bool {
         if let (Some(vertex_buffer), Some(index_buffer)) = (&self.vertex_buffer, &self.index_buffer) {
             render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
             render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
             render_pass.draw_indexed(0..self.index_count, 0, 0..1);
+            true
+        } else {
+            false
         }
     }
 }
@@ -225,4 +353,29 @@ impl Default for ChunkMesh {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec3;
+
+    #[test]
+    fn triangle_winding_matches_each_face_normal() {
+        for face in Face::all() {
+            let vertices = face.vertices(2.0, 3.0, 4.0, 0, FaceLight::SKY);
+            let corner = |index: u32| Vec3::from(vertices[index as usize].position());
+            for triangle in face.indices(0).chunks(3) {
+                let (a, b, c) = (corner(triangle[0]), corner(triangle[1]), corner(triangle[2]));
+                let winding_normal = (b - a).cross(c - a).normalize();
+                assert_eq!(winding_normal, Vec3::from(face.normal()), "{face:?} face");
+            }
+        }
+    }
+
+    #[test]
+    fn top_faces_are_brightest_and_bottom_faces_darkest() {
+        assert!(Face::Top.shade() > Face::Left.shade());
+        assert!(Face::Left.shade() > Face::Bottom.shade());
+    }
+}
@@ -0,0 +1,242 @@
+// Weather: rain and snow drawn as a small set of instanced quads around the camera.
+// Particles are placed from a hash of their index rather than simulated, so there
+// is no per-particle state to keep.
+
+use bytemuck::{Pod, Zeroable};
+use glam::{IVec3, Vec3};
+
+use crate::rendering::shader;
+use crate::rendering::Texture;
+use crate::utils::rng::position_hash;
+use crate::world::{Biome, World};
+
+/// Particles drawn at full intensity
+pub const MAX_WEATHER_PARTICLES: usize = 1024;
+
+/// Half the width of the square around the camera that particles fall in
+const WEATHER_RADIUS: f32 = 12.0;
+
+/// Height of the column particles fall through, centered on the camera
+const WEATHER_HEIGHT: f32 = 16.0;
+
+/// Blocks checked above the camera for a roof that keeps the weather out
+const SHELTER_CHECK_HEIGHT: i32 = 32;
+
+/// Fall speeds in blocks/second
+const RAIN_FALL_SPEED: f32 = 12.0;
+const SNOW_FALL_SPEED: f32 = 1.5;
+
+/// Seed for particle placement, so weather doesn't line up with terrain features
+const PARTICLE_SEED: u64 = 0x0057_4541_5448_4552;
+
+/// What falls from the sky when it rains
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precipitation {
+    Rain,
+    Snow,
+}
+
+impl Precipitation {
+    /// Snow in cold biomes, rain everywhere else
+    pub fn for_biome(biome: Option<Biome>) -> Self {
+        match biome {
+            Some(Biome::Tundra) => Precipitation::Snow,
+            _ => Precipitation::Rain,
+        }
+    }
+}
+
+/// One rain streak or snow flake
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Pod, Zeroable)]
+pub struct WeatherParticle {
+    /// Bottom center of the quad
+    pub position: [f32; 3],
+    /// Width and height
+    pub size: [f32; 2],
+    pub color: [f32; 4],
+}
+
+impl WeatherParticle {
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 3] =
+            wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x2, 2 => Float32x4];
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<WeatherParticle>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+/// Particles for precipitation of `intensity` (0-1) around `center` at `time`
+/// seconds. Their columns are fixed in the world and wrap around the camera, so
+/// walking through the rain doesn't drag it along.
+pub fn weather_particles(precipitation: Precipitation, intensity: f32, center: Vec3, time: f32) -> Vec<WeatherParticle> {
+    let count = (MAX_WEATHER_PARTICLES as f32 * intensity.clamp(0.0, 1.0)).round() as usize;
+    let unit = |hash: u64, shift: u32| ((hash >> shift) & 0xFFFF) as f32 / 65536.0;
+    let span = WEATHER_RADIUS * 2.0;
+    let wrap = |anchor: f32, center: f32| center - WEATHER_RADIUS + (anchor - center).rem_euclid(span);
+
+    (0..count)
+        .map(|index| {
+            let hash = position_hash(PARTICLE_SEED, index as i32, 0, 0);
+            let x = wrap(unit(hash, 0) * span, center.x);
+            let z = wrap(unit(hash, 16) * span, center.z);
+            let phase = unit(hash, 32);
+
+            match precipitation {
+                Precipitation::Rain => {
+                    let fallen = (time * RAIN_FALL_SPEED + phase * WEATHER_HEIGHT).rem_euclid(WEATHER_HEIGHT);
+                    WeatherParticle {
+                        position: [x, center.y + WEATHER_HEIGHT / 2.0 - fallen, z],
+                        size: [0.03, 0.6],
+                        color: [0.6, 0.7, 0.9, 0.5],
+                    }
+                }
+                Precipitation::Snow => {
+                    let fallen = (time * SNOW_FALL_SPEED + phase * WEATHER_HEIGHT).rem_euclid(WEATHER_HEIGHT);
+                    // Flakes drift from side to side as they fall
+                    let drift = (time + phase * std::f32::consts::TAU).sin() * 0.3;
+                    WeatherParticle {
+                        position: [x + drift, center.y + WEATHER_HEIGHT / 2.0 - fallen, z],
+                        size: [0.1, 0.1],
+                        color: [1.0, 1.0, 1.0, 0.9],
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+/// Whether a solid block above `position` keeps the weather out, e.g. indoors
+fn is_sheltered(world: &World, position: Vec3) -> bool {
+    let head = position.floor().as_ivec3();
+    (1..=SHELTER_CHECK_HEIGHT).any(|dy| world.is_solid_at(head + IVec3::Y * dy))
+}
+
+/// Draws the current weather around the camera
+pub struct WeatherRenderer {
+    pipeline: wgpu::RenderPipeline,
+    instance_buffer: wgpu::Buffer,
+    instance_count: u32,
+}
+
+impl WeatherRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+        format: wgpu::TextureFormat,
+    ) -> anyhow::Result<Self> {
+        let shader = shader::validated(device, || {
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Weather Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("shaders/weather.wgsl").into()),
+            })
+        })?;
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Weather Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = shader::validated(device, || {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Weather Pipeline"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[WeatherParticle::desc()],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    // Quads face the camera, but either winding may reach the screen
+                    cull_mode: None,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                // Hidden by terrain, but see-through particles don't hide each other
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: Texture::DEPTH_FORMAT,
+                    depth_write_enabled: false,
+                    depth_compare: wgpu::CompareFunction::Less,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+            })
+        })?;
+        let instance_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Weather Instance Buffer"),
+            size: (MAX_WEATHER_PARTICLES * std::mem::size_of::<WeatherParticle>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Ok(Self {
+            pipeline,
+            instance_buffer,
+            instance_count: 0,
+        })
+    }
+
+    /// Upload this frame's particles for the world's weather at the camera
+    pub fn prepare(&mut self, queue: &wgpu::Queue, world: &World, camera_position: Vec3, time: f32) {
+        let intensity = world.weather().precipitation();
+        if intensity <= 0.0 || is_sheltered(world, camera_position) {
+            self.instance_count = 0;
+            return;
+        }
+
+        let biome = world.biome_at(camera_position.x.floor() as i32, camera_position.z.floor() as i32);
+        let particles = weather_particles(Precipitation::for_biome(biome), intensity, camera_position, time);
+        queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&particles));
+        self.instance_count = particles.len() as u32;
+    }
+
+    /// Draw the prepared particles. Expects the camera bind group at group 0.
+    pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) -> bool {
+        if self.instance_count == 0 {
+            return false;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_vertex_buffer(0, self.instance_buffer.slice(..));
+        render_pass.draw(0..6, 0..self.instance_count);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_intensity_emits_no_particles() {
+        for precipitation in [Precipitation::Rain, Precipitation::Snow] {
+            assert!(weather_particles(precipitation, 0.0, Vec3::new(0.0, 70.0, 0.0), 3.0).is_empty());
+        }
+        assert_eq!(weather_particles(Precipitation::Rain, 1.0, Vec3::ZERO, 0.0).len(), MAX_WEATHER_PARTICLES);
+    }
+
+    #[test]
+    fn cold_biomes_get_snow_and_others_rain() {
+        assert_eq!(Precipitation::for_biome(Some(Biome::Tundra)), Precipitation::Snow);
+        assert_eq!(Precipitation::for_biome(Some(Biome::Plains)), Precipitation::Rain);
+        assert_eq!(Precipitation::for_biome(None), Precipitation::Rain);
+    }
+}
@@ -0,0 +1,106 @@
+use egui::{Color32, Pos2, Rect, Stroke, Vec2};
+use glam::Vec3;
+
+use crate::world::{Biome, World};
+
+/// Columns sampled along each side of the map
+const MAP_CELLS: i32 = 64;
+
+/// World blocks covered by each map cell, along each axis
+const BLOCKS_PER_CELL: i32 = 4;
+
+/// On-screen size of a map cell, in points
+const CELL_SIZE: f32 = 3.0;
+
+/// Debug color for each biome on the biome map
+pub fn biome_color(biome: Biome) -> Color32 {
+    match biome {
+        Biome::Plains => Color32::from_rgb(141, 199, 63),
+        Biome::Forest => Color32::from_rgb(34, 110, 34),
+        Biome::Desert => Color32::from_rgb(237, 214, 130),
+        Biome::Mountains => Color32::from_rgb(128, 128, 128),
+        Biome::Hills => Color32::from_rgb(160, 120, 70),
+        Biome::Swamp => Color32::from_rgb(80, 95, 60),
+        Biome::Ocean => Color32::from_rgb(40, 70, 200),
+        Biome::Tundra => Color32::from_rgb(235, 245, 250),
+    }
+}
+
+/// Top-down map of biomes around the player. Sampling runs the generator's climate
+/// noise for every cell, so the map is only rebuilt when the player moves to
+/// another cell.
+#[derive(Default)]
+pub struct BiomeMap {
+    /// Cell the player was in when the map was last sampled
+    center: Option<(i32, i32)>,
+    /// Row-major by z then x, starting at the north-west corner
+    cells: Vec<Option<Biome>>,
+}
+
+impl BiomeMap {
+    /// Resample the map if the player has moved to a different cell
+    pub fn update(&mut self, world: &World, position: Vec3) {
+        let center = (
+            (position.x.floor() as i32).div_euclid(BLOCKS_PER_CELL),
+            (position.z.floor() as i32).div_euclid(BLOCKS_PER_CELL),
+        );
+        if self.center == Some(center) {
+            return;
+        }
+        self.center = Some(center);
+
+        let half = MAP_CELLS / 2;
+        self.cells.clear();
+        for row in 0..MAP_CELLS {
+            for column in 0..MAP_CELLS {
+                // Sample the middle of each cell
+                let x = (center.0 + column - half) * BLOCKS_PER_CELL + BLOCKS_PER_CELL / 2;
+                let z = (center.1 + row - half) * BLOCKS_PER_CELL + BLOCKS_PER_CELL / 2;
+                self.cells.push(world.biome_at(x, z));
+            }
+        }
+    }
+
+    /// Paint the map with the player's cell outlined in the middle
+    pub fn show(&self, ui: &mut egui::Ui) {
+        let (rect, _) = ui.allocate_exact_size(Vec2::splat(MAP_CELLS as f32 * CELL_SIZE), egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, Color32::BLACK);
+
+        for (index, biome) in self.cells.iter().enumerate() {
+            if let Some(biome) = biome {
+                let column = (index as i32 % MAP_CELLS) as f32;
+                let row = (index as i32 / MAP_CELLS) as f32;
+                let min = rect.min + Vec2::new(column, row) * CELL_SIZE;
+                painter.rect_filled(Rect::from_min_size(min, Vec2::splat(CELL_SIZE)), 0.0, biome_color(*biome));
+            }
+        }
+
+        let half = (MAP_CELLS / 2) as f32 * CELL_SIZE;
+        let player = Rect::from_min_size(Pos2::new(rect.min.x + half, rect.min.y + half), Vec2::splat(CELL_SIZE));
+        painter.rect_stroke(player.expand(1.0), 0.0, Stroke::new(1.0, Color32::RED));
+    }
+
+    /// Color key for every biome
+    pub fn show_legend(ui: &mut egui::Ui) {
+        for biome in Biome::all() {
+            ui.horizontal(|ui| {
+                let (rect, _) = ui.allocate_exact_size(Vec2::splat(10.0), egui::Sense::hover());
+                ui.painter().rect_filled(rect, 0.0, biome_color(biome));
+                ui.label(biome.name());
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn every_biome_has_its_own_color() {
+        let colors: HashSet<Color32> = Biome::all().into_iter().map(biome_color).collect();
+        assert_eq!(colors.len(), Biome::all().len());
+    }
+}
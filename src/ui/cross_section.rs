@@ -0,0 +1,113 @@
+use egui::{Color32, ColorImage, Rect, Stroke, TextureHandle, TextureOptions, Vec2};
+use glam::Vec3;
+
+use crate::utils::rng::position_hash;
+use crate::world::{BlockType, ChunkCoordinate, SliceAxis, World, CHUNK_HEIGHT, CHUNK_SIZE};
+
+/// Chunks shown on each side of the player's chunk
+const CHUNK_RADIUS: i32 = 2;
+
+/// On-screen size of a block in the slice, in points
+const CELL_SIZE: f32 = 2.0;
+
+/// Debug color for a cell of the slice. Air and other see-through blocks show
+/// their light level in gray; opaque blocks get a fixed color per block type.
+fn cell_color(block: BlockType, light: u8) -> Color32 {
+    let gray = light * 17;
+    if block == BlockType::Air {
+        return Color32::from_gray(gray);
+    }
+
+    let hash = position_hash(block.id() as u64, 0, 0, 0);
+    let [r, g, b] = [hash as u8, (hash >> 8) as u8, (hash >> 16) as u8].map(|channel| channel / 2 + 64);
+    if block.is_transparent() {
+        // Half block color, half light, so light through water and leaves still shows
+        let blend = |channel: u8| ((channel as u16 + gray as u16) / 2) as u8;
+        Color32::from_rgb(blend(r), blend(g), blend(b))
+    } else {
+        Color32::from_rgb(r, g, b)
+    }
+}
+
+/// Vertical slice of blocks and light through the player's position, for
+/// checking the lighting engine. Rebuilt every frame it's shown, since light
+/// changes as blocks do.
+pub struct CrossSection {
+    /// Coordinate held fixed at the player's position
+    pub axis: SliceAxis,
+    texture: Option<TextureHandle>,
+    /// Column of the image the player is in
+    player_column: usize,
+    /// Y level the player's feet are at
+    player_y: usize,
+}
+
+impl Default for CrossSection {
+    fn default() -> Self {
+        Self {
+            axis: SliceAxis::X,
+            texture: None,
+            player_column: 0,
+            player_y: 0,
+        }
+    }
+}
+
+impl CrossSection {
+    /// Resample the slice through `position` from the chunks around it
+    pub fn update(&mut self, ctx: &egui::Context, world: &World, position: Vec3) {
+        let axis = self.axis;
+        let block_x = position.x.floor() as i32;
+        let block_z = position.z.floor() as i32;
+        let center = ChunkCoordinate::containing(block_x, block_z);
+        let (fixed, along) = match axis {
+            SliceAxis::X => (block_x, block_z),
+            SliceAxis::Z => (block_z, block_x),
+        };
+        let index = fixed.rem_euclid(CHUNK_SIZE as i32) as usize;
+
+        let width = (2 * CHUNK_RADIUS as usize + 1) * CHUNK_SIZE;
+        let mut image = ColorImage::new([width, CHUNK_HEIGHT], Color32::BLACK);
+        for offset in -CHUNK_RADIUS..=CHUNK_RADIUS {
+            let coord = match axis {
+                SliceAxis::X => ChunkCoordinate::new(center.x, center.z + offset),
+                SliceAxis::Z => ChunkCoordinate::new(center.x + offset, center.z),
+            };
+            // Unloaded chunks stay black
+            let Some(chunk) = world.get_chunk(coord) else {
+                continue;
+            };
+
+            let left = (offset + CHUNK_RADIUS) as usize * CHUNK_SIZE;
+            for (y, row) in chunk.cross_section(axis, index).iter().enumerate() {
+                // Images run top down
+                let image_row = CHUNK_HEIGHT - 1 - y;
+                for (column, &(block, light)) in row.iter().enumerate() {
+                    image[(left + column, image_row)] = cell_color(block, light);
+                }
+            }
+        }
+
+        self.player_column = CHUNK_RADIUS as usize * CHUNK_SIZE + along.rem_euclid(CHUNK_SIZE as i32) as usize;
+        self.player_y = (position.y.floor().max(0.0) as usize).min(CHUNK_HEIGHT - 1);
+
+        match &mut self.texture {
+            Some(texture) => texture.set(image, TextureOptions::NEAREST),
+            None => self.texture = Some(ctx.load_texture("cross_section", image, TextureOptions::NEAREST)),
+        }
+    }
+
+    /// Paint the slice with the player's cell outlined
+    pub fn show(&self, ui: &mut egui::Ui) {
+        let Some(texture) = &self.texture else {
+            return;
+        };
+        let size = texture.size_vec2() * CELL_SIZE;
+        let response = ui.image((texture.id(), size));
+        let rect = response.rect;
+
+        let min = rect.min + Vec2::new(self.player_column as f32, (CHUNK_HEIGHT - 1 - self.player_y) as f32) * CELL_SIZE;
+        let player = Rect::from_min_size(min, Vec2::splat(CELL_SIZE));
+        ui.painter().rect_stroke(player.expand(1.0), 0.0, Stroke::new(1.0, Color32::RED));
+    }
+}
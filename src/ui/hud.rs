@@ -0,0 +1,181 @@
+use egui::{Color32, Pos2, Rect, Vec2};
+
+/// Shape of the crosshair in the middle of the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrosshairStyle {
+    Cross,
+    Dot,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CrosshairSettings {
+    pub style: CrosshairStyle,
+    /// Length of each line tip to tip, in points at HUD scale 1
+    pub size: f32,
+    /// Line thickness (half the dot diameter) in points at HUD scale 1
+    pub thickness: f32,
+    pub color: Color32,
+}
+
+impl Default for CrosshairSettings {
+    fn default() -> Self {
+        Self {
+            style: CrosshairStyle::Cross,
+            size: 20.0,
+            thickness: 2.0,
+            color: Color32::WHITE,
+        }
+    }
+}
+
+/// Player-adjustable HUD appearance
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HudSettings {
+    /// Multiplier applied to every HUD element on top of the display's pixels-per-point
+    pub scale: f32,
+    pub crosshair: CrosshairSettings,
+    /// Side length of a hotbar slot, in points at HUD scale 1
+    pub hotbar_slot_size: f32,
+}
+
+impl Default for HudSettings {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            crosshair: CrosshairSettings::default(),
+            hotbar_slot_size: 48.0,
+        }
+    }
+}
+
+/// Number of slots drawn in the hotbar
+pub const HOTBAR_SLOT_COUNT: usize = 9;
+
+/// Gap between hotbar slots, as a fraction of the slot size
+const HOTBAR_SPACING: f32 = 0.1;
+
+/// Gap between the hotbar and the bottom of the screen, as a fraction of the slot size
+const HOTBAR_MARGIN: f32 = 0.4;
+
+/// Where each hotbar slot goes: a row of squares centered at the bottom of `screen`,
+/// sized by `slot_size` points times `hud_scale`
+pub fn hotbar_slot_rects(screen: Rect, slot_size: f32, hud_scale: f32) -> [Rect; HOTBAR_SLOT_COUNT] {
+    let size = slot_size * hud_scale;
+    let spacing = size * HOTBAR_SPACING;
+    let width = size * HOTBAR_SLOT_COUNT as f32 + spacing * (HOTBAR_SLOT_COUNT - 1) as f32;
+    let left = screen.center().x - width / 2.0;
+    let top = screen.bottom() - size * (1.0 + HOTBAR_MARGIN);
+
+    std::array::from_fn(|slot| {
+        let min = Pos2::new(left + slot as f32 * (size + spacing), top);
+        Rect::from_min_size(min, Vec2::splat(size))
+    })
+}
+
+/// Index of the hotbar slot under `pointer`, laid out as by `hotbar_slot_rects`.
+/// The gaps between slots belong to no slot.
+pub fn hotbar_slot_at(screen: Rect, slot_size: f32, hud_scale: f32, pointer: Pos2) -> Option<usize> {
+    hotbar_slot_rects(screen, slot_size, hud_scale)
+        .iter()
+        .position(|rect| rect.contains(pointer))
+}
+
+/// Filled rectangles making up the crosshair, centered on `center`. The dot style
+/// is a single square meant to be painted fully rounded.
+///
+/// Sizes are scaled by `hud_scale` and snapped to whole physical pixels so the
+/// lines stay crisp and symmetric at any DPI.
+pub fn crosshair_rects(center: Pos2, settings: &CrosshairSettings, hud_scale: f32, pixels_per_point: f32) -> Vec<Rect> {
+    let snap = |points: f32| (points * hud_scale * pixels_per_point).round().max(1.0) / pixels_per_point;
+    let size = snap(settings.size);
+    let thickness = snap(settings.thickness);
+
+    match settings.style {
+        CrosshairStyle::Cross => vec![
+            Rect::from_center_size(center, Vec2::new(size, thickness)),
+            Rect::from_center_size(center, Vec2::new(thickness, size)),
+        ],
+        CrosshairStyle::Dot => vec![Rect::from_center_size(center, Vec2::splat(thickness * 2.0))],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cross_lines_are_centered_and_symmetric() {
+        let center = Pos2::new(400.0, 300.0);
+        let settings = CrosshairSettings::default();
+
+        let rects = crosshair_rects(center, &settings, 1.5, 2.0);
+
+        assert_eq!(rects.len(), 2);
+        let (horizontal, vertical) = (rects[0], rects[1]);
+        assert_eq!(horizontal.center(), center);
+        assert_eq!(vertical.center(), center);
+        assert_eq!(horizontal.width(), 30.0);
+        assert_eq!(horizontal.height(), 3.0);
+        // The vertical line is the horizontal one turned a quarter
+        assert_eq!(vertical.size(), Vec2::new(horizontal.height(), horizontal.width()));
+    }
+
+    #[test]
+    fn crosshair_snaps_to_whole_physical_pixels() {
+        let settings = CrosshairSettings { size: 9.3, thickness: 0.2, ..CrosshairSettings::default() };
+
+        let rects = crosshair_rects(Pos2::ZERO, &settings, 1.0, 1.25);
+
+        for rect in rects {
+            for points in [rect.width(), rect.height()] {
+                let pixels = points * 1.25;
+                assert!((pixels - pixels.round()).abs() < 1e-4 && pixels >= 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn dot_is_a_single_centered_square() {
+        let center = Pos2::new(10.0, 20.0);
+        let settings = CrosshairSettings { style: CrosshairStyle::Dot, ..CrosshairSettings::default() };
+
+        let rects = crosshair_rects(center, &settings, 1.0, 1.0);
+
+        assert_eq!(rects, vec![Rect::from_center_size(center, Vec2::splat(4.0))]);
+    }
+
+    #[test]
+    fn hotbar_has_nine_evenly_spaced_non_overlapping_slots() {
+        let screen = Rect::from_min_size(Pos2::ZERO, Vec2::new(1280.0, 720.0));
+
+        let rects = hotbar_slot_rects(screen, 48.0, 1.5);
+
+        assert_eq!(rects.len(), 9);
+        let step = rects[1].left() - rects[0].left();
+        for pair in rects.windows(2) {
+            assert!((pair[1].left() - pair[0].left() - step).abs() < 1e-3);
+            assert!(pair[0].right() < pair[1].left(), "slots overlap");
+            assert_eq!(pair[0].top(), pair[1].top());
+        }
+        for rect in rects {
+            assert!((rect.size() - Vec2::splat(72.0)).length() < 1e-3);
+            assert!(screen.contains_rect(rect));
+        }
+        let row_center = (rects[0].left() + rects[8].right()) / 2.0;
+        assert!((row_center - screen.center().x).abs() < 1e-3);
+    }
+
+    #[test]
+    fn pointer_resolves_to_the_slot_under_it() {
+        let screen = Rect::from_min_size(Pos2::ZERO, Vec2::new(1280.0, 720.0));
+        let rects = hotbar_slot_rects(screen, 48.0, 1.0);
+
+        for (slot, rect) in rects.iter().enumerate() {
+            assert_eq!(hotbar_slot_at(screen, 48.0, 1.0, rect.center()), Some(slot));
+        }
+        // The gap between two slots and the open screen hover nothing
+        let gap = Pos2::new((rects[2].right() + rects[3].left()) / 2.0, rects[2].center().y);
+        assert_eq!(hotbar_slot_at(screen, 48.0, 1.0, gap), None);
+        assert_eq!(hotbar_slot_at(screen, 48.0, 1.0, screen.center()), None);
+    }
+}
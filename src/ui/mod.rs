@@ -3,11 +3,46 @@ use egui_wgpu::Renderer;
 use egui_winit::State;
 use winit::{event::WindowEvent, window::Window};
 
+use crate::game::{ItemStack, Player};
+use crate::rendering::{atlas_tile_uv, texture_id_for_block, Face, RenderStats, TextureAtlas};
+use crate::world::{BlockType, SliceAxis, World};
+
+mod biome_map;
+mod cross_section;
+mod hud;
+
+pub use biome_map::{biome_color, BiomeMap};
+pub use cross_section::CrossSection;
+pub use hud::{crosshair_rects, hotbar_slot_at, hotbar_slot_rects, CrosshairStyle, HudSettings, HOTBAR_SLOT_COUNT};
+
 /// UI manager using egui for immediate mode GUI
 pub struct UIManager {
     pub ctx: egui::Context,
     pub state: State,
     pub renderer: Renderer,
+    hud: HudSettings,
+    show_biome_map: bool,
+    biome_map: BiomeMap,
+    show_cross_section: bool,
+    cross_section: CrossSection,
+    /// The block atlas as an egui texture, for hotbar icons
+    block_icons: Option<BlockIcons>,
+}
+
+/// Block atlas registered with egui
+#[derive(Debug, Clone, Copy)]
+struct BlockIcons {
+    texture: egui::TextureId,
+    atlas_size: u32,
+}
+
+impl BlockIcons {
+    /// UV rectangle of the atlas tile shown for an item
+    fn uv(&self, item: &ItemStack) -> egui::Rect {
+        let texture_id = texture_id_for_block(item.item_type, item.state, Face::Front);
+        let (min, max) = atlas_tile_uv(texture_id, self.atlas_size);
+        egui::Rect::from_min_max(egui::pos2(min[0], min[1]), egui::pos2(max[0], max[1]))
+    }
 }
 
 impl UIManager {
@@ -39,16 +74,59 @@ impl UIManager {
             ctx,
             state: egui_state,
             renderer: egui_renderer,
+            hud: HudSettings::default(),
+            show_biome_map: false,
+            biome_map: BiomeMap::default(),
+            show_cross_section: false,
+            cross_section: CrossSection::default(),
+            block_icons: None,
         }
     }
 
+    /// Make the block atlas available to the HUD for item icons
+    pub fn register_block_atlas(&mut self, device: &wgpu::Device, atlas: &TextureAtlas) {
+        let texture = self.renderer.register_native_texture(device, atlas.view(), wgpu::FilterMode::Nearest);
+        self.block_icons = Some(BlockIcons {
+            texture,
+            atlas_size: atlas.atlas_size(),
+        });
+    }
+
+    pub fn hud_settings(&self) -> &HudSettings {
+        &self.hud
+    }
+
+    pub fn set_hud_settings(&mut self, settings: HudSettings) {
+        self.hud = settings;
+    }
+
     pub fn handle_input(&mut self, window: &Window, event: &winit::event::WindowEvent) -> bool {
         let response = self.state.on_window_event(window, event);
         response.consumed
     }
 
-    pub fn prepare(&mut self, window: &Window) -> Vec<egui::ClippedPrimitive> {
+    pub fn prepare(
+        &mut self,
+        window: &Window,
+        stats: &RenderStats,
+        world: &World,
+        player: &Player,
+        looking_at: Option<BlockType>,
+    ) -> Vec<egui::ClippedPrimitive> {
         let raw_input = self.state.take_egui_input(window);
+        let hud = self.hud;
+        let block_icons = self.block_icons;
+        if self.show_biome_map {
+            self.biome_map.update(world, player.position());
+        }
+        let mut show_biome_map = self.show_biome_map;
+        let biome_map = &self.biome_map;
+        if self.show_cross_section {
+            self.cross_section.update(&self.ctx, world, player.feet_position());
+        }
+        let mut show_cross_section = self.show_cross_section;
+        let mut cross_section_axis = self.cross_section.axis;
+        let cross_section = &self.cross_section;
         
         // Run UI rendering in a closure
         let (shapes, platform_output) = {
@@ -57,62 +135,115 @@ impl UIManager {
                 egui::Window::new("Debug Info")
                     .resizable(false)
                     .show(ctx, |ui| {
-                        ui.label("FPS: 60"); // TODO: Calculate actual FPS
+                        let fps = if stats.frame_time_ms > 0.0 { 1000.0 / stats.frame_time_ms } else { 0.0 };
+                        ui.label(format!("FPS: {:.0} ({:.1} ms)", fps, stats.frame_time_ms));
                         ui.label("Position: (0, 0, 0)"); // TODO: Get actual position
-                        ui.label("Chunks loaded: 0"); // TODO: Get actual chunk count
+                        ui.label(format!("Chunks drawn: {} (culled {})", stats.chunks_drawn, stats.chunks_culled));
+                        ui.label(format!("Draw calls: {}  Triangles: {}", stats.draw_calls, stats.triangles));
+                        if let Some(block) = looking_at {
+                            ui.separator();
+                            show_block_info(ui, block);
+                        }
+                        ui.checkbox(&mut show_biome_map, "Biome map");
+                        ui.checkbox(&mut show_cross_section, "Lighting cross-section");
                     });
 
-                // Render hotbar
-                egui::Area::new(egui::Id::new("hotbar"))
-                    .anchor(egui::Align2::CENTER_BOTTOM, egui::Vec2::new(0.0, -20.0))
-                    .show(ctx, |ui| {
-                        ui.horizontal(|ui| {
-                            for i in 0..9 {
-                                let selected = i == 0; // TODO: Get actual selected slot
-                                let bg_color = if selected {
-                                    egui::Color32::LIGHT_GRAY
-                                } else {
-                                    egui::Color32::DARK_GRAY
-                                };
-                                
-                                let (rect, _) = ui.allocate_exact_size(
-                                    egui::Vec2::splat(40.0),
-                                    egui::Sense::click()
-                                );
-                                
-                                ui.painter().rect_filled(rect, 2.0, bg_color);
-                                ui.painter().rect_stroke(rect, 2.0, egui::Stroke::new(1.0, egui::Color32::WHITE));
-                            }
+                if show_biome_map {
+                    egui::Window::new("Biome Map")
+                        .resizable(false)
+                        .show(ctx, |ui| {
+                            ui.horizontal(|ui| {
+                                biome_map.show(ui);
+                                ui.vertical(BiomeMap::show_legend);
+                            });
                         });
-                    });
+                }
 
-                // Render crosshair
-                egui::Area::new(egui::Id::new("crosshair"))
-                    .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
-                    .show(ctx, |ui| {
-                        let size = 20.0;
-                        let thickness = 2.0;
-                        let color = egui::Color32::WHITE;
-                        
-                        let center = ui.available_rect_before_wrap().center();
-                        let painter = ui.painter();
-                        
-                        // Horizontal line
-                        painter.line_segment(
-                            [center + egui::Vec2::new(-size/2.0, 0.0), center + egui::Vec2::new(size/2.0, 0.0)],
-                            egui::Stroke::new(thickness, color)
-                        );
-                        
-                        // Vertical line
-                        painter.line_segment(
-                            [center + egui::Vec2::new(0.0, -size/2.0), center + egui::Vec2::new(0.0, size/2.0)],
-                            egui::Stroke::new(thickness, color)
-                        );
+                if show_cross_section {
+                    egui::Window::new("Lighting Cross-Section")
+                        .resizable(false)
+                        .show(ctx, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.radio_value(&mut cross_section_axis, SliceAxis::X, "Along Z");
+                                ui.radio_value(&mut cross_section_axis, SliceAxis::Z, "Along X");
+                            });
+                            egui::ScrollArea::vertical().show(ui, |ui| cross_section.show(ui));
+                        });
+                }
+
+                // Render the hotbar on the foreground layer, below the crosshair
+                let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("hotbar")));
+                let slots = hotbar_slot_rects(ctx.screen_rect(), hud.hotbar_slot_size, hud.scale);
+                for (slot, (rect, item)) in slots.iter().zip(player.inventory().hotbar()).enumerate() {
+                    let selected = slot == player.selected_hotbar_slot();
+                    painter.rect_filled(*rect, 2.0, egui::Color32::from_black_alpha(140));
+
+                    if !item.is_empty() {
+                        if let Some(icons) = block_icons {
+                            let icon = rect.shrink(rect.width() * 0.15);
+                            painter.image(icons.texture, icon, icons.uv(item), egui::Color32::WHITE);
+                        }
+                        if item.count > 1 {
+                            painter.text(
+                                rect.right_bottom() - egui::vec2(3.0, 1.0) * hud.scale,
+                                egui::Align2::RIGHT_BOTTOM,
+                                item.count.to_string(),
+                                egui::FontId::proportional(13.0 * hud.scale),
+                                egui::Color32::WHITE,
+                            );
+                        }
+                    }
+
+                    let stroke = if selected {
+                        egui::Stroke::new(3.0 * hud.scale, egui::Color32::WHITE)
+                    } else {
+                        egui::Stroke::new(1.0 * hud.scale, egui::Color32::GRAY)
+                    };
+                    painter.rect_stroke(*rect, 2.0, stroke);
+                }
+
+                // Name the item under the cursor; empty slots get no tooltip
+                let hovered = ctx
+                    .pointer_hover_pos()
+                    .and_then(|pointer| hotbar_slot_at(ctx.screen_rect(), hud.hotbar_slot_size, hud.scale, pointer))
+                    .and_then(|slot| player.inventory().get_hotbar_item(slot))
+                    .filter(|item| !item.is_empty());
+                if let Some(item) = hovered {
+                    egui::show_tooltip_at_pointer(ctx, painter.layer_id(), egui::Id::new("hotbar_tooltip"), |ui| {
+                        show_item_tooltip(ui, item);
                     });
+                }
+
+                // Brief note in the corner while an autosave is being written
+                if world.is_autosaving() {
+                    let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("autosave")));
+                    painter.text(
+                        ctx.screen_rect().right_top() + egui::vec2(-8.0, 8.0) * hud.scale,
+                        egui::Align2::RIGHT_TOP,
+                        "Saving...",
+                        egui::FontId::proportional(14.0 * hud.scale),
+                        egui::Color32::WHITE,
+                    );
+                }
+
+                // Render crosshair directly on the foreground layer, centered on the screen
+                let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("crosshair")));
+                let center = ctx.screen_rect().center();
+                let crosshair = &hud.crosshair;
+                let rounding = match crosshair.style {
+                    CrosshairStyle::Cross => 0.0,
+                    CrosshairStyle::Dot => f32::INFINITY,
+                };
+                for rect in crosshair_rects(center, crosshair, hud.scale, ctx.pixels_per_point()) {
+                    painter.rect_filled(rect, rounding, crosshair.color);
+                }
             });
             (full_output.shapes, full_output.platform_output)
         };
         
+        self.show_biome_map = show_biome_map;
+        self.show_cross_section = show_cross_section;
+        self.cross_section.axis = cross_section_axis;
         self.state.handle_platform_output(window, platform_output);
         
         let primitives = self.ctx.tessellate(shapes, self.ctx.pixels_per_point());
@@ -147,4 +278,39 @@ impl UIManager {
         // Render UI
         self.renderer.render(&mut render_pass, &primitives, screen_descriptor);
     }
-}
\ No newline at end of file
+}
+
+/// Display name and a short description of an item
+fn show_item_tooltip(ui: &mut egui::Ui, item: &ItemStack) {
+    ui.strong(item.item_type.variant_name(item.state));
+    ui.label(egui::RichText::new(item_descriptor(item.item_type)).weak());
+}
+
+/// One-line summary of what a block is for
+fn item_descriptor(block: BlockType) -> &'static str {
+    match block {
+        BlockType::Water | BlockType::Lava => "Liquid",
+        _ if block.is_redstone_component() => "Redstone component",
+        _ if block.is_interactive() => "Can be used",
+        _ if block.light_level() > 0 => "Light source",
+        _ if block.is_cross_shaped() => "Plant",
+        _ if block.is_affected_by_gravity() => "Falls when unsupported",
+        _ => "Building block",
+    }
+}
+
+/// Properties of a block, for the debug window and block tooltips
+fn show_block_info(ui: &mut egui::Ui, block: BlockType) {
+    let info = block.info();
+    ui.label(format!("Looking at: {}", info.name));
+    ui.label(format!(
+        "Hardness: {:.1}  Mining time: {:.2}s  Blast resistance: {:.1}",
+        info.hardness, info.mining_time, info.explosion_resistance
+    ));
+    ui.label(if info.mineable_by_hand { "Tool: none needed" } else { "Tool: pickaxe" });
+    if info.light_level > 0 {
+        ui.label(format!("Light level: {}", info.light_level));
+    }
+    let drops: Vec<String> = info.drops.iter().map(|(drop, count)| format!("{} x{}", drop.name(), count)).collect();
+    ui.label(if drops.is_empty() { "Drops: nothing".to_string() } else { format!("Drops: {}", drops.join(", ")) });
+}
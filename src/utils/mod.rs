@@ -23,6 +23,68 @@ pub mod math {
     }
 }
 
+/// Deterministic randomness tied to positions in the world
+pub mod rng {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// SplitMix64 finalizer: spreads every input bit over the whole output, so
+    /// neighboring inputs give unrelated hashes
+    pub fn mix(mut value: u64) -> u64 {
+        value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        value ^ (value >> 31)
+    }
+
+    /// Hash a world seed and a block position into a single value
+    pub fn position_hash(seed: u64, x: i32, y: i32, z: i32) -> u64 {
+        let mut hash = mix(seed);
+        for coordinate in [x, y, z] {
+            hash = mix(hash ^ coordinate as u32 as u64);
+        }
+        hash
+    }
+
+    /// RNG that always produces the same sequence for the same seed and position,
+    /// for generation steps that need reproducible randomness at a coordinate.
+    /// Pass 0 for axes that don't matter, e.g. `y` for a whole column.
+    pub fn position_rng(seed: u64, x: i32, y: i32, z: i32) -> StdRng {
+        StdRng::seed_from_u64(position_hash(seed, x, y, z))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use rand::Rng;
+
+        #[test]
+        fn same_inputs_give_the_same_draws() {
+            let draws = |rng: &mut StdRng| -> Vec<u32> { (0..4).map(|_| rng.gen()).collect() };
+
+            let first = draws(&mut position_rng(42, 10, 64, -7));
+            let second = draws(&mut position_rng(42, 10, 64, -7));
+
+            assert_eq!(first, second);
+            assert_ne!(first, draws(&mut position_rng(43, 10, 64, -7)));
+        }
+
+        #[test]
+        fn adjacent_coordinates_give_uncorrelated_draws() {
+            let first_draw = |x: i32| position_rng(42, x, 64, 0).gen::<f64>();
+            let samples: Vec<(f64, f64)> = (0..2000).map(|x| (first_draw(x), first_draw(x + 1))).collect();
+
+            let n = samples.len() as f64;
+            let mean = |pick: fn(&(f64, f64)) -> f64| samples.iter().map(pick).sum::<f64>() / n;
+            let (mean_a, mean_b) = (mean(|s| s.0), mean(|s| s.1));
+            let covariance = samples.iter().map(|(a, b)| (a - mean_a) * (b - mean_b)).sum::<f64>() / n;
+            let variance = |pick: fn(&(f64, f64)) -> f64, m: f64| samples.iter().map(|s| (pick(s) - m).powi(2)).sum::<f64>() / n;
+            let correlation = covariance / (variance(|s| s.0, mean_a) * variance(|s| s.1, mean_b)).sqrt();
+
+            assert!(correlation.abs() < 0.1, "neighbors correlate: {correlation}");
+        }
+    }
+}
+
 /// Performance measurement utilities
 pub mod perf {
     use std::time::{Duration, Instant};
@@ -30,6 +30,8 @@ pub enum BlockType {
     TallGrass,
     Flower,
     Mushroom,
+    Snow,
+    Ice,
     
     // Liquids
     Water,
@@ -49,6 +51,7 @@ pub enum BlockType {
     Lever,
     Button,
     PressurePlate,
+    Tnt,
     
     // Utility blocks
     Chest,
@@ -66,9 +69,164 @@ pub enum BlockType {
     Netherrack,
     SoulSand,
     Glowstone,
+    Slab,
+    Stairs,
+}
+
+/// Flower varieties, stored as the block state of `BlockType::Flower`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowerKind {
+    Dandelion,
+    Poppy,
+    Cornflower,
+    OxeyeDaisy,
+    Allium,
+}
+
+impl FlowerKind {
+    pub fn state(self) -> u8 {
+        self as u8
+    }
+}
+
+/// Tall grass varieties, stored as the block state of `BlockType::TallGrass`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrassKind {
+    Grass,
+    Fern,
+}
+
+impl GrassKind {
+    pub fn state(self) -> u8 {
+        self as u8
+    }
+}
+
+/// Horizontal direction a door faces: the way the player was looking when placing it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DoorFacing {
+    /// +z
+    North,
+    /// -z
+    South,
+    /// +x
+    East,
+    /// -x
+    West,
+}
+
+impl DoorFacing {
+    /// The cardinal direction closest to a horizontal look direction
+    pub fn from_direction(x: f32, z: f32) -> Self {
+        if x.abs() > z.abs() {
+            if x > 0.0 { DoorFacing::East } else { DoorFacing::West }
+        } else if z > 0.0 {
+            DoorFacing::North
+        } else {
+            DoorFacing::South
+        }
+    }
+
+    /// Unit step along x and z
+    pub fn offset(self) -> (i32, i32) {
+        match self {
+            DoorFacing::North => (0, 1),
+            DoorFacing::South => (0, -1),
+            DoorFacing::East => (1, 0),
+            DoorFacing::West => (-1, 0),
+        }
+    }
+
+    /// Facing from the two low bits of a block state
+    pub fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0 => DoorFacing::North,
+            1 => DoorFacing::South,
+            2 => DoorFacing::East,
+            _ => DoorFacing::West,
+        }
+    }
+
+    /// The direction to the right when looking this way
+    pub fn right(self) -> Self {
+        match self {
+            DoorFacing::North => DoorFacing::West,
+            DoorFacing::West => DoorFacing::South,
+            DoorFacing::South => DoorFacing::East,
+            DoorFacing::East => DoorFacing::North,
+        }
+    }
+}
+
+/// Block state of one half of a door. Both halves carry the same open, hinge and
+/// facing bits, so either can be read on its own.
+///
+/// Bit 0 marks the upper half, bit 1 open, bit 2 a hinge on the right, and bits
+/// 3-4 the facing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DoorState {
+    pub upper: bool,
+    pub open: bool,
+    pub hinge_right: bool,
+    pub facing: DoorFacing,
+}
+
+impl DoorState {
+    pub fn from_state(state: u8) -> Self {
+        let facing = DoorFacing::from_bits(state >> 3);
+        Self {
+            upper: state & 0b001 != 0,
+            open: state & 0b010 != 0,
+            hinge_right: state & 0b100 != 0,
+            facing,
+        }
+    }
+
+    pub fn state(self) -> u8 {
+        self.upper as u8 | (self.open as u8) << 1 | (self.hinge_right as u8) << 2 | (self.facing as u8) << 3
+    }
+
+    /// Vertical offset from this half to the other one
+    pub fn partner_offset(self) -> i32 {
+        if self.upper { -1 } else { 1 }
+    }
+
+    /// The side of the block the door panel lies against: the far side when closed,
+    /// the hinge side when open
+    pub fn panel_side(self) -> DoorFacing {
+        match (self.open, self.hinge_right) {
+            (false, _) => self.facing,
+            (true, true) => self.facing.right(),
+            (true, false) => self.facing.right().right().right(),
+        }
+    }
 }
 
 impl BlockType {
+    /// Number of variants selectable through the block state (1 for plain blocks)
+    pub fn variant_count(&self) -> u8 {
+        match self {
+            BlockType::Flower => 5,
+            BlockType::TallGrass => 2,
+            BlockType::Slab => 2,
+            _ => 1,
+        }
+    }
+
+    /// Display name of a specific variant, falling back to the block name
+    pub fn variant_name(&self, state: u8) -> &'static str {
+        match (self, state) {
+            (BlockType::Flower, 0) => "Dandelion",
+            (BlockType::Flower, 1) => "Poppy",
+            (BlockType::Flower, 2) => "Cornflower",
+            (BlockType::Flower, 3) => "Oxeye Daisy",
+            (BlockType::Flower, 4) => "Allium",
+            (BlockType::TallGrass, 1) => "Fern",
+            (BlockType::Slab, 1) => "Upper Stone Slab",
+            _ => self.name(),
+        }
+    }
+
     /// Check if the block is solid (player can't walk through it)
     pub fn is_solid(&self) -> bool {
         match self {
@@ -79,31 +237,64 @@ impl BlockType {
             | BlockType::Flower 
             | BlockType::Mushroom 
             | BlockType::DeadBush 
+            | BlockType::Snow 
             | BlockType::Torch 
             | BlockType::RedstoneWire 
-            | BlockType::RedstoneTorch => false,
+            | BlockType::RedstoneTorch
+            | BlockType::Lever
+            | BlockType::Button
+            | BlockType::PressurePlate => false,
             _ => true,
         }
     }
 
+    /// Like `is_solid`, but for blocks whose collision depends on their state:
+    /// open doors can be walked through
+    pub fn is_solid_with_state(&self, state: u8) -> bool {
+        match self {
+            BlockType::Door => !DoorState::from_state(state).open,
+            _ => self.is_solid(),
+        }
+    }
+
     /// Check if the block is transparent (light passes through)
     pub fn is_transparent(&self) -> bool {
         match self {
             BlockType::Air
             | BlockType::Glass
             | BlockType::Water
+            | BlockType::Ice
             | BlockType::Leaves
             | BlockType::TallGrass
             | BlockType::Flower
             | BlockType::Mushroom
             | BlockType::DeadBush
+            | BlockType::Snow
             | BlockType::Torch
             | BlockType::RedstoneWire
-            | BlockType::RedstoneTorch => true,
+            | BlockType::RedstoneTorch
+            | BlockType::Door
+            | BlockType::Lever
+            | BlockType::Button
+            | BlockType::PressurePlate
+            | BlockType::Slab
+            | BlockType::Stairs => true,
             _ => false,
         }
     }
 
+    /// How much light, 0-15, the block takes away from light passing through it, on
+    /// top of the usual one level per block for spreading light. Opaque blocks take
+    /// it all; leaves and water let some through.
+    pub fn light_opacity(&self) -> u8 {
+        match self {
+            BlockType::Leaves => 1,
+            BlockType::Water => 2,
+            _ if self.is_transparent() => 0,
+            _ => 15,
+        }
+    }
+
     /// Check if the block emits light
     pub fn light_level(&self) -> u8 {
         match self {
@@ -115,6 +306,27 @@ impl BlockType {
         }
     }
 
+    /// Red, green and blue light emitted by the block, each 0-15.
+    /// The brightest channel matches `light_level`.
+    pub fn light_color(&self) -> [u8; 3] {
+        match self {
+            BlockType::Torch => [14, 12, 9],
+            BlockType::RedstoneTorch => [7, 1, 1],
+            BlockType::Glowstone => [15, 13, 9],
+            BlockType::Lava => [15, 9, 3],
+            _ => [0, 0, 0],
+        }
+    }
+
+    /// Damage per hit dealt to an entity touching this block
+    pub fn contact_damage(&self) -> f32 {
+        match self {
+            BlockType::Cactus => 1.0,
+            BlockType::Lava => 4.0,
+            _ => 0.0,
+        }
+    }
+
     /// Check if the block can be mined by hand
     pub fn can_mine_by_hand(&self) -> bool {
         match self {
@@ -140,12 +352,15 @@ impl BlockType {
             | BlockType::Flower
             | BlockType::Mushroom
             | BlockType::DeadBush
+            | BlockType::Snow
+            | BlockType::Tnt
             | BlockType::Torch
             | BlockType::RedstoneWire
             | BlockType::RedstoneTorch => 0.1,
             BlockType::Dirt
             | BlockType::Sand
-            | BlockType::Gravel => 0.5,
+            | BlockType::Gravel
+            | BlockType::Ice => 0.5,
             BlockType::Wood
             | BlockType::Planks
             | BlockType::Leaves => 0.75,
@@ -176,6 +391,7 @@ impl BlockType {
                 // TODO: Random chance for seeds
                 vec![]
             },
+            BlockType::Ice => vec![], // Ice melts instead of dropping
             _ => vec![(*self, 1)],
         }
     }
@@ -196,6 +412,7 @@ impl BlockType {
             | BlockType::Flower
             | BlockType::Mushroom
             | BlockType::DeadBush
+            | BlockType::Snow
             | BlockType::Water => true,
             _ => false,
         }
@@ -208,10 +425,13 @@ impl BlockType {
             BlockType::TallGrass
             | BlockType::Flower
             | BlockType::Mushroom
-            | BlockType::DeadBush => 0.0,
+            | BlockType::DeadBush
+            | BlockType::Tnt => 0.0,
+            BlockType::Snow => 0.1,
             BlockType::Dirt
             | BlockType::Sand
-            | BlockType::Gravel => 0.5,
+            | BlockType::Gravel
+            | BlockType::Ice => 0.5,
             BlockType::Wood
             | BlockType::Planks => 2.0,
             BlockType::Stone => 1.5,
@@ -239,10 +459,16 @@ impl BlockType {
             BlockType::Stone
             | BlockType::Cobblestone => 30.0,
             BlockType::Obsidian => 6000.0,
+            BlockType::Tnt => 0.0,
             _ => 15.0,
         }
     }
 
+    /// Plants drawn as two crossed quads instead of a cube
+    pub fn is_cross_shaped(&self) -> bool {
+        matches!(self, BlockType::TallGrass | BlockType::Flower | BlockType::Mushroom | BlockType::DeadBush)
+    }
+
     /// Check if the block requires a support block below it
     pub fn needs_support(&self) -> bool {
         match self {
@@ -250,6 +476,7 @@ impl BlockType {
             | BlockType::Flower
             | BlockType::Mushroom
             | BlockType::DeadBush
+            | BlockType::Snow
             | BlockType::Torch
             | BlockType::RedstoneTorch => true,
             _ => false,
@@ -281,6 +508,11 @@ impl BlockType {
             BlockType::Planks => 5,
             BlockType::Glass => 20,
             BlockType::Torch => 50,
+            BlockType::Tnt => 46,
+            BlockType::Snow => 78,
+            BlockType::Ice => 79,
+            BlockType::Slab => 44,
+            BlockType::Stairs => 67,
             _ => 255, // Unknown
         }
     }
@@ -309,6 +541,11 @@ impl BlockType {
             10 => Some(BlockType::Lava),
             20 => Some(BlockType::Glass),
             50 => Some(BlockType::Torch),
+            46 => Some(BlockType::Tnt),
+            78 => Some(BlockType::Snow),
+            79 => Some(BlockType::Ice),
+            44 => Some(BlockType::Slab),
+            67 => Some(BlockType::Stairs),
             _ => None,
         }
     }
@@ -338,6 +575,8 @@ impl BlockType {
             BlockType::TallGrass => "Tall Grass",
             BlockType::Flower => "Flower",
             BlockType::Mushroom => "Mushroom",
+            BlockType::Snow => "Snow",
+            BlockType::Ice => "Ice",
             BlockType::Water => "Water",
             BlockType::Lava => "Lava",
             BlockType::Planks => "Wooden Planks",
@@ -351,6 +590,7 @@ impl BlockType {
             BlockType::Lever => "Lever",
             BlockType::Button => "Button",
             BlockType::PressurePlate => "Pressure Plate",
+            BlockType::Tnt => "TNT",
             BlockType::Chest => "Chest",
             BlockType::Furnace => "Furnace",
             BlockType::CraftingTable => "Crafting Table",
@@ -364,6 +604,37 @@ impl BlockType {
             BlockType::Netherrack => "Netherrack",
             BlockType::SoulSand => "Soul Sand",
             BlockType::Glowstone => "Glowstone",
+            BlockType::Slab => "Stone Slab",
+            BlockType::Stairs => "Cobblestone Stairs",
+        }
+    }
+}
+
+/// A block's gameplay properties gathered in one place, for tooltips and the
+/// debug screen
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockInfo {
+    pub name: &'static str,
+    pub hardness: f32,
+    /// Seconds to mine, before tool bonuses
+    pub mining_time: f32,
+    pub explosion_resistance: f32,
+    /// Whether the block can be mined without a tool
+    pub mineable_by_hand: bool,
+    pub light_level: u8,
+    pub drops: Vec<(BlockType, u32)>,
+}
+
+impl BlockType {
+    pub fn info(&self) -> BlockInfo {
+        BlockInfo {
+            name: self.name(),
+            hardness: self.hardness(),
+            mining_time: self.mining_time(),
+            explosion_resistance: self.explosion_resistance(),
+            mineable_by_hand: self.can_mine_by_hand(),
+            light_level: self.light_level(),
+            drops: self.drops(),
         }
     }
 }
@@ -372,4 +643,22 @@ impl Default for BlockType {
     fn default() -> Self {
         BlockType::Air
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stone_info_matches_the_individual_properties() {
+        let stone = BlockType::Stone;
+
+        let info = stone.info();
+
+        assert_eq!(info.name, stone.name());
+        assert_eq!(info.hardness, stone.hardness());
+        assert_eq!(info.mining_time, stone.mining_time());
+        assert_eq!(info.light_level, stone.light_level());
+        assert_eq!(info.drops, stone.drops());
+    }
+}
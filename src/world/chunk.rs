@@ -1,10 +1,16 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use crate::world::block::BlockType;
+use crate::utils::rng::mix;
+use crate::world::lighting::LightingEngine;
 
 /// Size of a chunk in blocks (16x16 horizontal)
 pub const CHUNK_SIZE: usize = 16;
 /// Height of a chunk in blocks (256 blocks tall)
 pub const CHUNK_HEIGHT: usize = 256;
+/// Blocks farther than this from the origin along x or z are outside the world.
+/// Keeping well inside `i32` means chunk and block coordinate math never overflows.
+pub const WORLD_BORDER: i32 = 30_000_000;
 
 /// Coordinate for identifying chunks in the world
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -18,9 +24,25 @@ impl ChunkCoordinate {
         Self { x, z }
     }
 
-    /// Get world position of the chunk's origin (bottom-left corner)
+    /// The chunk containing the given world block column
+    pub fn containing(block_x: i32, block_z: i32) -> Self {
+        Self {
+            x: block_x.div_euclid(CHUNK_SIZE as i32),
+            z: block_z.div_euclid(CHUNK_SIZE as i32),
+        }
+    }
+
+    /// Get world position of the chunk's origin (bottom-left corner). Saturates
+    /// instead of overflowing for chunks far outside the world border.
     pub fn world_position(&self) -> (i32, i32) {
-        (self.x * CHUNK_SIZE as i32, self.z * CHUNK_SIZE as i32)
+        (self.x.saturating_mul(CHUNK_SIZE as i32), self.z.saturating_mul(CHUNK_SIZE as i32))
+    }
+
+    /// Whether any of the chunk lies inside the world border
+    pub fn is_within_border(&self) -> bool {
+        let min = ChunkCoordinate::containing(-WORLD_BORDER, -WORLD_BORDER);
+        let max = ChunkCoordinate::containing(WORLD_BORDER, WORLD_BORDER);
+        (min.x..=max.x).contains(&self.x) && (min.z..=max.z).contains(&self.z)
     }
 
     /// Get neighboring chunk coordinates
@@ -46,6 +68,38 @@ impl ChunkCoordinate {
             ChunkCoordinate::new(self.x - 1, self.z - 1), // Southwest
         ]
     }
+
+    /// Squared distance to another chunk, in chunks. Computed in `i64` since chunks
+    /// on opposite sides of the world are too far apart to square in `i32`.
+    pub fn distance_squared(&self, other: ChunkCoordinate) -> i64 {
+        (self.x as i64 - other.x as i64).pow(2) + (self.z as i64 - other.z as i64).pow(2)
+    }
+
+    /// Every chunk within `radius` chunks of this one, ordered nearest first
+    pub fn within_radius(&self, radius: i32) -> Vec<ChunkCoordinate> {
+        let mut coords = Vec::new();
+        for x in (self.x - radius)..=(self.x + radius) {
+            for z in (self.z - radius)..=(self.z + radius) {
+                let coord = ChunkCoordinate::new(x, z);
+                if self.distance_squared(coord) <= (radius as i64).pow(2) {
+                    coords.push(coord);
+                }
+            }
+        }
+
+        // Stable sort keeps scan order between chunks at the same distance
+        coords.sort_by_key(|coord| self.distance_squared(*coord));
+        coords
+    }
+}
+
+/// Which horizontal coordinate a vertical slice through a chunk holds fixed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SliceAxis {
+    /// A plane of constant X, running along Z
+    X,
+    /// A plane of constant Z, running along X
+    Z,
 }
 
 /// A chunk represents a 16x16x256 section of the world
@@ -60,15 +114,28 @@ pub struct Chunk {
     
     /// Highest non-air block at each (x, z) position for optimization
     height_map: Vec<Vec<usize>>,
+
+    /// Number of non-air blocks, maintained by `set_block`
+    non_air_count: usize,
     
     /// Whether this chunk has been modified since last save
     pub dirty: bool,
+
+    /// Whether this chunk has been modified since its mesh was last built
+    #[serde(skip)]
+    needs_remesh: bool,
     
     /// Light levels for each block position
-    /// Using u8 where: 
-    /// - bits 0-3: block light (torch light, etc.)
-    /// - bits 4-7: sky light (sunlight)
-    light_levels: Vec<Vec<Vec<u8>>>,
+    /// Using u16 where:
+    /// - bits 0-3: red block light (torch light, etc.)
+    /// - bits 4-7: green block light
+    /// - bits 8-11: blue block light
+    /// - bits 12-15: sky light (sunlight)
+    light_levels: Vec<Vec<Vec<u16>>>,
+
+    /// Sparse block states (variants) keyed by `state_index`. Blocks without an
+    /// entry are in state 0.
+    block_states: HashMap<u32, u8>,
 }
 
 impl Chunk {
@@ -89,7 +156,7 @@ impl Chunk {
 
                 for _y in 0..CHUNK_HEIGHT {
                     z_blocks.push(BlockType::Air);
-                    z_lights.push(0xFF); // Full sky light initially
+                    z_lights.push(0xF000); // Full sky light initially
                 }
 
                 x_blocks.push(z_blocks);
@@ -106,8 +173,11 @@ impl Chunk {
             coordinate,
             blocks,
             height_map,
+            non_air_count: 0,
             dirty: false,
+            needs_remesh: false,
             light_levels,
+            block_states: HashMap::new(),
         }
     }
 
@@ -128,16 +198,55 @@ impl Chunk {
         let old_block = self.blocks[x][z][y];
         if old_block != block {
             self.blocks[x][z][y] = block;
+            self.block_states.remove(&Self::state_index(x, y, z));
             self.dirty = true;
+            self.needs_remesh = true;
+
+            if old_block == BlockType::Air {
+                self.non_air_count += 1;
+            } else if block == BlockType::Air {
+                self.non_air_count -= 1;
+            }
 
             // Update height map
-            self.update_height_at(x, z);
+            self.update_height_at(x, y, z);
             
             // TODO: Update lighting
             self.update_lighting_at(x, y, z);
         }
     }
 
+    /// Get the block state (variant) at local chunk coordinates
+    pub fn get_block_state(&self, x: usize, y: usize, z: usize) -> u8 {
+        if x >= CHUNK_SIZE || y >= CHUNK_HEIGHT || z >= CHUNK_SIZE {
+            return 0;
+        }
+        self.block_states.get(&Self::state_index(x, y, z)).copied().unwrap_or(0)
+    }
+
+    /// Set the block state at local chunk coordinates. Changing the block itself
+    /// resets its state, so set the block first.
+    pub fn set_block_state(&mut self, x: usize, y: usize, z: usize, state: u8) {
+        if x >= CHUNK_SIZE || y >= CHUNK_HEIGHT || z >= CHUNK_SIZE {
+            return;
+        }
+
+        let index = Self::state_index(x, y, z);
+        let changed = if state == 0 {
+            self.block_states.remove(&index).is_some()
+        } else {
+            self.block_states.insert(index, state) != Some(state)
+        };
+        if changed {
+            self.dirty = true;
+            self.needs_remesh = true;
+        }
+    }
+
+    fn state_index(x: usize, y: usize, z: usize) -> u32 {
+        ((x * CHUNK_SIZE + z) * CHUNK_HEIGHT + y) as u32
+    }
+
     /// Get the height of the highest non-air block at (x, z)
     pub fn get_height_at(&self, x: usize, z: usize) -> usize {
         if x >= CHUNK_SIZE || z >= CHUNK_SIZE {
@@ -146,138 +255,161 @@ impl Chunk {
         self.height_map[x][z]
     }
 
-    /// Update the height map for a specific column
-    fn update_height_at(&mut self, x: usize, z: usize) {
-        if x >= CHUNK_SIZE || z >= CHUNK_SIZE {
-            return;
-        }
+    /// Update the height map after the block at (x, y, z) changed.
+    ///
+    /// Only edits at or above the current top of the column can move it: placing raises
+    /// it directly, and clearing the top block scans down from there.
+    fn update_height_at(&mut self, x: usize, y: usize, z: usize) {
+        let height = self.height_map[x][z];
 
-        let mut height = 0;
-        for y in (0..CHUNK_HEIGHT).rev() {
-            if self.blocks[x][z][y] != BlockType::Air {
-                height = y + 1;
-                break;
+        if self.blocks[x][z][y] != BlockType::Air {
+            if y + 1 > height {
+                self.height_map[x][z] = y + 1;
             }
+        } else if y + 1 == height {
+            self.height_map[x][z] = Self::column_height(&self.blocks[x][z][..y]);
         }
-        self.height_map[x][z] = height;
     }
 
-    /// Update height map for the entire chunk
+    /// Height just above the highest non-air block in a column slice
+    fn column_height(column: &[BlockType]) -> usize {
+        column
+            .iter()
+            .rposition(|&block| block != BlockType::Air)
+            .map_or(0, |y| y + 1)
+    }
+
+    /// Recompute the height map for the entire chunk
     pub fn update_height_map(&mut self) {
         for x in 0..CHUNK_SIZE {
             for z in 0..CHUNK_SIZE {
-                self.update_height_at(x, z);
+                self.height_map[x][z] = Self::column_height(&self.blocks[x][z]);
             }
         }
     }
 
-    /// Get light level at a position
-    pub fn get_light_level(&self, x: usize, y: usize, z: usize) -> u8 {
+    /// Get the packed light value at a position
+    pub fn get_light_level(&self, x: usize, y: usize, z: usize) -> u16 {
         if x >= CHUNK_SIZE || y >= CHUNK_HEIGHT || z >= CHUNK_SIZE {
             return 0;
         }
         self.light_levels[x][z][y]
     }
 
-    /// Set light level at a position
-    pub fn set_light_level(&mut self, x: usize, y: usize, z: usize, light: u8) {
+    /// Set the packed light value at a position
+    pub fn set_light_level(&mut self, x: usize, y: usize, z: usize, light: u16) {
         if x >= CHUNK_SIZE || y >= CHUNK_HEIGHT || z >= CHUNK_SIZE {
             return;
         }
         self.light_levels[x][z][y] = light;
     }
 
-    /// Get sky light level (bits 4-7)
+    /// Get sky light level (bits 12-15)
     pub fn get_sky_light(&self, x: usize, y: usize, z: usize) -> u8 {
-        (self.get_light_level(x, y, z) >> 4) & 0x0F
+        ((self.get_light_level(x, y, z) >> 12) & 0x0F) as u8
     }
 
-    /// Get block light level (bits 0-3)
+    /// Get block light intensity: the brightest of the three channels
     pub fn get_block_light(&self, x: usize, y: usize, z: usize) -> u8 {
-        self.get_light_level(x, y, z) & 0x0F
+        self.get_block_light_rgb(x, y, z).into_iter().max().unwrap_or(0)
+    }
+
+    /// Get block light as red, green and blue levels (bits 0-11)
+    pub fn get_block_light_rgb(&self, x: usize, y: usize, z: usize) -> [u8; 3] {
+        let light = self.get_light_level(x, y, z);
+        [0, 4, 8].map(|shift| ((light >> shift) & 0x0F) as u8)
     }
 
     /// Set sky light level
     pub fn set_sky_light(&mut self, x: usize, y: usize, z: usize, light: u8) {
         let current = self.get_light_level(x, y, z);
-        let new_light = (current & 0x0F) | ((light & 0x0F) << 4);
+        let new_light = (current & 0x0FFF) | (((light & 0x0F) as u16) << 12);
         self.set_light_level(x, y, z, new_light);
     }
 
-    /// Set block light level
+    /// Set white block light of the given level on all channels
     pub fn set_block_light(&mut self, x: usize, y: usize, z: usize, light: u8) {
+        self.set_block_light_rgb(x, y, z, [light; 3]);
+    }
+
+    /// Set block light per channel
+    pub fn set_block_light_rgb(&mut self, x: usize, y: usize, z: usize, rgb: [u8; 3]) {
         let current = self.get_light_level(x, y, z);
-        let new_light = (current & 0xF0) | (light & 0x0F);
-        self.set_light_level(x, y, z, new_light);
+        let packed = rgb
+            .iter()
+            .zip([0, 4, 8])
+            .fold(0u16, |packed, (&channel, shift)| packed | (((channel & 0x0F) as u16) << shift));
+        self.set_light_level(x, y, z, (current & 0xF000) | packed);
     }
 
     /// Simple lighting update for a single block
     fn update_lighting_at(&mut self, x: usize, y: usize, z: usize) {
         // TODO: Implement proper lighting propagation
-        // For now, just set sky light based on whether there are blocks above
+        // For now, just set sky light from how much the blocks above dim it
         
-        let mut sky_light = 15; // Full sunlight
-        for check_y in (y + 1)..CHUNK_HEIGHT {
-            if self.blocks[x][z][check_y] != BlockType::Air {
-                sky_light = 0;
-                break;
-            }
-        }
+        let sky_light = self.blocks[x][z][y..]
+            .iter()
+            .fold(15u8, |light, block| light.saturating_sub(block.light_opacity()));
         
         self.set_sky_light(x, y, z, sky_light);
         
         // Block light is 0 unless the block itself emits light
-        let block_light = match self.blocks[x][z][y] {
-            // TODO: Add light-emitting blocks
-            _ => 0,
-        };
-        self.set_block_light(x, y, z, block_light);
+        let block_light = self.blocks[x][z][y].light_color();
+        self.set_block_light_rgb(x, y, z, block_light);
     }
 
-    /// Calculate lighting for the entire chunk
+    /// Calculate sky and colored block lighting for the entire chunk
     pub fn calculate_lighting(&mut self) {
-        // Sky lighting - propagate from top down
-        for x in 0..CHUNK_SIZE {
+        LightingEngine::new().calculate_chunk_lighting(self);
+    }
+
+    /// Vertical slice through the chunk at local coordinate `index` along `axis`,
+    /// for inspecting lighting. One row per Y level from the bottom up, each with
+    /// `CHUNK_SIZE` cells of the block and its light level: the brighter of sky
+    /// and block light.
+    pub fn cross_section(&self, axis: SliceAxis, index: usize) -> Vec<Vec<(BlockType, u8)>> {
+        (0..CHUNK_HEIGHT)
+            .map(|y| {
+                (0..CHUNK_SIZE)
+                    .map(|along| {
+                        let (x, z) = match axis {
+                            SliceAxis::X => (index, along),
+                            SliceAxis::Z => (along, index),
+                        };
+                        let light = self.get_sky_light(x, y, z).max(self.get_block_light(x, y, z));
+                        (self.get_block(x, y, z), light)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Hash of the chunk's coordinate, blocks and block states. Reads through
+    /// `get_block` in a fixed order with a fixed hash function, so the value only
+    /// changes when the chunk's content does, not when its storage layout or the
+    /// compiler does. Light is left out since it's derived from the blocks.
+    pub fn content_hash(&self) -> u64 {
+        let mut hash = mix(self.coordinate.x as u32 as u64 ^ ((self.coordinate.z as u32 as u64) << 32));
+        for y in 0..CHUNK_HEIGHT {
             for z in 0..CHUNK_SIZE {
-                let mut sky_light = 15;
-                
-                for y in (0..CHUNK_HEIGHT).rev() {
-                    if self.blocks[x][z][y] != BlockType::Air {
-                        sky_light = 0;
-                    }
-                    self.set_sky_light(x, y, z, sky_light);
+                for x in 0..CHUNK_SIZE {
+                    let block = self.get_block(x, y, z).id() as u64;
+                    let state = self.get_block_state(x, y, z) as u64;
+                    hash = mix(hash ^ (block << 8 | state));
                 }
             }
         }
-
-        // TODO: Implement block light propagation and more sophisticated lighting
+        hash
     }
 
     /// Check if chunk is empty (all air blocks)
     pub fn is_empty(&self) -> bool {
-        for x in 0..CHUNK_SIZE {
-            for z in 0..CHUNK_SIZE {
-                if self.height_map[x][z] > 0 {
-                    return false;
-                }
-            }
-        }
-        true
+        self.non_air_count == 0
     }
 
     /// Get the total number of non-air blocks in this chunk
     pub fn block_count(&self) -> usize {
-        let mut count = 0;
-        for x in 0..CHUNK_SIZE {
-            for z in 0..CHUNK_SIZE {
-                for y in 0..CHUNK_HEIGHT {
-                    if self.blocks[x][z][y] != BlockType::Air {
-                        count += 1;
-                    }
-                }
-            }
-        }
-        count
+        self.non_air_count
     }
 
     /// Fill a region with a specific block type
@@ -309,4 +441,95 @@ impl Chunk {
     pub fn mark_clean(&mut self) {
         self.dirty = false;
     }
-}
\ No newline at end of file
+
+    /// Whether the chunk's blocks changed since its mesh was built. Kept apart from
+    /// `dirty`, which tracks saving.
+    pub fn needs_remesh(&self) -> bool {
+        self.needs_remesh
+    }
+
+    /// Mark chunk as meshed (or queued for meshing)
+    pub fn mark_meshed(&mut self) {
+        self.needs_remesh = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn assert_matches_brute_force(chunk: &Chunk) {
+        let mut count = 0;
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                let height = (0..CHUNK_HEIGHT)
+                    .rev()
+                    .find(|&y| chunk.get_block(x, y, z) != BlockType::Air)
+                    .map_or(0, |y| y + 1);
+                assert_eq!(chunk.get_height_at(x, z), height, "height at ({x}, {z})");
+                count += (0..CHUNK_HEIGHT).filter(|&y| chunk.get_block(x, y, z) != BlockType::Air).count();
+            }
+        }
+        assert_eq!(chunk.block_count(), count);
+    }
+
+    #[test]
+    fn within_radius_is_ordered_nearest_first() {
+        let center = ChunkCoordinate::new(-5, 7);
+        let coords = center.within_radius(6);
+
+        let distances: Vec<_> = coords.iter().map(|c| center.distance_squared(*c)).collect();
+        assert!(distances.windows(2).all(|pair| pair[0] <= pair[1]), "load order {distances:?}");
+        assert_eq!(coords[0], center);
+        assert!(distances.iter().all(|&d| d <= 36));
+        // Every chunk of the disc, none twice
+        let expected = (-6..=6i64).flat_map(|x| (-6..=6i64).map(move |z| x * x + z * z)).filter(|&d| d <= 36).count();
+        let unique: std::collections::HashSet<_> = coords.iter().collect();
+        assert_eq!(unique.len(), expected);
+        assert_eq!(coords.len(), expected);
+    }
+
+    #[test]
+    fn incremental_height_map_matches_a_full_rescan() {
+        let mut chunk = Chunk::new(ChunkCoordinate::new(0, 0));
+        let mut rng = StdRng::seed_from_u64(99);
+        chunk.fill_region(0, 0, 0, CHUNK_SIZE, 40, CHUNK_SIZE, BlockType::Stone);
+        assert_matches_brute_force(&chunk);
+
+        // Edits concentrated in a few columns so tops get both raised and dug out
+        for edit in 1..=2000 {
+            let (x, z) = (rng.gen_range(0..4), rng.gen_range(0..4));
+            let y = rng.gen_range(0..60);
+            let block = if rng.gen_bool(0.5) { BlockType::Air } else { BlockType::Dirt };
+            chunk.set_block(x, y, z, block);
+            if edit % 250 == 0 {
+                assert_matches_brute_force(&chunk);
+            }
+        }
+
+        // Clearing whole columns drops their height to zero
+        chunk.fill_region(0, 0, 0, 2, CHUNK_HEIGHT, 2, BlockType::Air);
+        assert_matches_brute_force(&chunk);
+        assert_eq!(chunk.get_height_at(0, 0), 0);
+    }
+
+    #[test]
+    fn cross_section_is_a_height_by_width_grid_of_blocks_and_light() {
+        let mut chunk = Chunk::new(ChunkCoordinate::new(0, 0));
+        chunk.set_block(5, 30, 9, BlockType::Glowstone);
+        chunk.calculate_lighting();
+
+        let along_z = chunk.cross_section(SliceAxis::X, 5);
+        let along_x = chunk.cross_section(SliceAxis::Z, 9);
+
+        for slice in [&along_z, &along_x] {
+            assert_eq!(slice.len(), CHUNK_HEIGHT);
+            assert!(slice.iter().all(|row| row.len() == CHUNK_SIZE));
+        }
+        assert_eq!(along_z[30][9], (BlockType::Glowstone, 15));
+        assert_eq!(along_x[30][5], (BlockType::Glowstone, 15));
+        assert_eq!(along_z[30][8].0, BlockType::Air);
+    }
+}
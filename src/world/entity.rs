@@ -0,0 +1,236 @@
+use glam::Vec3;
+
+use crate::game::Aabb;
+use crate::world::{BlockType, World};
+
+/// Size of a dropped item's collision box
+const ITEM_SIZE: f32 = 0.25;
+/// Slowest landing, in blocks/second, that makes an item bounce; slower ones just stop
+const MIN_BOUNCE_SPEED: f32 = 1.0;
+
+/// Tuning for dropped items
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ItemConfig {
+    /// How far, in blocks, beyond a collector's box an item is picked up from
+    pub pickup_radius: f32,
+    /// Seconds after being dropped before an item can be picked up
+    pub pickup_delay: f32,
+    /// Items of the same type closer than this, in blocks, merge into one stack
+    pub merge_radius: f32,
+    /// Seconds before an untouched dropped item disappears
+    pub despawn_time: f32,
+    /// Fraction of its falling speed an item bounces back up with on landing
+    pub bounce: f32,
+    /// Fraction of horizontal speed kept each step while on the ground
+    pub ground_friction: f32,
+}
+
+impl Default for ItemConfig {
+    fn default() -> Self {
+        Self {
+            pickup_radius: 1.0,
+            pickup_delay: 0.5,
+            merge_radius: 0.5,
+            despawn_time: 300.0,
+            bounce: 0.25,
+            ground_friction: 0.5,
+        }
+    }
+}
+
+/// Kinds of entity, used for spawn caps and rendering
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityKind {
+    Player,
+    Item,
+    Pig,
+}
+
+/// Anything that lives in the world and moves independently of the block grid
+pub trait Entity {
+    fn kind(&self) -> EntityKind;
+
+    /// Position of the entity's feet (bottom center of its box)
+    fn position(&self) -> Vec3;
+
+    fn velocity(&self) -> Vec3;
+
+    /// Collision box at the current position
+    fn aabb(&self) -> Aabb;
+
+    /// Advance the entity by one step
+    fn update(&mut self, world: &World, delta_time: f32);
+
+    /// Deal damage to the entity
+    fn damage(&mut self, _amount: f32) {}
+
+    /// Entities this one wants added to the world (e.g. drops on death)
+    fn take_spawns(&mut self) -> Vec<Box<dyn Entity>> {
+        Vec::new()
+    }
+
+    /// Whether the entity should be removed from the world
+    fn is_removed(&self) -> bool {
+        false
+    }
+
+    /// The entity as a dropped item, for pickup and merging
+    fn as_item_mut(&mut self) -> Option<&mut ItemEntity> {
+        None
+    }
+}
+
+/// A stack of items lying in the world waiting to be picked up
+#[derive(Debug, Clone)]
+pub struct ItemEntity {
+    position: Vec3,
+    velocity: Vec3,
+    item_type: BlockType,
+    count: u32,
+    age: f32,
+    on_ground: bool,
+    /// Lay untouched for the configured despawn time
+    despawned: bool,
+}
+
+impl ItemEntity {
+    pub fn new(position: Vec3, item_type: BlockType, count: u32) -> Self {
+        Self {
+            position,
+            velocity: Vec3::ZERO,
+            item_type,
+            count,
+            age: 0.0,
+            on_ground: false,
+            despawned: false,
+        }
+    }
+
+    pub fn with_velocity(mut self, velocity: Vec3) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    pub fn item_type(&self) -> BlockType {
+        self.item_type
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn age(&self) -> f32 {
+        self.age
+    }
+
+    pub fn is_on_ground(&self) -> bool {
+        self.on_ground
+    }
+
+    /// Whether the item has been lying around long enough to be picked up
+    pub fn can_be_picked_up(&self, config: &ItemConfig) -> bool {
+        self.count > 0 && self.age >= config.pickup_delay
+    }
+
+    /// Take up to `count` items out of the stack, returning how many were taken
+    pub fn take(&mut self, count: u32) -> u32 {
+        let taken = count.min(self.count);
+        self.count -= taken;
+        taken
+    }
+
+    /// Move as many of `other`'s items into this stack as fit under `max_count`
+    pub fn absorb(&mut self, other: &mut ItemEntity, max_count: u32) {
+        if other.item_type != self.item_type {
+            return;
+        }
+        self.count += other.take(max_count.saturating_sub(self.count));
+        // Keep the younger age so neither stack's pickup delay or despawn time is cut short
+        self.age = self.age.min(other.age);
+    }
+}
+
+impl Entity for ItemEntity {
+    fn kind(&self) -> EntityKind {
+        EntityKind::Item
+    }
+
+    fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    fn velocity(&self) -> Vec3 {
+        self.velocity
+    }
+
+    fn aabb(&self) -> Aabb {
+        Aabb::from_feet(self.position, ITEM_SIZE, ITEM_SIZE)
+    }
+
+    fn update(&mut self, world: &World, delta_time: f32) {
+        let config = world.item_config();
+        self.age += delta_time;
+        self.despawned = self.age >= config.despawn_time;
+
+        let falling_speed = -self.velocity.y;
+        let (offset, on_ground) = world.physics().step_body(world, &self.aabb(), &mut self.velocity, delta_time);
+        self.position += offset;
+        self.on_ground = on_ground;
+
+        // Items bounce a little, then slide to a stop once they land
+        if on_ground {
+            if falling_speed > MIN_BOUNCE_SPEED {
+                self.velocity.y = falling_speed * config.bounce;
+            }
+            self.velocity.x *= config.ground_friction;
+            self.velocity.z *= config.ground_friction;
+        }
+    }
+
+    fn is_removed(&self) -> bool {
+        self.count == 0 || self.despawned
+    }
+
+    fn as_item_mut(&mut self) -> Option<&mut ItemEntity> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Player;
+    use crate::world::testing::flat_world;
+
+    const STEP: f32 = 1.0 / 60.0;
+
+    #[test]
+    fn falling_item_lands_and_stops() {
+        let world = flat_world(10);
+        let mut item = ItemEntity::new(Vec3::new(0.5, 20.0, 0.5), BlockType::Dirt, 1);
+
+        for _ in 0..300 {
+            item.update(&world, STEP);
+        }
+
+        assert!(item.is_on_ground());
+        assert!((item.position().y - 11.0).abs() < 1e-3);
+        assert_eq!(item.velocity(), Vec3::ZERO);
+    }
+
+    #[test]
+    fn items_and_the_player_land_on_the_same_ground() {
+        let world = flat_world(10);
+        let mut item = ItemEntity::new(Vec3::new(0.5, 20.0, 0.5), BlockType::Dirt, 1);
+        let mut player = Player::new(Vec3::ZERO);
+        player.teleport(Vec3::new(2.5, 20.0, 0.5));
+
+        for _ in 0..300 {
+            item.update(&world, STEP);
+            player.update(&world, STEP);
+        }
+
+        assert!(player.is_on_ground());
+        assert!((player.feet_position().y - item.position().y).abs() < 1e-3);
+    }
+}
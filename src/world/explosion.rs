@@ -0,0 +1,292 @@
+use std::collections::HashSet;
+use glam::{IVec3, Vec3};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::world::{BlockType, ItemEntity, World};
+
+/// Rays cast per cube edge when tracing an explosion (16 gives 1352 surface rays)
+const RAYS_PER_EDGE: i32 = 16;
+/// Distance each ray advances per step
+const RAY_STEP: f32 = 0.3;
+
+/// Seconds between igniting TNT and the explosion
+pub const TNT_FUSE_TIME: f32 = 4.0;
+/// Explosion power of a single TNT block
+pub const TNT_POWER: f32 = 4.0;
+
+/// A lit TNT block counting down to its explosion
+#[derive(Debug, Clone)]
+pub struct PrimedTnt {
+    pub position: Vec3,
+    pub fuse: f32,
+}
+
+impl PrimedTnt {
+    pub fn new(block: IVec3, fuse: f32) -> Self {
+        Self {
+            position: block.as_vec3() + Vec3::splat(0.5),
+            fuse,
+        }
+    }
+
+    /// Whether the blinking overlay is in its bright phase
+    pub fn is_flashing(&self) -> bool {
+        (self.fuse * 4.0) as i32 % 2 == 0
+    }
+}
+
+/// Outcome of an explosion, used to spawn drops and damage entities
+#[derive(Debug, Clone)]
+pub struct ExplosionResult {
+    pub center: Vec3,
+    pub power: f32,
+    /// Every block removed by the blast with the block it used to be
+    pub destroyed: Vec<(IVec3, BlockType)>,
+    /// Items dropped from a fraction of the destroyed blocks
+    pub drops: Vec<(BlockType, u32)>,
+}
+
+impl ExplosionResult {
+    /// Damage dealt to an entity at `position`, falling off with distance
+    pub fn damage_at(&self, position: Vec3) -> f32 {
+        let radius = self.power * 2.0;
+        let distance = position.distance(self.center);
+        if radius <= 0.0 || distance >= radius {
+            return 0.0;
+        }
+
+        let impact = 1.0 - distance / radius;
+        (impact * impact + impact) / 2.0 * 7.0 * radius + 1.0
+    }
+}
+
+impl World {
+    /// Light the TNT block at a position, returning false if there is no TNT there
+    pub fn ignite_tnt(&mut self, x: i32, y: i32, z: i32) -> bool {
+        self.prime_tnt(IVec3::new(x, y, z), TNT_FUSE_TIME)
+    }
+
+    fn prime_tnt(&mut self, pos: IVec3, fuse: f32) -> bool {
+        if self.block_at(pos) != Some(BlockType::Tnt) {
+            return false;
+        }
+
+        self.set_block(pos, BlockType::Air);
+        self.primed_tnt.push(PrimedTnt::new(pos, fuse));
+        self.play_sound("tnt_fuse");
+        true
+    }
+
+    /// Ignite TNT touching lava after either block changes at `pos`
+    pub(crate) fn check_lava_ignition(&mut self, pos: IVec3) {
+        let offsets = [IVec3::X, IVec3::NEG_X, IVec3::Y, IVec3::NEG_Y, IVec3::Z, IVec3::NEG_Z];
+        match self.block_at(pos) {
+            Some(BlockType::Tnt) => {
+                let touches_lava = offsets.iter().any(|&offset| {
+                    let n = pos + offset;
+                    self.block_at(n) == Some(BlockType::Lava)
+                });
+                if touches_lava {
+                    self.prime_tnt(pos, TNT_FUSE_TIME);
+                }
+            }
+            Some(BlockType::Lava) => {
+                for offset in offsets {
+                    self.prime_tnt(pos + offset, TNT_FUSE_TIME);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Count down lit TNT and detonate any whose fuse has run out
+    pub(crate) fn update_primed_tnt(&mut self, delta_time: f32) {
+        for tnt in &mut self.primed_tnt {
+            tnt.fuse -= delta_time;
+        }
+
+        let (exploding, remaining): (Vec<_>, Vec<_>) = std::mem::take(&mut self.primed_tnt)
+            .into_iter()
+            .partition(|tnt| tnt.fuse <= 0.0);
+        self.primed_tnt = remaining;
+
+        for tnt in exploding {
+            let result = self.explode(tnt.position, TNT_POWER);
+            self.explosions.push(result);
+        }
+    }
+
+    /// Detonate an explosion, destroying blocks whose resistance the blast overcomes
+    pub fn explode(&mut self, center: Vec3, power: f32) -> ExplosionResult {
+        let position_bits = ((center.x.to_bits() as u64) << 32)
+            ^ ((center.y.to_bits() as u64) << 16)
+            ^ center.z.to_bits() as u64;
+        let mut rng = StdRng::seed_from_u64(self.seed() ^ position_bits);
+        let mut affected = HashSet::new();
+
+        // Trace rays from the center towards every cell on the surface of a cube
+        let max = RAYS_PER_EDGE - 1;
+        for i in 0..RAYS_PER_EDGE {
+            for j in 0..RAYS_PER_EDGE {
+                for k in 0..RAYS_PER_EDGE {
+                    if i != 0 && i != max && j != 0 && j != max && k != 0 && k != max {
+                        continue;
+                    }
+
+                    let direction = Vec3::new(
+                        i as f32 / max as f32 * 2.0 - 1.0,
+                        j as f32 / max as f32 * 2.0 - 1.0,
+                        k as f32 / max as f32 * 2.0 - 1.0,
+                    ).normalize();
+
+                    let mut intensity = power * (0.7 + rng.gen::<f32>() * 0.6);
+                    let mut point = center;
+                    while intensity > 0.0 {
+                        let block_pos = point.floor().as_ivec3();
+                        if let Some(block) = self.block_at(block_pos) {
+                            if block != BlockType::Air {
+                                intensity -= (block.explosion_resistance() / 5.0 + 0.3) * RAY_STEP;
+                                if intensity > 0.0 {
+                                    affected.insert(block_pos);
+                                }
+                            }
+                        }
+
+                        intensity -= RAY_STEP * 0.75;
+                        point += direction * RAY_STEP;
+                    }
+                }
+            }
+        }
+
+        // Remove the blocks, dropping roughly one in `power` of them
+        let drop_chance = (1.0 / power.max(1.0)) as f64;
+        let mut destroyed = Vec::new();
+        let mut drops = Vec::new();
+        for pos in affected {
+            if let Some(block) = self.block_at(pos) {
+                if block == BlockType::Air {
+                    continue;
+                }
+
+                // Caught TNT is lit with a short random fuse for chain reactions
+                if block == BlockType::Tnt {
+                    self.prime_tnt(pos, rng.gen_range(0.5..1.5));
+                    continue;
+                }
+
+                self.set_block(pos, BlockType::Air);
+                destroyed.push((pos, block));
+
+                if rng.gen::<f64>() < drop_chance {
+                    for (item_type, count) in block.drops() {
+                        let position = pos.as_vec3() + Vec3::new(0.5, 0.0, 0.5);
+                        self.spawn_entity(Box::new(ItemEntity::new(position, item_type, count)));
+                        drops.push((item_type, count));
+                    }
+                }
+            }
+        }
+
+        self.play_sound("explode");
+
+        let result = ExplosionResult {
+            center,
+            power,
+            destroyed,
+            drops,
+        };
+
+        // Damage entities caught in the blast
+        for entity in &mut self.entities {
+            let damage = result.damage_at(entity.position());
+            if damage > 0.0 {
+                entity.damage(damage);
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::testing::flat_world;
+
+    #[test]
+    fn weak_blast_clears_dirt_but_not_obsidian() {
+        let mut world = flat_world(10);
+        for x in -2..=2 {
+            world.set_block(IVec3::new(x, 11, 0), BlockType::Dirt);
+        }
+        world.set_block(IVec3::new(0, 11, 1), BlockType::Obsidian);
+
+        let result = world.explode(Vec3::new(0.5, 11.5, 0.5), 2.0);
+
+        assert_eq!(world.block_at(IVec3::new(0, 11, 0)), Some(BlockType::Air));
+        assert_eq!(world.block_at(IVec3::new(1, 11, 0)), Some(BlockType::Air));
+        assert_eq!(world.block_at(IVec3::new(0, 11, 1)), Some(BlockType::Obsidian));
+        assert!(result.destroyed.iter().all(|&(_, block)| block != BlockType::Obsidian));
+    }
+
+    #[test]
+    fn drops_come_from_destroyed_blocks() {
+        let mut world = flat_world(10);
+        for x in -3..=3 {
+            for z in -3..=3 {
+                world.set_block(IVec3::new(x, 11, z), BlockType::Dirt);
+            }
+        }
+
+        let result = world.explode(Vec3::new(0.5, 11.5, 0.5), 3.0);
+
+        assert!(!result.destroyed.is_empty());
+        assert!(result.drops.len() <= result.destroyed.len());
+        for (item, _) in &result.drops {
+            assert!(result
+                .destroyed
+                .iter()
+                .any(|(_, block)| block.drops().iter().any(|(drop, _)| drop == item)));
+        }
+    }
+
+    #[test]
+    fn ignited_tnt_explodes_when_the_fuse_runs_out() {
+        let mut world = flat_world(10);
+        world.set_block(IVec3::new(0, 11, 0), BlockType::Tnt);
+
+        assert!(world.ignite_tnt(0, 11, 0));
+        assert_eq!(world.block_at(IVec3::new(0, 11, 0)), Some(BlockType::Air));
+        assert_eq!(world.primed_tnt().len(), 1);
+
+        world.update_primed_tnt(TNT_FUSE_TIME - 0.1);
+        assert!(world.take_explosions().is_empty());
+
+        world.update_primed_tnt(0.1);
+        let explosions = world.take_explosions();
+        assert_eq!(explosions.len(), 1);
+        assert_eq!(explosions[0].center, Vec3::new(0.5, 11.5, 0.5));
+        assert!(world.primed_tnt().is_empty());
+    }
+
+    #[test]
+    fn explosion_ignites_nearby_tnt() {
+        let mut world = flat_world(10);
+        world.set_block(IVec3::new(2, 11, 0), BlockType::Tnt);
+
+        world.explode(Vec3::new(0.5, 11.5, 0.5), TNT_POWER);
+
+        assert_eq!(world.block_at(IVec3::new(2, 11, 0)), Some(BlockType::Air));
+        assert_eq!(world.primed_tnt().len(), 1);
+        assert!(world.primed_tnt()[0].fuse < TNT_FUSE_TIME);
+    }
+
+    #[test]
+    fn only_tnt_can_be_ignited() {
+        let mut world = flat_world(10);
+        assert!(!world.ignite_tnt(0, 10, 0));
+        assert!(world.primed_tnt().is_empty());
+    }
+}
@@ -1,12 +1,145 @@
+use anyhow::{bail, Result};
 use noise::{NoiseFn, OpenSimplex, Seedable};
-use rand::{Rng, SeedableRng};
+use rand::Rng;
 use rand::rngs::StdRng;
 
-use crate::world::{Chunk, ChunkCoordinate, BlockType, CHUNK_SIZE, CHUNK_HEIGHT};
+use crate::utils::rng::{mix, position_rng};
+use crate::world::{Chunk, ChunkCoordinate, BlockType, FlowerKind, GrassKind, CHUNK_SIZE, CHUNK_HEIGHT};
+
+/// Fractal Brownian motion: sums `octaves` layers of noise, each at `lacunarity` times
+/// the previous frequency and `persistence` times the previous amplitude.
+///
+/// The first octave is the raw noise value, so the result stays within the sum of the
+/// octave amplitudes.
+pub fn fbm<N, const DIM: usize>(
+    noise: &N,
+    point: [f64; DIM],
+    octaves: u32,
+    lacunarity: f64,
+    persistence: f64,
+) -> f64
+where
+    N: NoiseFn<f64, DIM>,
+{
+    let mut total = 0.0;
+    let mut frequency = 1.0;
+    let mut amplitude = 1.0;
+
+    for _ in 0..octaves {
+        total += noise.get(point.map(|p| p * frequency)) * amplitude;
+        frequency *= lacunarity;
+        amplitude *= persistence;
+    }
+
+    total
+}
+
+/// Tunable noise parameters for world generation
+#[derive(Debug, Clone)]
+pub struct GenerationConfig {
+    pub terrain_octaves: u32,
+    pub cave_octaves: u32,
+    pub biome_octaves: u32,
+    /// Frequency multiplier between octaves
+    pub lacunarity: f64,
+    /// Amplitude multiplier between octaves
+    pub persistence: f64,
+    /// Water fills terrain up to and including this height
+    pub sea_level: usize,
+    /// Lowest and highest terrain surface the height noise can produce
+    pub min_height: usize,
+    pub max_height: usize,
+    /// Caves are carved between these heights
+    pub cave_min_y: usize,
+    pub cave_max_y: usize,
+    /// Cave noise threshold at `cave_min_y` and at `cave_max_y`, interpolated in
+    /// between. Lower thresholds carve more and wider caves.
+    pub cave_threshold_deep: f64,
+    pub cave_threshold_shallow: f64,
+    /// Chance, 0-1, that an ore vein which would start buried is moved onto a cave
+    /// wall instead, if one of a few re-rolled spots is exposed
+    pub ore_exposure_bias: f64,
+}
+
+/// Extra spots tried when moving an ore vein onto a cave wall
+const ORE_EXPOSURE_ATTEMPTS: usize = 4;
+
+impl GenerationConfig {
+    /// Check the heights fit inside a chunk, with at least one block of air above
+    /// the tallest terrain
+    pub fn validate(&self) -> Result<()> {
+        if self.min_height > self.max_height {
+            bail!("min_height {} is above max_height {}", self.min_height, self.max_height);
+        }
+        if self.max_height >= CHUNK_HEIGHT - 1 {
+            bail!("max_height {} must be below {}", self.max_height, CHUNK_HEIGHT - 1);
+        }
+        if self.sea_level >= CHUNK_HEIGHT {
+            bail!("sea_level {} must be below the chunk height {}", self.sea_level, CHUNK_HEIGHT);
+        }
+        if self.cave_min_y >= self.cave_max_y || self.cave_max_y > CHUNK_HEIGHT {
+            bail!("cave range {}..{} must be non-empty and within the chunk height", self.cave_min_y, self.cave_max_y);
+        }
+        if !(0.0..=1.0).contains(&self.ore_exposure_bias) {
+            bail!("ore_exposure_bias {} must be between 0 and 1", self.ore_exposure_bias);
+        }
+        Ok(())
+    }
+
+    /// Cave noise threshold at height `y`: denser, wider caves deep down, thinning
+    /// out towards the surface
+    pub fn cave_threshold(&self, y: usize) -> f64 {
+        let span = (self.cave_max_y - self.cave_min_y) as f64;
+        let t = (y.saturating_sub(self.cave_min_y) as f64 / span).min(1.0);
+        self.cave_threshold_deep + (self.cave_threshold_shallow - self.cave_threshold_deep) * t
+    }
+}
+
+impl Default for GenerationConfig {
+    fn default() -> Self {
+        Self {
+            terrain_octaves: 3,
+            cave_octaves: 1,
+            biome_octaves: 1,
+            lacunarity: 2.0,
+            persistence: 0.5,
+            sea_level: 64,
+            min_height: 30,
+            max_height: 120,
+            cave_min_y: 5,
+            cave_max_y: 80,
+            cave_threshold_deep: 0.3,
+            cave_threshold_shallow: 0.5,
+            ore_exposure_bias: 0.5,
+        }
+    }
+}
+
+/// Produces the initial contents of a chunk. Generation must be deterministic for a
+/// given coordinate, since chunks that were never edited are regenerated instead of saved.
+pub trait ChunkGenerator: Send + Sync {
+    fn generate_chunk(&self, coord: ChunkCoordinate) -> Chunk;
+
+    /// Biome of a world column, for generators that have biomes
+    fn biome_at(&self, _x: i32, _z: i32) -> Option<Biome> {
+        None
+    }
+}
+
+/// Combined `Chunk::content_hash` of generating each of `coords` in order. With a
+/// fixed seed and chunk list this is a snapshot of generator output: compare it to
+/// a recorded value to catch refactors that accidentally change generated worlds,
+/// and record the new value when a change is intended.
+pub fn generation_fingerprint(generator: &dyn ChunkGenerator, coords: &[ChunkCoordinate]) -> u64 {
+    coords
+        .iter()
+        .fold(0, |hash, &coord| mix(hash ^ generator.generate_chunk(coord).content_hash()))
+}
 
 /// World generator that creates Minecraft-like terrain using multiple noise layers
 pub struct WorldGenerator {
     seed: u64,
+    config: GenerationConfig,
     
     // Terrain noise generators
     terrain_noise: OpenSimplex,
@@ -16,24 +149,31 @@ pub struct WorldGenerator {
     biome_humidity: OpenSimplex,
     
     // Generation parameters
-    sea_level: usize,
-    max_height: usize,
-    min_height: usize,
+    beach_band: usize,
 }
 
 impl WorldGenerator {
     pub fn new(seed: u64) -> Self {
-        Self {
+        Self::with_config(seed, GenerationConfig::default()).expect("default generation config is valid")
+    }
+
+    /// Create a generator, failing if the config's heights don't fit in a chunk
+    pub fn with_config(seed: u64, config: GenerationConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
             seed,
+            config,
             terrain_noise: OpenSimplex::new(seed as u32),
             cave_noise: OpenSimplex::new(seed.wrapping_add(1) as u32),
             ore_noise: OpenSimplex::new(seed.wrapping_add(2) as u32),
             biome_temperature: OpenSimplex::new(seed.wrapping_add(3) as u32),
             biome_humidity: OpenSimplex::new(seed.wrapping_add(4) as u32),
-            sea_level: 64,
-            max_height: 120,
-            min_height: 30,
-        }
+            beach_band: 2,
+        })
+    }
+
+    pub fn config(&self) -> &GenerationConfig {
+        &self.config
     }
 
     /// Generate a complete chunk with terrain, caves, ores, and structures
@@ -84,11 +224,13 @@ impl WorldGenerator {
         let scale = 0.01; // Noise scale
         
         // Base terrain with multiple octaves
-        let noise1 = self.terrain_noise.get([x * scale, z * scale]) * 0.5;
-        let noise2 = self.terrain_noise.get([x * scale * 2.0, z * scale * 2.0]) * 0.25;
-        let noise3 = self.terrain_noise.get([x * scale * 4.0, z * scale * 4.0]) * 0.125;
-        
-        let combined_noise = noise1 + noise2 + noise3;
+        let combined_noise = 0.5 * fbm(
+            &self.terrain_noise,
+            [x * scale, z * scale],
+            self.config.terrain_octaves,
+            self.config.lacunarity,
+            self.config.persistence,
+        );
         
         // Apply biome-specific height modifiers
         let height_modifier = match biome {
@@ -99,17 +241,22 @@ impl WorldGenerator {
             Biome::Forest => 1.0,
             Biome::Swamp => 0.6,
             Biome::Ocean => 0.3,
+            Biome::Tundra => 0.9,
         };
         
-        let height_range = (self.max_height - self.min_height) as f64;
+        let height_range = (self.config.max_height - self.config.min_height) as f64;
         let normalized_height = (combined_noise + 1.0) * 0.5; // Normalize to 0-1
-        let final_height = self.min_height as f64 + normalized_height * height_range * height_modifier;
+        let final_height = self.config.min_height as f64 + normalized_height * height_range * height_modifier;
         
-        final_height.max(self.min_height as f64).min(self.max_height as f64) as usize
+        final_height.max(self.config.min_height as f64).min(self.config.max_height as f64) as usize
     }
 
     /// Fill a terrain column with appropriate blocks
     fn fill_terrain_column(&self, chunk: &mut Chunk, x: usize, z: usize, height: usize, biome: &Biome) {
+        // Columns close to sea level form sandy shorelines
+        let is_beach = height + self.beach_band >= self.config.sea_level
+            && height <= self.config.sea_level + self.beach_band;
+
         for y in 0..CHUNK_HEIGHT {
             let block = if y == 0 {
                 BlockType::Stone // Bedrock equivalent
@@ -118,6 +265,7 @@ impl WorldGenerator {
                     // Surface block
                     match biome {
                         Biome::Desert => BlockType::Sand,
+                        _ if is_beach => BlockType::Sand,
                         Biome::Ocean | Biome::Swamp => BlockType::Dirt,
                         _ => BlockType::Grass,
                     }
@@ -125,13 +273,17 @@ impl WorldGenerator {
                     // Subsurface (dirt layer)
                     match biome {
                         Biome::Desert => BlockType::Sand,
+                        _ if is_beach => BlockType::Sand,
                         _ => BlockType::Dirt,
                     }
                 } else {
                     // Deep underground
                     BlockType::Stone
                 }
-            } else if y <= self.sea_level {
+            } else if y == self.config.sea_level && matches!(biome, Biome::Tundra) {
+                // Frozen water surface in cold biomes
+                BlockType::Ice
+            } else if y <= self.config.sea_level {
                 // Water below sea level
                 BlockType::Water
             } else {
@@ -146,22 +298,11 @@ impl WorldGenerator {
     /// Generate cave systems using 3D noise
     fn generate_caves(&self, chunk: &mut Chunk) {
         let (world_x, world_z) = chunk.coordinate.world_position();
-        let cave_scale = 0.02;
-        let cave_threshold = 0.4;
 
         for local_x in 0..CHUNK_SIZE {
             for local_z in 0..CHUNK_SIZE {
-                for y in 5..80 { // Caves only in certain Y range
-                    let world_pos_x = (world_x + local_x as i32) as f64;
-                    let world_pos_z = (world_z + local_z as i32) as f64;
-                    
-                    let cave_noise = self.cave_noise.get([
-                        world_pos_x * cave_scale,
-                        y as f64 * cave_scale * 2.0, // Stretch vertically
-                        world_pos_z * cave_scale
-                    ]);
-
-                    if cave_noise > cave_threshold {
+                for y in self.config.cave_min_y..self.config.cave_max_y {
+                    if self.is_cave(world_x + local_x as i32, y, world_z + local_z as i32) {
                         let current_block = chunk.get_block(local_x, y, local_z);
                         if current_block == BlockType::Stone || current_block == BlockType::Dirt {
                             chunk.set_block(local_x, y, local_z, BlockType::Air);
@@ -172,12 +313,29 @@ impl WorldGenerator {
         }
     }
 
+    /// Whether the cave noise carves the block at this world position, ignoring
+    /// what's there and the configured cave height range
+    fn is_cave(&self, x: i32, y: usize, z: i32) -> bool {
+        let cave_scale = 0.02;
+        let cave_noise = fbm(
+            &self.cave_noise,
+            [
+                x as f64 * cave_scale,
+                y as f64 * cave_scale * 2.0, // Stretch vertically
+                z as f64 * cave_scale,
+            ],
+            self.config.cave_octaves,
+            self.config.lacunarity,
+            self.config.persistence,
+        );
+        cave_noise > self.config.cave_threshold(y)
+    }
+
     /// Generate ore deposits
     fn generate_ores(&self, chunk: &mut Chunk) {
         let (world_x, world_z) = chunk.coordinate.world_position();
-        let mut rng = StdRng::seed_from_u64(
-            self.seed.wrapping_add((world_x as u64) << 32).wrapping_add(world_z as u64)
-        );
+        // y = -1 keeps this apart from the corner column's surface-feature RNG
+        let mut rng = position_rng(self.seed, world_x, -1, world_z);
 
         // Coal ore (common, high levels)
         self.generate_ore_type(chunk, &mut rng, BlockType::CoalOre, 10..70, 0.02, 8);
@@ -208,9 +366,23 @@ impl WorldGenerator {
 
         for _ in 0..(CHUNK_SIZE * CHUNK_SIZE / 64) { // Attempt frequency
             if rng.gen::<f64>() < frequency {
-                let local_x = rng.gen_range(0..CHUNK_SIZE);
-                let local_z = rng.gen_range(0..CHUNK_SIZE);
-                let y = rng.gen_range(y_range.clone());
+                let mut local_x = rng.gen_range(0..CHUNK_SIZE);
+                let mut local_z = rng.gen_range(0..CHUNK_SIZE);
+                let mut y = rng.gen_range(y_range.clone());
+
+                // Prefer cave walls, so ore is found while exploring and not only
+                // by digging blind
+                if !Self::is_exposed(chunk, local_x, y, local_z) && rng.gen::<f64>() < self.config.ore_exposure_bias {
+                    for _ in 0..ORE_EXPOSURE_ATTEMPTS {
+                        let x = rng.gen_range(0..CHUNK_SIZE);
+                        let z = rng.gen_range(0..CHUNK_SIZE);
+                        let candidate_y = rng.gen_range(y_range.clone());
+                        if Self::is_exposed(chunk, x, candidate_y, z) {
+                            (local_x, y, local_z) = (x, candidate_y, z);
+                            break;
+                        }
+                    }
+                }
 
                 // Generate ore vein
                 self.place_ore_vein(chunk, local_x, y, local_z, ore_type, vein_size, rng);
@@ -218,6 +390,19 @@ impl WorldGenerator {
         }
     }
 
+    /// Whether the stone at a position borders air inside the chunk
+    fn is_exposed(chunk: &Chunk, x: usize, y: usize, z: usize) -> bool {
+        if chunk.get_block(x, y, z) != BlockType::Stone {
+            return false;
+        }
+        [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)].iter().any(|&(dx, dy, dz)| {
+            let (nx, ny, nz) = (x as i32 + dx, y as i32 + dy, z as i32 + dz);
+            nx >= 0 && ny >= 0 && nz >= 0
+                && (nx as usize) < CHUNK_SIZE && (ny as usize) < CHUNK_HEIGHT && (nz as usize) < CHUNK_SIZE
+                && chunk.get_block(nx as usize, ny as usize, nz as usize) == BlockType::Air
+        })
+    }
+
     fn place_ore_vein(
         &self,
         chunk: &mut Chunk,
@@ -257,49 +442,122 @@ impl WorldGenerator {
         }
     }
 
-    /// Generate surface features like trees and grass
+    /// Generate surface features like trees and grass.
+    ///
+    /// Every column decides from the untouched terrain and its own position-seeded RNG
+    /// before anything is placed, so the result doesn't depend on traversal order.
     fn generate_surface_features(&self, chunk: &mut Chunk) {
+        let columns = (0..CHUNK_SIZE).flat_map(|x| (0..CHUNK_SIZE).map(move |z| (x, z)));
+        self.place_surface_features(chunk, columns);
+    }
+
+    /// Decide and place the features for the given columns. Every column decides from
+    /// its own position-seeded RNG and features are placed trees first, then by
+    /// position, so the order columns are visited in doesn't change the chunk.
+    fn place_surface_features(&self, chunk: &mut Chunk, columns: impl IntoIterator<Item = (usize, usize)>) {
         let (world_x, world_z) = chunk.coordinate.world_position();
-        let mut rng = StdRng::seed_from_u64(
-            self.seed.wrapping_add((world_x as u64) << 32).wrapping_add(world_z as u64)
-        );
 
-        for local_x in 0..CHUNK_SIZE {
-            for local_z in 0..CHUNK_SIZE {
-                let world_pos_x = world_x + local_x as i32;
-                let world_pos_z = world_z + local_z as i32;
-                let biome = self.get_biome(world_pos_x as f64, world_pos_z as f64);
+        let mut features = Vec::new();
+        for (local_x, local_z) in columns {
+            let world_pos_x = world_x + local_x as i32;
+            let world_pos_z = world_z + local_z as i32;
+            let biome = self.get_biome(world_pos_x as f64, world_pos_z as f64);
+            let mut rng = self.column_rng(world_pos_x, world_pos_z);
 
-                let surface_y = self.find_surface_level(chunk, local_x, local_z);
-                
-                if let Some(y) = surface_y {
-                    if y < CHUNK_HEIGHT - 1 {
-                        match biome {
-                            Biome::Forest => {
-                                if rng.gen::<f64>() < 0.1 {
-                                    self.place_tree(chunk, local_x, y + 1, local_z, &mut rng);
-                                } else if rng.gen::<f64>() < 0.3 {
-                                    chunk.set_block(local_x, y + 1, local_z, BlockType::TallGrass);
-                                }
-                            },
-                            Biome::Plains => {
-                                if rng.gen::<f64>() < 0.2 {
-                                    chunk.set_block(local_x, y + 1, local_z, BlockType::TallGrass);
-                                }
-                            },
-                            Biome::Desert => {
-                                if rng.gen::<f64>() < 0.02 {
-                                    chunk.set_block(local_x, y + 1, local_z, BlockType::DeadBush);
-                                }
-                            },
-                            _ => {}
-                        }
+            if let Some((y, feature)) = self.choose_surface_feature(chunk, local_x, local_z, biome, &mut rng) {
+                features.push((local_x, y, local_z, feature, rng));
+            }
+        }
+        features.sort_by_key(|&(x, _, z, feature, _)| (feature.is_single_block(), x, z));
+
+        for (x, y, z, feature, mut rng) in features {
+            match feature {
+                SurfaceFeature::Tree => self.place_tree(chunk, x, y, z, &mut rng),
+                SurfaceFeature::SpruceTree => self.place_spruce_tree(chunk, x, y, z, &mut rng),
+                SurfaceFeature::Block(block) => {
+                    place_feature_block(chunk, x, y, z, block);
+                }
+                SurfaceFeature::Plant(block, state) => {
+                    if place_feature_block(chunk, x, y, z, block) {
+                        chunk.set_block_state(x, y, z, state);
                     }
                 }
             }
         }
     }
 
+    /// RNG seeded from the world seed and a column position
+    fn column_rng(&self, world_x: i32, world_z: i32) -> StdRng {
+        position_rng(self.seed, world_x, 0, world_z)
+    }
+
+    /// Pick the feature for a column and the height it sits at, if any
+    fn choose_surface_feature(
+        &self,
+        chunk: &Chunk,
+        local_x: usize,
+        local_z: usize,
+        biome: Biome,
+        rng: &mut StdRng,
+    ) -> Option<(usize, SurfaceFeature)> {
+        let y = self.find_surface_level(chunk, local_x, local_z)?;
+        if y >= CHUNK_HEIGHT - 1 {
+            return None;
+        }
+
+        let feature = match biome {
+            Biome::Forest => {
+                if rng.gen::<f64>() < 0.1 {
+                    SurfaceFeature::Tree
+                } else if rng.gen::<f64>() < 0.03 {
+                    Self::flower(&[FlowerKind::Poppy, FlowerKind::Dandelion, FlowerKind::Allium], rng)
+                } else if rng.gen::<f64>() < 0.3 {
+                    let kind = if rng.gen::<f64>() < 0.3 { GrassKind::Fern } else { GrassKind::Grass };
+                    SurfaceFeature::Plant(BlockType::TallGrass, kind.state())
+                } else {
+                    return None;
+                }
+            },
+            Biome::Plains => {
+                if rng.gen::<f64>() < 0.05 {
+                    Self::flower(&[
+                        FlowerKind::Dandelion,
+                        FlowerKind::Poppy,
+                        FlowerKind::Cornflower,
+                        FlowerKind::OxeyeDaisy,
+                    ], rng)
+                } else if rng.gen::<f64>() < 0.2 {
+                    SurfaceFeature::Plant(BlockType::TallGrass, GrassKind::Grass.state())
+                } else {
+                    return None;
+                }
+            },
+            Biome::Desert if rng.gen::<f64>() < 0.02 => SurfaceFeature::Block(BlockType::DeadBush),
+            Biome::Tundra => {
+                // Snow only settles on solid ground, not on frozen lakes or plants
+                let surface = chunk.get_block(local_x, y, local_z);
+                if surface == BlockType::Ice || !surface.is_solid() {
+                    return None;
+                }
+                let on_grass = surface == BlockType::Grass;
+                if on_grass && rng.gen::<f64>() < 0.02 {
+                    SurfaceFeature::SpruceTree
+                } else {
+                    SurfaceFeature::Block(BlockType::Snow)
+                }
+            },
+            _ => return None,
+        };
+
+        Some((y + 1, feature))
+    }
+
+    /// A flower picked at random from a biome's palette
+    fn flower(palette: &[FlowerKind], rng: &mut StdRng) -> SurfaceFeature {
+        let kind = palette[rng.gen_range(0..palette.len())];
+        SurfaceFeature::Plant(BlockType::Flower, kind.state())
+    }
+
     fn find_surface_level(&self, chunk: &Chunk, x: usize, z: usize) -> Option<usize> {
         for y in (0..CHUNK_HEIGHT).rev() {
             let block = chunk.get_block(x, y, z);
@@ -316,7 +574,7 @@ impl WorldGenerator {
         // Place trunk
         for h in 0..tree_height {
             if y + h < CHUNK_HEIGHT {
-                chunk.set_block(x, y + h, z, BlockType::Log);
+                place_feature_block(chunk, x, y + h, z, BlockType::Log);
             }
         }
         
@@ -337,10 +595,7 @@ impl WorldGenerator {
                         
                         let distance = (dx * dx + dz * dz) as f32;
                         if distance <= (radius * radius) as f32 && rng.gen::<f64>() < 0.8 {
-                            let current = chunk.get_block(leaf_x as usize, leaf_y, leaf_z as usize);
-                            if current == BlockType::Air {
-                                chunk.set_block(leaf_x as usize, leaf_y, leaf_z as usize, BlockType::Leaves);
-                            }
+                            place_feature_block(chunk, leaf_x as usize, leaf_y, leaf_z as usize, BlockType::Leaves);
                         }
                     }
                 }
@@ -348,26 +603,108 @@ impl WorldGenerator {
         }
     }
 
+    /// Spruce-style tree: a tall trunk with narrow, cone-shaped leaf layers
+    fn place_spruce_tree(&self, chunk: &mut Chunk, x: usize, y: usize, z: usize, rng: &mut StdRng) {
+        let tree_height = rng.gen_range(6..10);
+
+        // Place trunk
+        for h in 0..tree_height {
+            if y + h < CHUNK_HEIGHT {
+                place_feature_block(chunk, x, y + h, z, BlockType::Log);
+            }
+        }
+
+        // Place leaves, widening every other layer towards the base
+        let leaf_start = y + 2;
+        for leaf_y in leaf_start..(y + tree_height + 1) {
+            if leaf_y >= CHUNK_HEIGHT { break; }
+
+            let from_top = y + tree_height - leaf_y;
+            let radius: i32 = if from_top == 0 { 0 } else if from_top % 2 == 1 { 1 } else { 2 };
+
+            for dx in -radius..=radius {
+                for dz in -radius..=radius {
+                    let leaf_x = x as i32 + dx;
+                    let leaf_z = z as i32 + dz;
+
+                    if leaf_x >= 0 && leaf_x < CHUNK_SIZE as i32 &&
+                       leaf_z >= 0 && leaf_z < CHUNK_SIZE as i32 &&
+                       dx.abs() + dz.abs() <= radius + 1 {
+                        place_feature_block(chunk, leaf_x as usize, leaf_y, leaf_z as usize, BlockType::Leaves);
+                    }
+                }
+            }
+        }
+
+        // Cap the tip with a leaf block
+        if y + tree_height < CHUNK_HEIGHT {
+            place_feature_block(chunk, x, y + tree_height, z, BlockType::Leaves);
+        }
+    }
+
     /// Determine biome based on temperature and humidity noise
     fn get_biome(&self, x: f64, z: f64) -> Biome {
         let biome_scale = 0.005;
-        let temperature = self.biome_temperature.get([x * biome_scale, z * biome_scale]);
-        let humidity = self.biome_humidity.get([x * biome_scale * 1.3, z * biome_scale * 1.7]);
+        let octaves = self.config.biome_octaves;
+        let (lacunarity, persistence) = (self.config.lacunarity, self.config.persistence);
+        let temperature = fbm(
+            &self.biome_temperature,
+            [x * biome_scale, z * biome_scale],
+            octaves,
+            lacunarity,
+            persistence,
+        );
+        let humidity = fbm(
+            &self.biome_humidity,
+            [x * biome_scale * 1.3, z * biome_scale * 1.7],
+            octaves,
+            lacunarity,
+            persistence,
+        );
 
-        match (temperature, humidity) {
-            (t, _) if t < -0.5 => Biome::Mountains,
-            (t, h) if t > 0.5 && h < -0.3 => Biome::Desert,
-            (t, h) if t < 0.2 && h > 0.3 => Biome::Swamp,
-            (_, h) if h < -0.6 => Biome::Ocean,
-            (t, h) if t > 0.0 && h > 0.0 => Biome::Forest,
-            (t, _) if t > 0.2 => Biome::Hills,
-            _ => Biome::Plains,
-        }
+        Biome::from_climate(temperature, humidity)
     }
 }
 
-/// Different biome types that affect terrain generation
+impl ChunkGenerator for WorldGenerator {
+    fn generate_chunk(&self, coord: ChunkCoordinate) -> Chunk {
+        WorldGenerator::generate_chunk(self, coord)
+    }
+
+    fn biome_at(&self, x: i32, z: i32) -> Option<Biome> {
+        Some(self.get_biome(x as f64, z as f64))
+    }
+}
+
+/// Something placed on top of a terrain column
 #[derive(Debug, Clone, Copy)]
+enum SurfaceFeature {
+    Tree,
+    SpruceTree,
+    Block(BlockType),
+    /// A block with a specific block state, such as a flower variety
+    Plant(BlockType, u8),
+}
+
+impl SurfaceFeature {
+    /// Single blocks are placed after trees, so they never claim a cell a canopy needs
+    fn is_single_block(&self) -> bool {
+        matches!(self, SurfaceFeature::Block(_) | SurfaceFeature::Plant(..))
+    }
+}
+
+/// Surface features only ever fill air, so whatever is already in a cell, terrain or
+/// an earlier feature, keeps it. Returns whether the block was placed.
+fn place_feature_block(chunk: &mut Chunk, x: usize, y: usize, z: usize, block: BlockType) -> bool {
+    if chunk.get_block(x, y, z) != BlockType::Air {
+        return false;
+    }
+    chunk.set_block(x, y, z, block);
+    true
+}
+
+/// Different biome types that affect terrain generation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Biome {
     Plains,
     Forest,
@@ -376,9 +713,37 @@ pub enum Biome {
     Hills,
     Swamp,
     Ocean,
+    Tundra,
 }
 
 impl Biome {
+    pub fn all() -> [Biome; 8] {
+        [
+            Biome::Plains,
+            Biome::Forest,
+            Biome::Desert,
+            Biome::Mountains,
+            Biome::Hills,
+            Biome::Swamp,
+            Biome::Ocean,
+            Biome::Tundra,
+        ]
+    }
+
+    /// Select a biome from temperature and humidity noise values
+    pub fn from_climate(temperature: f64, humidity: f64) -> Biome {
+        match (temperature, humidity) {
+            (t, _) if t < -0.7 => Biome::Tundra,
+            (t, _) if t < -0.5 => Biome::Mountains,
+            (t, h) if t > 0.5 && h < -0.3 => Biome::Desert,
+            (t, h) if t < 0.2 && h > 0.3 => Biome::Swamp,
+            (_, h) if h < -0.6 => Biome::Ocean,
+            (t, h) if t > 0.0 && h > 0.0 => Biome::Forest,
+            (t, _) if t > 0.2 => Biome::Hills,
+            _ => Biome::Plains,
+        }
+    }
+
     pub fn name(&self) -> &'static str {
         match self {
             Biome::Plains => "Plains",
@@ -388,6 +753,225 @@ impl Biome {
             Biome::Hills => "Hills",
             Biome::Swamp => "Swamp",
             Biome::Ocean => "Ocean",
+            Biome::Tundra => "Tundra",
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED: u64 = 12345;
+
+    #[test]
+    fn fbm_with_one_octave_is_the_raw_noise() {
+        let noise = OpenSimplex::new(SEED as u32);
+        for i in 0..50 {
+            let point = [i as f64 * 0.37, i as f64 * -0.21];
+            assert_eq!(fbm(&noise, point, 1, 2.0, 0.5), noise.get(point));
+        }
+    }
+
+    #[test]
+    fn fbm_stays_within_the_sum_of_octave_amplitudes() {
+        let noise = OpenSimplex::new(SEED as u32);
+        let (octaves, persistence) = (5, 0.5_f64);
+        let bound: f64 = (0..octaves).map(|octave| persistence.powi(octave)).sum();
+
+        for x in -20..20 {
+            for z in -20..20 {
+                let point = [x as f64 * 0.13, z as f64 * 0.17];
+                let value = fbm(&noise, point, octaves as u32, 2.0, persistence);
+                assert!(value.abs() <= bound, "fbm({point:?}) = {value} exceeds {bound}");
+            }
+        }
+    }
+
+    #[test]
+    fn very_cold_climate_is_tundra() {
+        assert_eq!(Biome::from_climate(-0.9, 0.0), Biome::Tundra);
+        assert_eq!(Biome::from_climate(-0.9, 0.8), Biome::Tundra);
+        assert_ne!(Biome::from_climate(-0.6, 0.0), Biome::Tundra);
+    }
+
+    #[test]
+    fn tundra_column_is_topped_with_snow() {
+        let generator = WorldGenerator::new(SEED);
+        let height = 80;
+
+        for x in 0..CHUNK_SIZE {
+            let mut chunk = Chunk::new(ChunkCoordinate::new(0, 0));
+            generator.fill_terrain_column(&mut chunk, x, 0, height, &Biome::Tundra);
+            let mut rng = generator.column_rng(x as i32, 0);
+
+            let (y, feature) = generator
+                .choose_surface_feature(&chunk, x, 0, Biome::Tundra, &mut rng)
+                .expect("tundra grass gets a surface feature");
+            assert_eq!(y, height + 1);
+            assert!(matches!(feature, SurfaceFeature::Block(BlockType::Snow) | SurfaceFeature::SpruceTree));
+        }
+    }
+
+    #[test]
+    fn frozen_lake_gets_no_snow() {
+        let generator = WorldGenerator::new(SEED);
+        let sea_level = generator.config().sea_level;
+        let mut chunk = Chunk::new(ChunkCoordinate::new(0, 0));
+        generator.fill_terrain_column(&mut chunk, 0, 0, sea_level - 10, &Biome::Tundra);
+        assert_eq!(chunk.get_block(0, sea_level, 0), BlockType::Ice);
+
+        let mut rng = generator.column_rng(0, 0);
+        assert!(generator.choose_surface_feature(&chunk, 0, 0, Biome::Tundra, &mut rng).is_none());
+    }
+
+    #[test]
+    fn shoreline_columns_are_sand() {
+        let generator = WorldGenerator::new(SEED);
+        let sea_level = generator.config().sea_level;
+        let mut chunk = Chunk::new(ChunkCoordinate::new(0, 0));
+
+        generator.fill_terrain_column(&mut chunk, 0, 0, sea_level + 1, &Biome::Plains);
+        generator.fill_terrain_column(&mut chunk, 1, 0, sea_level + 20, &Biome::Plains);
+
+        assert_eq!(chunk.get_block(0, sea_level + 1, 0), BlockType::Sand);
+        assert_eq!(chunk.get_block(0, sea_level, 0), BlockType::Sand);
+        assert_eq!(chunk.get_block(1, sea_level + 20, 0), BlockType::Grass);
+    }
+
+    /// Terrain, caves and ores for a chunk, without surface features
+    fn bare_chunk(generator: &WorldGenerator, coord: ChunkCoordinate) -> Chunk {
+        let mut chunk = Chunk::new(coord);
+        generator.generate_terrain(&mut chunk);
+        generator.generate_caves(&mut chunk);
+        generator.generate_ores(&mut chunk);
+        chunk
+    }
+
+    fn assert_same_blocks(a: &Chunk, b: &Chunk) {
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                for y in 0..CHUNK_HEIGHT {
+                    assert_eq!(a.get_block(x, y, z), b.get_block(x, y, z), "block at ({x}, {y}, {z})");
+                    assert_eq!(a.get_block_state(x, y, z), b.get_block_state(x, y, z), "state at ({x}, {y}, {z})");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn shuffled_column_order_gives_an_identical_chunk() {
+        use rand::seq::SliceRandom;
+        use rand::SeedableRng;
+
+        let generator = WorldGenerator::new(SEED);
+        let mut shuffle_rng = StdRng::seed_from_u64(7);
+        let mut trees_seen = false;
+
+        for cx in 0..6 {
+            let coord = ChunkCoordinate::new(cx, -cx);
+            let mut in_order = bare_chunk(&generator, coord);
+            generator.generate_surface_features(&mut in_order);
+
+            let mut columns: Vec<_> = (0..CHUNK_SIZE).flat_map(|x| (0..CHUNK_SIZE).map(move |z| (x, z))).collect();
+            columns.shuffle(&mut shuffle_rng);
+            let mut shuffled = bare_chunk(&generator, coord);
+            generator.place_surface_features(&mut shuffled, columns);
+
+            assert_same_blocks(&in_order, &shuffled);
+            trees_seen |= (0..CHUNK_SIZE).any(|x| {
+                (0..CHUNK_SIZE).any(|z| (0..CHUNK_HEIGHT).any(|y| in_order.get_block(x, y, z) == BlockType::Log))
+            });
+        }
+        assert!(trees_seen, "expected at least one tree in the sampled chunks");
+    }
+
+    #[test]
+    fn plains_grow_more_than_one_kind_of_flower() {
+        let generator = WorldGenerator::new(SEED);
+        let mut kinds = std::collections::HashSet::new();
+
+        'search: for cx in -8..8 {
+            for cz in -8..8 {
+                let chunk = generator.generate_chunk(ChunkCoordinate::new(cx, cz));
+                let (world_x, world_z) = chunk.coordinate.world_position();
+                for x in 0..CHUNK_SIZE {
+                    for z in 0..CHUNK_SIZE {
+                        let biome = generator.get_biome((world_x + x as i32) as f64, (world_z + z as i32) as f64);
+                        if biome != Biome::Plains {
+                            continue;
+                        }
+                        for y in 0..CHUNK_HEIGHT {
+                            if chunk.get_block(x, y, z) == BlockType::Flower {
+                                kinds.insert(chunk.get_block_state(x, y, z));
+                            }
+                        }
+                    }
+                }
+                if kinds.len() >= 2 {
+                    break 'search;
+                }
+            }
+        }
+
+        assert!(kinds.len() >= 2, "plains flowers found: {kinds:?}");
+    }
+
+    #[test]
+    fn features_never_overwrite_existing_blocks() {
+        let mut chunk = Chunk::new(ChunkCoordinate::new(0, 0));
+        chunk.set_block(3, 70, 3, BlockType::Leaves);
+
+        assert!(!place_feature_block(&mut chunk, 3, 70, 3, BlockType::Flower));
+        assert_eq!(chunk.get_block(3, 70, 3), BlockType::Leaves);
+        assert!(place_feature_block(&mut chunk, 3, 71, 3, BlockType::Log));
+        assert_eq!(chunk.get_block(3, 71, 3), BlockType::Log);
+    }
+
+    #[test]
+    fn cave_threshold_rises_from_deep_to_shallow() {
+        let config = GenerationConfig::default();
+
+        assert_eq!(config.cave_threshold(config.cave_min_y), config.cave_threshold_deep);
+        assert_eq!(config.cave_threshold(config.cave_max_y), config.cave_threshold_shallow);
+        let middle = config.cave_threshold((config.cave_min_y + config.cave_max_y) / 2);
+        assert!(config.cave_threshold_deep < middle && middle < config.cave_threshold_shallow);
+    }
+
+    #[test]
+    fn deep_layers_carve_more_than_shallow_ones() {
+        let generator = WorldGenerator::new(SEED);
+        let carved = |y: usize| {
+            (-64..64)
+                .flat_map(|x| (-64..64).map(move |z| (x, z)))
+                .filter(|&(x, z)| generator.is_cave(x * 3, y, z * 3))
+                .count()
+        };
+
+        let config = generator.config();
+        let (deep, shallow) = (carved(config.cave_min_y + 5), carved(config.cave_max_y - 5));
+
+        assert!(deep > shallow, "deep layer carved {deep} cells, shallow {shallow}");
+    }
+
+    /// Fingerprint of `SNAPSHOT_CHUNKS` generated with `SEED` and the default config.
+    /// When a change to generation is intended, run this test and record the value
+    /// it reports here.
+    const GENERATION_SNAPSHOT: u64 = 0x5af4_e099_907d_e96a;
+
+    const SNAPSHOT_CHUNKS: [(i32, i32); 5] = [(0, 0), (1, 0), (0, -1), (-3, 2), (7, -5)];
+
+    #[test]
+    fn generated_chunks_match_the_recorded_snapshot() {
+        let generator = WorldGenerator::new(SEED);
+        let coords: Vec<ChunkCoordinate> = SNAPSHOT_CHUNKS.iter().map(|&(x, z)| ChunkCoordinate::new(x, z)).collect();
+
+        let fingerprint = generation_fingerprint(&generator, &coords);
+
+        assert_eq!(
+            fingerprint, GENERATION_SNAPSHOT,
+            "generated terrain changed (fingerprint {fingerprint:#018x}); if that's intended, \
+             update GENERATION_SNAPSHOT to the new value"
+        );
+    }
+}
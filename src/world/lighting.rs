@@ -1,5 +1,8 @@
+use glam::IVec3;
 use std::collections::VecDeque;
-use crate::world::{Chunk, ChunkCoordinate, BlockType, CHUNK_SIZE, CHUNK_HEIGHT};
+use crate::world::{Chunk, ChunkCoordinate, World, CHUNK_SIZE, CHUNK_HEIGHT};
+
+const FACE_NEIGHBORS: [IVec3; 6] = [IVec3::X, IVec3::NEG_X, IVec3::Y, IVec3::NEG_Y, IVec3::Z, IVec3::NEG_Z];
 
 /// Lighting engine for calculating light propagation
 pub struct LightingEngine {
@@ -11,7 +14,25 @@ struct LightNode {
     x: usize,
     y: usize,
     z: usize,
-    light_level: u8,
+    /// Red, green and blue block light at this position
+    color: [u8; 3],
+}
+
+impl LightNode {
+    fn is_lit(&self) -> bool {
+        self.color.iter().any(|&channel| channel > 0)
+    }
+}
+
+/// Channel-wise maximum of two light colors
+fn max_color(a: [u8; 3], b: [u8; 3]) -> [u8; 3] {
+    [a[0].max(b[0]), a[1].max(b[1]), a[2].max(b[2])]
+}
+
+/// Light one step further from its source, after passing into a block with the
+/// given `light_opacity`
+fn attenuate(color: [u8; 3], opacity: u8) -> [u8; 3] {
+    color.map(|channel| channel.saturating_sub(1 + opacity))
 }
 
 impl LightingEngine {
@@ -37,26 +58,14 @@ impl LightingEngine {
     fn calculate_sky_lighting(&mut self, chunk: &mut Chunk) {
         for x in 0..CHUNK_SIZE {
             for z in 0..CHUNK_SIZE {
-                let mut sky_light = 15; // Start with full sunlight
+                let mut sky_light = 15u8; // Start with full sunlight
                 
-                // Propagate from top to bottom
+                // Propagate from top to bottom, dimmed by each block on the way
                 for y in (0..CHUNK_HEIGHT).rev() {
                     let block = chunk.get_block(x, y, z);
-                    
-                    // Reduce light if block is not transparent
-                    if !block.is_transparent() {
-                        sky_light = 0;
-                    }
+                    sky_light = sky_light.saturating_sub(block.light_opacity());
                     
                     chunk.set_sky_light(x, y, z, sky_light);
-                    
-                    // Add to light queue for propagation
-                    if sky_light > 0 {
-                        self.light_queue.push_back(LightNode {
-                            x, y, z,
-                            light_level: sky_light,
-                        });
-                    }
                 }
             }
         }
@@ -67,31 +76,29 @@ impl LightingEngine {
         for x in 0..CHUNK_SIZE {
             for y in 0..CHUNK_HEIGHT {
                 for z in 0..CHUNK_SIZE {
-                    let block = chunk.get_block(x, y, z);
-                    let light_level = block.light_level();
-                    
-                    if light_level > 0 {
-                        chunk.set_block_light(x, y, z, light_level);
-                        
+                    let node = LightNode {
+                        x, y, z,
+                        color: chunk.get_block(x, y, z).light_color(),
+                    };
+
+                    if node.is_lit() {
+                        chunk.set_block_light_rgb(x, y, z, node.color);
+
                         // Add to propagation queue
-                        self.light_queue.push_back(LightNode {
-                            x, y, z,
-                            light_level,
-                        });
+                        self.light_queue.push_back(node);
                     }
                 }
             }
         }
     }
 
-    /// Propagate lighting throughout the chunk
+    /// Propagate lighting throughout the chunk, each color channel independently
     fn propagate_lighting(&mut self, chunk: &mut Chunk) {
         while let Some(node) = self.light_queue.pop_front() {
-            if node.light_level <= 1 {
+            // Even the clearest neighbor takes one level off
+            if !attenuate(node.color, 0).iter().any(|&channel| channel > 0) {
                 continue;
             }
-
-            let new_light_level = node.light_level.saturating_sub(1);
             
             // Check all 6 adjacent positions
             let neighbors = [
@@ -106,19 +113,21 @@ impl LightingEngine {
             for (nx, ny, nz) in neighbors {
                 if nx < CHUNK_SIZE && ny < CHUNK_HEIGHT && nz < CHUNK_SIZE {
                     let neighbor_block = chunk.get_block(nx, ny, nz);
+                    let spread = attenuate(node.color, neighbor_block.light_opacity());
                     
-                    // Only propagate through transparent blocks
-                    if neighbor_block.is_transparent() {
-                        let current_light = chunk.get_block_light(nx, ny, nz);
+                    // Only propagate into blocks that let some of the light through
+                    if spread.iter().any(|&channel| channel > 0) {
+                        let current = chunk.get_block_light_rgb(nx, ny, nz);
+                        let merged = max_color(current, spread);
                         
-                        if new_light_level > current_light {
-                            chunk.set_block_light(nx, ny, nz, new_light_level);
+                        if merged != current {
+                            chunk.set_block_light_rgb(nx, ny, nz, merged);
                             
                             self.light_queue.push_back(LightNode {
                                 x: nx,
                                 y: ny,
                                 z: nz,
-                                light_level: new_light_level,
+                                color: merged,
                             });
                         }
                     }
@@ -127,123 +136,18 @@ impl LightingEngine {
         }
     }
 
-    /// Update lighting when a block is placed
-    pub fn update_lighting_add_block(&mut self, chunk: &mut Chunk, x: usize, y: usize, z: usize) {
-        // Remove light from this position
-        chunk.set_sky_light(x, y, z, 0);
-        chunk.set_block_light(x, y, z, 0);
-        
-        // Recalculate lighting in the affected area
-        self.recalculate_area_lighting(chunk, x, y, z, 2);
-    }
-
-    /// Update lighting when a block is removed
-    pub fn update_lighting_remove_block(&mut self, chunk: &mut Chunk, x: usize, y: usize, z: usize) {
-        let block = chunk.get_block(x, y, z);
-        
-        // If it was a light source, remove its contribution
-        if block.light_level() > 0 {
-            self.remove_light_source(chunk, x, y, z, block.light_level());
-        }
-        
-        // Recalculate sky lighting for this column
-        self.recalculate_sky_column(chunk, x, z);
-        
-        // Propagate light into the newly empty space
-        self.propagate_light_to_position(chunk, x, y, z);
-    }
-
-    /// Recalculate lighting in a specific area
-    fn recalculate_area_lighting(&mut self, chunk: &mut Chunk, center_x: usize, center_y: usize, center_z: usize, radius: usize) {
-        let start_x = center_x.saturating_sub(radius);
-        let start_y = center_y.saturating_sub(radius);
-        let start_z = center_z.saturating_sub(radius);
-        
-        let end_x = (center_x + radius).min(CHUNK_SIZE - 1);
-        let end_y = (center_y + radius).min(CHUNK_HEIGHT - 1);
-        let end_z = (center_z + radius).min(CHUNK_SIZE - 1);
-
-        for x in start_x..=end_x {
-            for y in start_y..=end_y {
-                for z in start_z..=end_z {
-                    let block = chunk.get_block(x, y, z);
-                    
-                    // Reset block light
-                    let light_level = block.light_level();
-                    chunk.set_block_light(x, y, z, light_level);
-                    
-                    if light_level > 0 {
-                        self.light_queue.push_back(LightNode {
-                            x, y, z,
-                            light_level,
-                        });
-                    }
-                }
-            }
-        }
-        
-        // Propagate the changes
-        self.propagate_lighting(chunk);
-    }
-
-    /// Recalculate sky lighting for a column
-    fn recalculate_sky_column(&mut self, chunk: &mut Chunk, x: usize, z: usize) {
-        let mut sky_light = 15;
+    /// Recalculate sky lighting for a column, after a block in it was placed or removed
+    pub fn recalculate_sky_column(&mut self, chunk: &mut Chunk, x: usize, z: usize) {
+        let mut sky_light = 15u8;
         
         for y in (0..CHUNK_HEIGHT).rev() {
             let block = chunk.get_block(x, y, z);
-            
-            if !block.is_transparent() {
-                sky_light = 0;
-            }
+            sky_light = sky_light.saturating_sub(block.light_opacity());
             
             chunk.set_sky_light(x, y, z, sky_light);
         }
     }
 
-    /// Remove light from a light source
-    fn remove_light_source(&mut self, chunk: &mut Chunk, x: usize, y: usize, z: usize, light_level: u8) {
-        // Simple approach: just recalculate the area
-        // TODO: Implement proper light removal algorithm
-        self.recalculate_area_lighting(chunk, x, y, z, light_level as usize);
-    }
-
-    /// Propagate light to a specific position
-    fn propagate_light_to_position(&mut self, chunk: &mut Chunk, x: usize, y: usize, z: usize) {
-        let mut max_light = 0u8;
-        
-        // Check all neighbors and take the maximum light level
-        let neighbors = [
-            (x.wrapping_add(1), y, z),
-            (x.wrapping_sub(1), y, z),
-            (x, y.wrapping_add(1), z),
-            (x, y.wrapping_sub(1), z),
-            (x, y, z.wrapping_add(1)),
-            (x, y, z.wrapping_sub(1)),
-        ];
-
-        for (nx, ny, nz) in neighbors {
-            if nx < CHUNK_SIZE && ny < CHUNK_HEIGHT && nz < CHUNK_SIZE {
-                let neighbor_light = chunk.get_block_light(nx, ny, nz);
-                if neighbor_light > max_light {
-                    max_light = neighbor_light;
-                }
-            }
-        }
-        
-        if max_light > 1 {
-            let new_light = max_light - 1;
-            chunk.set_block_light(x, y, z, new_light);
-            
-            self.light_queue.push_back(LightNode {
-                x, y, z,
-                light_level: new_light,
-            });
-            
-            self.propagate_lighting(chunk);
-        }
-    }
-
     /// Calculate ambient occlusion for a vertex
     pub fn calculate_ambient_occlusion(&self, chunk: &Chunk, x: f32, y: f32, z: f32) -> f32 {
         // Simple ambient occlusion based on nearby blocks
@@ -288,4 +192,199 @@ impl Default for LightingEngine {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+/// Light from a block near a chunk border spills into the neighbors, so block
+/// edits are relit in world coordinates rather than one chunk at a time
+impl World {
+    /// Relight block light within `radius` blocks of `center` after the block there
+    /// changed. `radius` must reach as far as the light the old block gave off.
+    ///
+    /// The area is reset to what its own blocks emit, then flooded from those
+    /// emitters and from the light already sitting just outside it, which is how
+    /// light from further away gets back in.
+    pub(crate) fn relight_area(&mut self, center: IVec3, radius: i32) {
+        let min = IVec3::new(center.x - radius, (center.y - radius).max(0), center.z - radius);
+        let max = IVec3::new(center.x + radius, (center.y + radius).min(CHUNK_HEIGHT as i32 - 1), center.z + radius);
+        let mut queue = VecDeque::new();
+
+        for pos in positions_between(min, max) {
+            let Some(block) = self.block_at(pos) else {
+                continue;
+            };
+            let color = block.light_color();
+            self.set_block_light_at(pos, color);
+            if color.iter().any(|&channel| channel > 0) {
+                queue.push_back((pos, color));
+            }
+        }
+
+        // The shell one block outside the area keeps its light and shines back in
+        for pos in positions_between(min - IVec3::ONE, max + IVec3::ONE) {
+            let inside = pos.cmpge(min).all() && pos.cmple(max).all();
+            if inside {
+                continue;
+            }
+            if let Some(color) = self.block_light_at(pos).filter(|color| color.iter().any(|&channel| channel > 0)) {
+                queue.push_back((pos, color));
+            }
+        }
+
+        self.flood_block_light(queue);
+    }
+
+    /// Spread each queued light to its neighbors, each channel independently,
+    /// crossing into any loaded chunk
+    fn flood_block_light(&mut self, mut queue: VecDeque<(IVec3, [u8; 3])>) {
+        while let Some((pos, color)) = queue.pop_front() {
+            for offset in FACE_NEIGHBORS {
+                let neighbor = pos + offset;
+                let Some(neighbor_block) = self.block_at(neighbor) else {
+                    continue;
+                };
+                let spread = attenuate(color, neighbor_block.light_opacity());
+                if !spread.iter().any(|&channel| channel > 0) {
+                    continue;
+                }
+
+                let current = self.block_light_at(neighbor).unwrap_or([0; 3]);
+                let merged = max_color(current, spread);
+                if merged != current {
+                    self.set_block_light_at(neighbor, merged);
+                    queue.push_back((neighbor, merged));
+                }
+            }
+        }
+    }
+
+    fn set_block_light_at(&mut self, pos: IVec3, color: [u8; 3]) {
+        if let Some((chunk_coord, x, y, z)) = Self::locate(pos) {
+            if let Some(chunk) = self.get_chunk_mut(chunk_coord) {
+                chunk.set_block_light_rgb(x, y, z, color);
+            }
+        }
+    }
+}
+
+/// Every position in the inclusive box from `min` to `max`
+fn positions_between(min: IVec3, max: IVec3) -> impl Iterator<Item = IVec3> {
+    (min.x..=max.x).flat_map(move |x| {
+        (min.y..=max.y).flat_map(move |y| (min.z..=max.z).map(move |z| IVec3::new(x, y, z)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::testing::flat_world;
+    use crate::world::BlockType;
+
+    fn lit_chunk(sources: &[(usize, BlockType)]) -> Chunk {
+        let mut chunk = Chunk::new(ChunkCoordinate::new(0, 0));
+        for &(x, block) in sources {
+            chunk.set_block(x, 100, 8, block);
+        }
+        chunk.calculate_lighting();
+        chunk
+    }
+
+    #[test]
+    fn red_torch_light_is_red_dominant() {
+        let chunk = lit_chunk(&[(2, BlockType::RedstoneTorch)]);
+
+        for x in 3..8 {
+            let [r, g, b] = chunk.get_block_light_rgb(x, 100, 8);
+            assert!(r > g && r > b, "light at x = {x} is [{r}, {g}, {b}]");
+        }
+        assert_eq!(chunk.get_block_light_rgb(3, 100, 8), [6, 0, 0]);
+    }
+
+    #[test]
+    fn overlapping_colors_blend_channel_by_channel() {
+        let red = lit_chunk(&[(2, BlockType::RedstoneTorch)]);
+        let warm = lit_chunk(&[(12, BlockType::Torch)]);
+        let both = lit_chunk(&[(2, BlockType::RedstoneTorch), (12, BlockType::Torch)]);
+
+        // One block from the red torch and nine from the torch: red comes from the
+        // red torch, green from the torch
+        assert_eq!(both.get_block_light_rgb(3, 100, 8), [6, 3, 0]);
+        for x in 0..CHUNK_SIZE {
+            let expected = max_color(red.get_block_light_rgb(x, 100, 8), warm.get_block_light_rgb(x, 100, 8));
+            assert_eq!(both.get_block_light_rgb(x, 100, 8), expected, "light at x = {x}");
+        }
+    }
+
+    /// Apply `block` at `pos` and relight, as the engine does after each change
+    fn place_and_relight(world: &mut World, pos: IVec3, block: BlockType) {
+        world.set_block(pos, block);
+        for change in world.take_block_changes() {
+            world.update_block_lighting(&change);
+        }
+    }
+
+    #[test]
+    fn torch_light_crosses_into_the_neighbor_chunk_and_leaves_with_it() {
+        let mut world = flat_world(10);
+        let torch = IVec3::new(15, 11, 8);
+
+        place_and_relight(&mut world, torch, BlockType::Torch);
+        assert_eq!(world.block_light_at(IVec3::new(16, 11, 8)), Some([13, 11, 8]));
+        assert_eq!(world.block_light_at(IVec3::new(20, 11, 8)), Some([9, 7, 4]));
+
+        place_and_relight(&mut world, torch, BlockType::Air);
+        assert_eq!(world.block_light_at(IVec3::new(16, 11, 8)), Some([0; 3]));
+    }
+
+    #[test]
+    fn removing_a_wall_lets_light_from_outside_the_area_back_in() {
+        let mut world = flat_world(10);
+        place_and_relight(&mut world, IVec3::new(2, 11, 8), BlockType::Torch);
+        let wall = IVec3::new(6, 11, 8);
+
+        place_and_relight(&mut world, wall, BlockType::Stone);
+        assert_eq!(world.block_light_at(wall), Some([0; 3]));
+
+        // The torch is outside the relit area, so its light comes back in from the edge
+        place_and_relight(&mut world, wall, BlockType::Air);
+        assert_eq!(world.block_light_at(wall), Some([10, 8, 5]));
+    }
+
+    #[test]
+    fn sky_light_through_five_water_blocks_is_dimmer_than_through_air() {
+        let mut chunk = Chunk::new(ChunkCoordinate::new(0, 0));
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                chunk.set_block(x, 49, z, BlockType::Stone);
+            }
+        }
+        for y in 50..55 {
+            chunk.set_block(4, y, 4, BlockType::Water);
+        }
+
+        chunk.calculate_lighting();
+
+        assert!(chunk.get_sky_light(4, 50, 4) < chunk.get_sky_light(12, 50, 12));
+        assert!(BlockType::Water.light_opacity() > BlockType::Air.light_opacity());
+    }
+
+    #[test]
+    fn leaves_dim_spreading_light_more_than_air_and_opaque_blocks_stop_it() {
+        let start = [14, 14, 14];
+        let through = |block: BlockType, steps: usize| {
+            (0..steps).fold(start, |color, _| attenuate(color, block.light_opacity()))[0]
+        };
+
+        assert!(through(BlockType::Leaves, 3) < through(BlockType::Air, 3));
+        assert_eq!(through(BlockType::Stone, 1), 0);
+
+        // A torch next to a sealed stone shell leaves the inside dark
+        let mut chunk = Chunk::new(ChunkCoordinate::new(0, 0));
+        for (x, y, z) in [(7, 100, 8), (9, 100, 8), (8, 99, 8), (8, 101, 8), (8, 100, 7), (8, 100, 9)] {
+            chunk.set_block(x, y, z, BlockType::Stone);
+        }
+        chunk.set_block(6, 100, 8, BlockType::Torch);
+        chunk.calculate_lighting();
+        assert_eq!(chunk.get_block_light_rgb(8, 100, 8), [0, 0, 0]);
+        assert!(chunk.get_block_light_rgb(6, 101, 8)[0] > 0);
+    }
+}
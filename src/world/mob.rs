@@ -0,0 +1,164 @@
+use glam::Vec3;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::game::Aabb;
+use crate::world::{BlockType, Entity, EntityKind, ItemEntity, World};
+
+/// Pig collision box dimensions in blocks
+const PIG_WIDTH: f32 = 0.9;
+const PIG_HEIGHT: f32 = 0.9;
+const PIG_MAX_HEALTH: f32 = 10.0;
+/// Walking speed while wandering (blocks/second)
+const PIG_WALK_SPEED: f32 = 1.0;
+/// Upward velocity used to hop onto a block in the way
+const PIG_HOP_VELOCITY: f32 = 5.0;
+
+/// A passive pig that wanders around at random
+pub struct Pig {
+    position: Vec3,
+    velocity: Vec3,
+    health: f32,
+    on_ground: bool,
+    wander_direction: Vec3,
+    wander_timer: f32,
+    rng: StdRng,
+    drops: Vec<Box<dyn Entity>>,
+}
+
+impl Pig {
+    pub fn new(position: Vec3, seed: u64) -> Self {
+        Self {
+            position,
+            velocity: Vec3::ZERO,
+            health: PIG_MAX_HEALTH,
+            on_ground: false,
+            wander_direction: Vec3::ZERO,
+            wander_timer: 0.0,
+            rng: StdRng::seed_from_u64(seed),
+            drops: Vec::new(),
+        }
+    }
+
+    pub fn health(&self) -> f32 {
+        self.health
+    }
+
+    pub fn is_on_ground(&self) -> bool {
+        self.on_ground
+    }
+
+    /// Pick a new random heading, or stand still for a while
+    fn choose_wander_direction(&mut self) {
+        self.wander_timer = self.rng.gen_range(2.0..6.0);
+        self.wander_direction = if self.rng.gen::<f32>() < 0.3 {
+            Vec3::ZERO
+        } else {
+            let angle = self.rng.gen_range(0.0..std::f32::consts::TAU);
+            Vec3::new(angle.cos(), 0.0, angle.sin())
+        };
+    }
+}
+
+impl Entity for Pig {
+    fn kind(&self) -> EntityKind {
+        EntityKind::Pig
+    }
+
+    fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    fn velocity(&self) -> Vec3 {
+        self.velocity
+    }
+
+    fn aabb(&self) -> Aabb {
+        Aabb::from_feet(self.position, PIG_WIDTH, PIG_HEIGHT)
+    }
+
+    fn update(&mut self, world: &World, delta_time: f32) {
+        if self.health <= 0.0 {
+            return;
+        }
+
+        self.wander_timer -= delta_time;
+        if self.wander_timer <= 0.0 {
+            self.choose_wander_direction();
+        }
+
+        let wish = self.wander_direction * PIG_WALK_SPEED;
+        self.velocity.x = wish.x;
+        self.velocity.z = wish.z;
+
+        let (offset, on_ground) = world.physics().step_body(world, &self.aabb(), &mut self.velocity, delta_time);
+        self.position += offset;
+        self.on_ground = on_ground;
+
+        // Hop when something blocks the way
+        let blocked = wish.length_squared() > 0.0 && self.velocity.x == 0.0 && self.velocity.z == 0.0;
+        if blocked && self.on_ground {
+            self.velocity.y = PIG_HOP_VELOCITY;
+        }
+    }
+
+    fn damage(&mut self, amount: f32) {
+        if self.health <= 0.0 {
+            return;
+        }
+
+        self.health = (self.health - amount).max(0.0);
+        if self.health <= 0.0 {
+            // No food items yet, wool stands in for the pig's drop
+            let drop = ItemEntity::new(self.position, BlockType::Wool, 1);
+            self.drops.push(Box::new(drop));
+        }
+    }
+
+    fn take_spawns(&mut self) -> Vec<Box<dyn Entity>> {
+        std::mem::take(&mut self.drops)
+    }
+
+    fn is_removed(&self) -> bool {
+        self.health <= 0.0 && self.drops.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::testing::flat_world_with_surface;
+    use crate::world::{MOB_CAP_PER_CHUNK, MOB_SPAWN_INTERVAL};
+
+    #[test]
+    fn spawning_stops_at_the_per_chunk_cap() {
+        let mut world = flat_world_with_surface(10, BlockType::Grass);
+
+        for _ in 0..2000 {
+            world.update_mob_spawning(MOB_SPAWN_INTERVAL);
+        }
+
+        let counts: Vec<usize> = world
+            .loaded_chunks()
+            .iter()
+            .map(|&coord| world.mob_count_in_chunk(coord))
+            .collect();
+        assert!(counts.iter().all(|&count| count <= MOB_CAP_PER_CHUNK));
+        assert!(counts.contains(&MOB_CAP_PER_CHUNK));
+        assert_eq!(world.entities().len(), counts.iter().sum::<usize>());
+    }
+
+    #[test]
+    fn wandering_pig_stays_on_the_ground() {
+        let world = flat_world_with_surface(10, BlockType::Grass);
+        let mut pig = Pig::new(Vec3::new(0.5, 11.0, 0.5), 7);
+        let start = pig.position();
+
+        for _ in 0..600 {
+            pig.update(&world, 1.0 / 60.0);
+            assert!(pig.is_on_ground());
+            assert!((pig.position().y - 11.0).abs() < 1e-3);
+        }
+        assert_ne!(pig.position(), start);
+    }
+}
@@ -1,121 +1,650 @@
-use std::collections::HashMap;
+use anyhow::Result;
+use log::warn;
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
 use serde::{Deserialize, Serialize};
-use glam::Vec3;
+use glam::{IVec3, Vec3};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
 
 mod chunk;
 mod block;
 mod generation;
 mod lighting;
+mod explosion;
+mod entity;
+mod mob;
+mod storage;
+mod switches;
+mod redstone;
+mod neighbors;
+mod shape;
+mod state;
+#[cfg(test)]
+pub(crate) mod testing;
 
-pub use chunk::{Chunk, ChunkCoordinate, CHUNK_SIZE, CHUNK_HEIGHT};
-pub use block::BlockType;
-pub use generation::WorldGenerator;
+pub use chunk::{Chunk, ChunkCoordinate, SliceAxis, CHUNK_SIZE, CHUNK_HEIGHT, WORLD_BORDER};
+pub use block::{BlockInfo, BlockType, DoorFacing, DoorState, FlowerKind, GrassKind};
+pub use generation::{generation_fingerprint, Biome, ChunkGenerator, GenerationConfig, WorldGenerator};
+pub use explosion::{ExplosionResult, PrimedTnt};
+pub use entity::{Entity, EntityKind, ItemConfig, ItemEntity};
+pub use mob::Pig;
+pub use storage::{WorldMetadata, WorldStorage};
+pub use switches::{BUTTON_PRESS_TIME, POWERED_BIT};
+pub use redstone::{wire_power, MAX_POWER};
+pub use neighbors::{NeighborReaction, NeighborUpdate, MAX_NEIGHBOR_UPDATES_PER_TICK};
+pub use shape::{stairs_facing, SLAB_TOP_BIT};
+pub use state::{GameRules, Weather, WorldState, WorldStateChange};
+
+use lighting::LightingEngine;
+use switches::PressedButton;
+
+use crate::game::{Aabb, GameMode, ItemStack, Physics, PhysicsConfig, Player};
+use crate::rendering::Face;
+
+/// Seconds between passive mob spawn attempts
+const MOB_SPAWN_INTERVAL: f32 = 1.0;
+/// Chance per loaded chunk and attempt that a mob spawns
+const MOB_SPAWN_CHANCE: f64 = 0.01;
+/// Maximum passive mobs allowed in a single chunk
+pub const MOB_CAP_PER_CHUNK: usize = 2;
+/// Length of a full day/night cycle in seconds
+pub const DAY_LENGTH: f32 = 1200.0;
+/// Length in seconds of the ambient rain sound, replayed while it rains
+const RAIN_SOUND_INTERVAL: f32 = 4.0;
+/// Seconds between random ticks (20 per second)
+const RANDOM_TICK_INTERVAL: f32 = 0.05;
+/// Blocks picked per 16-block-tall chunk section on each random tick
+const RANDOM_TICKS_PER_SECTION: usize = 3;
+/// Sky light needed above dirt for grass to spread onto it
+const GRASS_SPREAD_MIN_SKY_LIGHT: u8 = 9;
+/// Chunk loads plus unloads performed per `load_chunks_around` call by default
+pub const DEFAULT_CHUNK_BUDGET: usize = 4;
 
 /// Main world manager that handles chunks, blocks, and world generation
 pub struct World {
     chunks: HashMap<ChunkCoordinate, Chunk>,
-    generator: WorldGenerator,
+    /// Shared with background region requests
+    generator: Arc<dyn ChunkGenerator>,
     seed: u64,
     spawn_point: Vec3,
+    /// Time of day, weather and game rules
+    state: WorldState,
+    /// Chunks still to be written by a running autosave
+    autosave_queue: Option<Vec<ChunkCoordinate>>,
+    /// Seconds until the rain loop sound is played again
+    rain_sound_timer: f32,
+    storage: Option<WorldStorage>,
     
     // Chunk loading/unloading
     loaded_chunks: Vec<ChunkCoordinate>,
+    /// Chunks scheduled for loading that haven't been loaded yet
+    pending_chunks: HashSet<ChunkCoordinate>,
     render_distance: i32,
+    /// Maximum chunk loads plus unloads per `load_chunks_around` call
+    chunk_budget: usize,
+
+    // Entities and the physics that moves them
+    physics: Physics,
+    item_config: ItemConfig,
+    entities: Vec<Box<dyn Entity>>,
+    mob_spawn_timer: f32,
+    random_tick_timer: f32,
+    rng: StdRng,
+
+    // Switches: buttons waiting to pop back out and plates currently stood on
+    pressed_buttons: Vec<PressedButton>,
+    active_plates: HashSet<IVec3>,
+
+    // Explosives
+    primed_tnt: Vec<PrimedTnt>,
+    explosions: Vec<ExplosionResult>,
+
+    // Sounds emitted by world events, drained by the engine each frame
+    sounds: Vec<&'static str>,
+
+    // Block changes since the last drain, for systems that react to edits
+    block_changes: Vec<BlockChangeEvent>,
+    /// Blocks to tell about a change next to them on the next tick
+    neighbor_updates: Vec<NeighborUpdate>,
+    /// Blocks can't be placed at or above this height
+    build_limit: i32,
 }
 
 impl World {
     pub fn new() -> Self {
-        let seed = 12345; // TODO: Make configurable
-        let generator = WorldGenerator::new(seed);
-        
+        Self::with_seed(12345) // TODO: Make configurable
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_generator(seed, Box::new(WorldGenerator::new(seed)))
+    }
+
+    /// Create a world with custom generation settings, failing if they don't fit
+    /// inside the chunk height
+    pub fn with_generation_config(seed: u64, config: GenerationConfig) -> Result<Self> {
+        Ok(Self::with_generator(seed, Box::new(WorldGenerator::with_config(seed, config)?)))
+    }
+
+    /// Create a world whose chunks come from a custom generator. `seed` still drives
+    /// world-level randomness such as mob spawning.
+    pub fn with_generator(seed: u64, generator: Box<dyn ChunkGenerator>) -> Self {
         Self {
             chunks: HashMap::new(),
-            generator,
+            generator: Arc::from(generator),
             seed,
             spawn_point: Vec3::new(0.0, 100.0, 0.0),
+            state: WorldState::default(),
+            autosave_queue: None,
+            rain_sound_timer: 0.0,
+            storage: None,
             loaded_chunks: Vec::new(),
+            pending_chunks: HashSet::new(),
+            build_limit: CHUNK_HEIGHT as i32,
             render_distance: 8, // 8 chunk radius
+            chunk_budget: DEFAULT_CHUNK_BUDGET,
+            physics: Physics::new(),
+            item_config: ItemConfig::default(),
+            entities: Vec::new(),
+            mob_spawn_timer: 0.0,
+            random_tick_timer: 0.0,
+            rng: StdRng::seed_from_u64(seed),
+            pressed_buttons: Vec::new(),
+            active_plates: HashSet::new(),
+            primed_tnt: Vec::new(),
+            explosions: Vec::new(),
+            sounds: Vec::new(),
+            block_changes: Vec::new(),
+            neighbor_updates: Vec::new(),
         }
     }
 
-    pub fn with_seed(seed: u64) -> Self {
-        let generator = WorldGenerator::new(seed);
-        
-        Self {
-            chunks: HashMap::new(),
-            generator,
-            seed,
-            spawn_point: Vec3::new(0.0, 100.0, 0.0),
-            loaded_chunks: Vec::new(),
-            render_distance: 8,
+    /// Open a saved world, or start a new default world if nothing was saved there.
+    ///
+    /// Returns the saved metadata so the game can restore the player.
+    pub fn open(storage: WorldStorage) -> Result<(Self, Option<WorldMetadata>)> {
+        let metadata = storage.load_metadata()?;
+        let mut world = match &metadata {
+            Some(metadata) => {
+                let mut world = Self::with_seed(metadata.seed);
+                world.spawn_point = metadata.spawn_point;
+                world.state = metadata.state;
+                world
+            }
+            None => {
+                let mut world = Self::new();
+                world.find_safe_spawn();
+                world
+            }
+        };
+        world.storage = Some(storage);
+        Ok((world, metadata))
+    }
+
+    /// Write the world metadata and player state to storage
+    pub fn save_metadata(&self, game_mode: GameMode, player: &Player) -> Result<()> {
+        if let Some(storage) = &self.storage {
+            storage.save_metadata(&WorldMetadata::capture(self, game_mode, player))?;
+        }
+        Ok(())
+    }
+
+    /// Write every loaded dirty chunk, then the metadata. Used on shutdown.
+    pub fn save_all(&mut self, game_mode: GameMode, player: &Player) -> Result<()> {
+        let Some(storage) = &self.storage else {
+            return Ok(());
+        };
+
+        for chunk in self.chunks.values_mut().filter(|chunk| chunk.dirty) {
+            storage.save_chunk(chunk)?;
+            chunk.mark_clean();
+        }
+        self.save_metadata(game_mode, player)
+    }
+
+    /// Start saving the chunks that are dirty right now, a few per frame through
+    /// `continue_autosave`. Does nothing without storage or if one is already running.
+    pub fn begin_autosave(&mut self) {
+        if self.storage.is_none() || self.autosave_queue.is_some() {
+            return;
         }
+        let dirty = self.chunks.values().filter(|chunk| chunk.dirty).map(|chunk| chunk.coordinate).collect();
+        self.autosave_queue = Some(dirty);
+    }
+
+    pub fn is_autosaving(&self) -> bool {
+        self.autosave_queue.is_some()
     }
 
-    pub fn update(&mut self, _delta_time: f32) {
+    /// Write up to `max_chunks` queued chunks, then the metadata once the queue is
+    /// empty. Chunks unloaded in the meantime were already saved on unload.
+    /// Returns true when the autosave has finished. A failed write abandons the
+    /// autosave; the chunks it didn't get to stay dirty for the next one.
+    pub fn continue_autosave(&mut self, max_chunks: usize, game_mode: GameMode, player: &Player) -> Result<bool> {
+        let (Some(storage), Some(queue)) = (&self.storage, &mut self.autosave_queue) else {
+            return Ok(true);
+        };
+
+        for _ in 0..max_chunks {
+            let Some(coord) = queue.pop() else {
+                break;
+            };
+            if let Some(chunk) = self.chunks.get_mut(&coord).filter(|chunk| chunk.dirty) {
+                if let Err(e) = storage.save_chunk(chunk) {
+                    self.autosave_queue = None;
+                    return Err(e);
+                }
+                chunk.mark_clean();
+            }
+        }
+
+        if !queue.is_empty() {
+            return Ok(false);
+        }
+        self.autosave_queue = None;
+        self.save_metadata(game_mode, player)?;
+        Ok(true)
+    }
+
+    pub fn storage(&self) -> Option<&WorldStorage> {
+        self.storage.as_ref()
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
         // TODO: Implement world tick updates (water flow, plant growth, etc.)
+        if self.state.rules.daylight_cycle {
+            self.state.time_of_day = (self.state.time_of_day + delta_time / DAY_LENGTH).fract();
+        }
+        self.update_rain_sound(delta_time);
+        self.update_entities(delta_time);
+        if self.state.rules.mob_spawning {
+            self.update_mob_spawning(delta_time);
+        }
+        self.process_neighbor_updates();
+        self.update_primed_tnt(delta_time);
+        self.update_buttons(delta_time);
+        self.update_random_ticks(delta_time);
     }
 
-    /// Load chunks around a player position
-    pub fn load_chunks_around(&mut self, player_pos: Vec3) {
-        let player_chunk_x = (player_pos.x / CHUNK_SIZE as f32).floor() as i32;
-        let player_chunk_z = (player_pos.z / CHUNK_SIZE as f32).floor() as i32;
+    /// Run random ticks at a fixed rate: each tick picks a few random blocks in every
+    /// section of every loaded chunk and lets them update (grass spreading, etc.)
+    fn update_random_ticks(&mut self, delta_time: f32) {
+        self.random_tick_timer += delta_time;
+        // Don't try to catch up on more than a few ticks after a long frame
+        self.random_tick_timer = self.random_tick_timer.min(RANDOM_TICK_INTERVAL * 4.0);
 
-        let mut chunks_to_load = Vec::new();
-        let mut chunks_to_unload = Vec::new();
+        while self.random_tick_timer >= RANDOM_TICK_INTERVAL {
+            self.random_tick_timer -= RANDOM_TICK_INTERVAL;
+            for coord in self.loaded_chunks.clone() {
+                self.random_tick_chunk(coord);
+            }
+        }
+    }
 
-        // Find chunks that should be loaded
-        for x in (player_chunk_x - self.render_distance)..=(player_chunk_x + self.render_distance) {
-            for z in (player_chunk_z - self.render_distance)..=(player_chunk_z + self.render_distance) {
-                let chunk_coord = ChunkCoordinate { x, z };
-                let distance = ((x - player_chunk_x).pow(2) + (z - player_chunk_z).pow(2)) as f32;
-                
-                if distance <= (self.render_distance as f32).powi(2) {
-                    if !self.chunks.contains_key(&chunk_coord) {
-                        chunks_to_load.push(chunk_coord);
-                    }
+    fn random_tick_chunk(&mut self, coord: ChunkCoordinate) {
+        let (chunk_x, chunk_z) = coord.world_position();
+        for section in 0..CHUNK_HEIGHT / CHUNK_SIZE {
+            for _ in 0..RANDOM_TICKS_PER_SECTION {
+                let pos = IVec3::new(
+                    chunk_x + self.rng.gen_range(0..CHUNK_SIZE) as i32,
+                    (section * CHUNK_SIZE + self.rng.gen_range(0..CHUNK_SIZE)) as i32,
+                    chunk_z + self.rng.gen_range(0..CHUNK_SIZE) as i32,
+                );
+                if let Some(block) = self.block_at(pos) {
+                    self.random_tick(pos, block);
                 }
             }
         }
+    }
+
+    /// React to a random tick landing on `block` at `pos`
+    pub fn random_tick(&mut self, pos: IVec3, block: BlockType) {
+        if block == BlockType::Grass {
+            self.tick_grass(pos);
+        }
+    }
+
+    /// Covered grass dies back to dirt; uncovered grass spreads to nearby sunlit dirt
+    fn tick_grass(&mut self, pos: IVec3) {
+        if !self.is_uncovered(pos) {
+            self.set_block(pos, BlockType::Dirt);
+            return;
+        }
+
+        let target = pos + IVec3::new(
+            self.rng.gen_range(-1..=1),
+            self.rng.gen_range(-3..=1),
+            self.rng.gen_range(-1..=1),
+        );
+        let sunlit = self.sky_light_at(target + IVec3::Y).unwrap_or(15) >= GRASS_SPREAD_MIN_SKY_LIGHT;
+        if self.block_at(target) == Some(BlockType::Dirt) && self.is_uncovered(target) && sunlit {
+            self.set_block(target, BlockType::Grass);
+        }
+    }
+
+    /// Whether the block directly above lets light through
+    fn is_uncovered(&self, pos: IVec3) -> bool {
+        self.block_at(pos + IVec3::Y).is_none_or(|above| above.is_transparent())
+    }
+
+    /// Step every entity and drop the ones that have been removed
+    fn update_entities(&mut self, delta_time: f32) {
+        // Entities read the world while updating, so detach the list for the duration
+        let mut entities = std::mem::take(&mut self.entities);
+        let mut spawned = Vec::new();
+        for entity in &mut entities {
+            entity.update(self, delta_time);
+            spawned.extend(entity.take_spawns());
+        }
+        entities.extend(spawned);
+        Self::merge_items(&mut entities, self.item_config.merge_radius);
+        entities.retain(|entity| !entity.is_removed());
+        self.entities = entities;
+    }
+
+    /// Combine dropped stacks of the same item lying within `radius` of each other
+    fn merge_items(entities: &mut [Box<dyn Entity>], radius: f32) {
+        let radius_squared = radius * radius;
+        for i in 0..entities.len() {
+            let (before, rest) = entities.split_at_mut(i + 1);
+            let Some(item) = before[i].as_item_mut() else {
+                continue;
+            };
+            for other in rest.iter_mut().filter_map(|entity| entity.as_item_mut()) {
+                if item.count() == 0 || item.position().distance_squared(other.position()) > radius_squared {
+                    continue;
+                }
+                let max_count = ItemStack::new(item.item_type(), 0).max_stack_size;
+                item.absorb(other, max_count);
+            }
+        }
+    }
+
+    /// Hand every dropped item near `collector` (e.g. the player's box) that is past
+    /// its pickup delay to `accept`, which returns how many of the offered items it
+    /// took. Items it takes are removed from the world; the rest stay where they are.
+    pub fn collect_items(&mut self, collector: &Aabb, mut accept: impl FnMut(BlockType, u32) -> u32) {
+        let reach = collector.expanded(self.item_config.pickup_radius);
+        let config = self.item_config;
+        for item in self.entities.iter_mut().filter_map(|entity| entity.as_item_mut()) {
+            if !item.can_be_picked_up(&config) || !item.aabb().intersects(&reach) {
+                continue;
+            }
+            let taken = accept(item.item_type(), item.count());
+            item.take(taken);
+        }
+    }
+
+    /// Periodically try to spawn passive mobs on grass in loaded chunks
+    fn update_mob_spawning(&mut self, delta_time: f32) {
+        self.mob_spawn_timer += delta_time;
+        if self.mob_spawn_timer < MOB_SPAWN_INTERVAL {
+            return;
+        }
+        self.mob_spawn_timer = 0.0;
+
+        for coord in self.loaded_chunks.clone() {
+            if self.rng.gen::<f64>() >= MOB_SPAWN_CHANCE {
+                continue;
+            }
+            if self.mob_count_in_chunk(coord) >= MOB_CAP_PER_CHUNK {
+                continue;
+            }
 
-        // Find chunks that should be unloaded
-        for &chunk_coord in self.chunks.keys() {
-            let distance = ((chunk_coord.x - player_chunk_x).pow(2) + 
-                           (chunk_coord.z - player_chunk_z).pow(2)) as f32;
-            
-            if distance > ((self.render_distance + 2) as f32).powi(2) {
-                chunks_to_unload.push(chunk_coord);
+            let local_x = self.rng.gen_range(0..CHUNK_SIZE);
+            let local_z = self.rng.gen_range(0..CHUNK_SIZE);
+            let seed = self.rng.gen();
+            if let Some(position) = self.find_mob_spawn(coord, local_x, local_z) {
+                self.spawn_entity(Box::new(Pig::new(position, seed)));
             }
         }
+    }
+
+    /// Number of mobs whose position lies inside the given chunk
+    pub fn mob_count_in_chunk(&self, coord: ChunkCoordinate) -> usize {
+        self.entities
+            .iter()
+            .filter(|entity| entity.kind() == EntityKind::Pig)
+            .filter(|entity| {
+                let position = entity.position();
+                let chunk_x = (position.x / CHUNK_SIZE as f32).floor() as i32;
+                let chunk_z = (position.z / CHUNK_SIZE as f32).floor() as i32;
+                chunk_x == coord.x && chunk_z == coord.z
+            })
+            .count()
+    }
+
+    /// Spawn position on top of a grass column, if that column is grass with room above
+    fn find_mob_spawn(&self, coord: ChunkCoordinate, local_x: usize, local_z: usize) -> Option<Vec3> {
+        let chunk = self.get_chunk(coord)?;
+        let height = chunk.get_height_at(local_x, local_z);
+        if height == 0 || height >= CHUNK_HEIGHT {
+            return None;
+        }
+        if chunk.get_block(local_x, height - 1, local_z) != BlockType::Grass {
+            return None;
+        }
+
+        let (world_x, world_z) = coord.world_position();
+        Some(Vec3::new(
+            world_x as f32 + local_x as f32 + 0.5,
+            height as f32,
+            world_z as f32 + local_z as f32 + 0.5,
+        ))
+    }
+
+    pub fn spawn_entity(&mut self, entity: Box<dyn Entity>) {
+        self.entities.push(entity);
+    }
+
+    pub fn entities(&self) -> &[Box<dyn Entity>] {
+        &self.entities
+    }
+
+    /// The nearest mob the ray hits within its reach, as an index into `entities`
+    /// and the distance along the ray. Dropped items can't be hit.
+    pub fn raycast_entity(&self, ray: &crate::rendering::camera::Ray) -> Option<(usize, f32)> {
+        let direction = ray.direction.normalize_or_zero();
+        if direction == Vec3::ZERO {
+            return None;
+        }
+
+        self.entities
+            .iter()
+            .enumerate()
+            .filter(|(_, entity)| entity.kind() != EntityKind::Item)
+            .filter_map(|(index, entity)| {
+                entity
+                    .aabb()
+                    .ray_distance(ray.origin, direction, ray.max_distance)
+                    .map(|distance| (index, distance))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Deal `damage` to the entity at `index` in `entities`
+    pub fn attack_entity(&mut self, index: usize, damage: f32) {
+        if let Some(entity) = self.entities.get_mut(index) {
+            entity.damage(damage);
+        }
+    }
+
+    pub fn physics(&self) -> &Physics {
+        &self.physics
+    }
+
+    pub fn item_config(&self) -> &ItemConfig {
+        &self.item_config
+    }
+
+    /// Replace dropped item tuning: pickup reach and delay, merging, despawning
+    pub fn set_item_config(&mut self, config: ItemConfig) {
+        self.item_config = config;
+    }
+
+    /// Replace gravity and movement tuning, e.g. for a low-gravity world
+    pub fn set_physics_config(&mut self, config: PhysicsConfig) {
+        self.physics.set_config(config);
+    }
+
+    /// Schedule missing chunks within render distance and unload distant ones.
+    ///
+    /// Each missing chunk is scheduled once and stays pending until loaded. At most
+    /// `chunk_budget` chunks are loaded or unloaded per call. Loads go nearest first so
+    /// terrain fills in outward from the player, and unloads farthest first; the rest
+    /// are picked up on later calls. Returns how many chunks were processed.
+    pub fn load_chunks_around(&mut self, player_pos: Vec3) -> usize {
+        let player_chunk = ChunkCoordinate::containing(player_pos.x.floor() as i32, player_pos.z.floor() as i32);
+        let load_distance_sq = (self.render_distance as i64).pow(2);
+
+        for coord in player_chunk.within_radius(self.render_distance) {
+            self.schedule_chunk(coord);
+        }
+        // Forget requests the player has since moved away from
+        self.pending_chunks.retain(|coord| player_chunk.distance_squared(*coord) <= load_distance_sq);
 
-        // Load new chunks
-        for chunk_coord in chunks_to_load {
+        let mut chunks_to_load: Vec<ChunkCoordinate> = self.pending_chunks.iter().copied().collect();
+        chunks_to_load.sort_by_key(|coord| (player_chunk.distance_squared(*coord), coord.x, coord.z));
+
+        let unload_distance_sq = (self.render_distance as i64 + 2).pow(2);
+        let mut chunks_to_unload: Vec<ChunkCoordinate> = self.chunks
+            .keys()
+            .copied()
+            .filter(|coord| player_chunk.distance_squared(*coord) > unload_distance_sq)
+            .collect();
+        chunks_to_unload.sort_by_key(|coord| std::cmp::Reverse(player_chunk.distance_squared(*coord)));
+
+        // Load the nearest pending chunks within the budget
+        let load_count = chunks_to_load.len().min(self.chunk_budget);
+        for &chunk_coord in &chunks_to_load[..load_count] {
             self.load_chunk(chunk_coord);
         }
 
-        // Unload distant chunks
-        for chunk_coord in chunks_to_unload {
+        // Unload the farthest chunks with whatever budget is left
+        let unload_count = chunks_to_unload.len().min(self.chunk_budget - load_count);
+        for &chunk_coord in &chunks_to_unload[..unload_count] {
             self.unload_chunk(chunk_coord);
         }
+
+        load_count + unload_count
+    }
+
+    /// Generate every chunk from `min` to `max` (inclusive) on a background thread,
+    /// sending each one as it's finished. Meant for tools like map viewers: the
+    /// chunks are fresh from the generator, without saved edits, and never join the
+    /// loaded set used for gameplay. Chunks outside the world border are skipped,
+    /// and generation stops early if the receiver is dropped.
+    pub fn request_region(&self, min: ChunkCoordinate, max: ChunkCoordinate) -> Receiver<(ChunkCoordinate, Chunk)> {
+        let (sender, receiver) = mpsc::channel();
+        let generator = Arc::clone(&self.generator);
+        thread::spawn(move || {
+            for x in min.x..=max.x {
+                for z in min.z..=max.z {
+                    let coord = ChunkCoordinate::new(x, z);
+                    if !coord.is_within_border() {
+                        continue;
+                    }
+                    if sender.send((coord, generator.generate_chunk(coord))).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        receiver
+    }
+
+    /// Queue a chunk for loading. Returns false if it is already loaded or pending,
+    /// or lies outside the world border.
+    pub fn schedule_chunk(&mut self, coord: ChunkCoordinate) -> bool {
+        coord.is_within_border() && !self.chunks.contains_key(&coord) && self.pending_chunks.insert(coord)
+    }
+
+    /// Load or generate the chunk right away instead of waiting for its turn in
+    /// `load_chunks_around`. Returns false for chunks outside the world border.
+    pub fn load_chunk_now(&mut self, coord: ChunkCoordinate) -> bool {
+        if !coord.is_within_border() {
+            return false;
+        }
+        self.load_chunk(coord);
+        true
     }
 
     fn load_chunk(&mut self, coord: ChunkCoordinate) {
+        self.pending_chunks.remove(&coord);
         if !self.chunks.contains_key(&coord) {
-            let chunk = self.generator.generate_chunk(coord);
+            let mut chunk = self.load_saved_chunk(coord)
+                .unwrap_or_else(|| self.generator.generate_chunk(coord));
+            // Generation is deterministic, so only later edits need saving; the
+            // renderer meshes chunks the first time it sees them
+            chunk.mark_clean();
+            chunk.mark_meshed();
             self.chunks.insert(coord, chunk);
             self.loaded_chunks.push(coord);
         }
     }
 
+    /// Read a previously saved chunk, if there is storage and the chunk exists in it
+    fn load_saved_chunk(&self, coord: ChunkCoordinate) -> Option<Chunk> {
+        let storage = self.storage.as_ref()?;
+        match storage.load_chunk(coord) {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                warn!("Failed to load chunk {:?}, regenerating: {}", coord, e);
+                None
+            }
+        }
+    }
+
     fn unload_chunk(&mut self, coord: ChunkCoordinate) {
-        // TODO: Save chunk data before unloading
+        // Flush edits now; once removed, save_all can no longer see this chunk
+        if let (Some(storage), Some(chunk)) = (&self.storage, self.chunks.get(&coord)) {
+            if chunk.dirty {
+                if let Err(e) = storage.save_chunk(chunk) {
+                    warn!("Failed to save chunk {:?}: {}", coord, e);
+                }
+            }
+        }
         self.chunks.remove(&coord);
         self.loaded_chunks.retain(|&c| c != coord);
     }
 
+    /// Debug aid: throw away a loaded chunk, including its saved data, and run the
+    /// generator for it again. Any player edits in the chunk are lost. Returns false
+    /// if the chunk isn't loaded.
+    pub fn regenerate_chunk(&mut self, coord: ChunkCoordinate) -> Result<bool> {
+        if !self.chunks.contains_key(&coord) {
+            return Ok(false);
+        }
+        if let Some(storage) = &self.storage {
+            storage.delete_chunk(coord)?;
+        }
+
+        let mut chunk = self.generator.generate_chunk(coord);
+        chunk.mark_clean();
+        chunk.mark_meshed();
+        self.chunks.insert(coord, chunk);
+        Ok(true)
+    }
+
     pub fn get_chunk(&self, coord: ChunkCoordinate) -> Option<&Chunk> {
         self.chunks.get(&coord)
     }
 
+    /// Loaded chunks edited since they were last handed out here, for the renderer to
+    /// remesh. Catches edits made straight on a chunk, which raise no block change event.
+    pub fn take_remesh_chunks(&mut self) -> Vec<ChunkCoordinate> {
+        self.chunks
+            .values_mut()
+            .filter(|chunk| chunk.needs_remesh())
+            .map(|chunk| {
+                chunk.mark_meshed();
+                chunk.coordinate
+            })
+            .collect()
+    }
+
     pub fn get_chunk_mut(&mut self, coord: ChunkCoordinate) -> Option<&mut Chunk> {
         self.chunks.get_mut(&coord)
     }
@@ -124,70 +653,312 @@ impl World {
         self.chunks.contains_key(&coord)
     }
 
-    pub fn get_block_at(&self, x: i32, y: i32, z: i32) -> Option<BlockType> {
-        if y < 0 || y >= CHUNK_HEIGHT as i32 {
+    /// Whether a chunk has been scheduled but not loaded yet
+    pub fn is_chunk_pending(&self, coord: ChunkCoordinate) -> bool {
+        self.pending_chunks.contains(&coord)
+    }
+
+    /// Split a world block position into its chunk and local coordinates. Positions
+    /// outside the world border or height give `None`.
+    fn locate(pos: IVec3) -> Option<(ChunkCoordinate, usize, usize, usize)> {
+        if pos.y < 0 || pos.y >= CHUNK_HEIGHT as i32 {
+            return None;
+        }
+        let border = -WORLD_BORDER..=WORLD_BORDER;
+        if !border.contains(&pos.x) || !border.contains(&pos.z) {
             return None;
         }
 
-        let chunk_x = x.div_euclid(CHUNK_SIZE as i32);
-        let chunk_z = z.div_euclid(CHUNK_SIZE as i32);
-        let chunk_coord = ChunkCoordinate { x: chunk_x, z: chunk_z };
+        let local_x = pos.x.rem_euclid(CHUNK_SIZE as i32) as usize;
+        let local_z = pos.z.rem_euclid(CHUNK_SIZE as i32) as usize;
+        Some((ChunkCoordinate::containing(pos.x, pos.z), local_x, pos.y as usize, local_z))
+    }
+
+    /// Block at a world position, or `None` if it is outside the world or not loaded
+    pub fn block_at(&self, pos: IVec3) -> Option<BlockType> {
+        let (chunk_coord, x, y, z) = Self::locate(pos)?;
+        self.get_chunk(chunk_coord).map(|chunk| chunk.get_block(x, y, z))
+    }
 
-        if let Some(chunk) = self.get_chunk(chunk_coord) {
-            let local_x = x.rem_euclid(CHUNK_SIZE as i32) as usize;
-            let local_z = z.rem_euclid(CHUNK_SIZE as i32) as usize;
-            Some(chunk.get_block(local_x, y as usize, local_z))
-        } else {
-            None
+    /// True if a loaded, solid block occupies the position
+    pub fn is_solid_at(&self, pos: IVec3) -> bool {
+        match self.block_at(pos) {
+            Some(block) => block.is_solid_with_state(self.block_state_at(pos).unwrap_or(0)),
+            None => false,
         }
     }
 
-    pub fn set_block_at(&mut self, x: i32, y: i32, z: i32, block: BlockType) -> bool {
-        if y < 0 || y >= CHUNK_HEIGHT as i32 {
+    /// Height blocks may be placed below. Never more than `CHUNK_HEIGHT`.
+    pub fn build_limit(&self) -> i32 {
+        self.build_limit
+    }
+
+    /// Lower the build limit, e.g. for a flat test world. Clamped to `1..=CHUNK_HEIGHT`.
+    pub fn set_build_limit(&mut self, limit: i32) {
+        self.build_limit = limit.clamp(1, CHUNK_HEIGHT as i32);
+    }
+
+    /// Whether a block may be placed at this position
+    pub fn is_within_build_limit(&self, pos: IVec3) -> bool {
+        pos.y >= 0 && pos.y < self.build_limit
+    }
+
+    /// Set the block at a world position. Returns false if it isn't loaded, or if
+    /// a non-air block would go above the build limit.
+    pub fn set_block(&mut self, pos: IVec3, block: BlockType) -> bool {
+        self.set_block_with_state(pos, block, 0)
+    }
+
+    /// Set a block together with its state (variant), e.g. a specific flower
+    pub fn set_block_with_state(&mut self, pos: IVec3, block: BlockType, state: u8) -> bool {
+        if block != BlockType::Air && !self.is_within_build_limit(pos) {
+            return false;
+        }
+        let Some((chunk_coord, x, y, z)) = Self::locate(pos) else {
+            return false;
+        };
+        let Some(chunk) = self.get_chunk_mut(chunk_coord) else {
+            return false;
+        };
+        let old = chunk.get_block(x, y, z);
+        let old_state = chunk.get_block_state(x, y, z);
+        if old == block && old_state == state {
+            return true;
+        }
+        chunk.set_block(x, y, z, block);
+        chunk.set_block_state(x, y, z, state);
+        self.block_changes.push(BlockChangeEvent { position: pos, old, new: block });
+        self.notify_neighbors(pos);
+
+        // Doors come in pairs; replacing one half takes the other with it
+        if old == BlockType::Door && block != BlockType::Door {
+            let partner = pos + IVec3::Y * DoorState::from_state(old_state).partner_offset();
+            if self.block_at(partner) == Some(BlockType::Door) {
+                self.set_block(partner, BlockType::Air);
+            }
+        }
+
+        if block == BlockType::Tnt || block == BlockType::Lava {
+            self.check_lava_ignition(pos);
+        }
+
+        // A wire changing only its power was written by the redstone update itself
+        let rewired = old == BlockType::RedstoneWire && block == BlockType::RedstoneWire;
+        if !rewired && (old.is_redstone_component() || block.is_redstone_component()) {
+            self.update_redstone(pos);
+        }
+        true
+    }
+
+    /// Place a closed two-block door with its lower half at `pos`, hinged on the left.
+    /// Returns false unless both cells are loaded, replaceable and under the build limit.
+    pub fn place_door(&mut self, pos: IVec3, facing: DoorFacing) -> bool {
+        let upper = pos + IVec3::Y;
+        let fits = |world: &World, cell: IVec3| {
+            world.is_within_build_limit(cell) && world.block_at(cell).is_some_and(|block| block.is_replaceable())
+        };
+        if !fits(self, pos) || !fits(self, upper) {
             return false;
         }
 
-        let chunk_x = x.div_euclid(CHUNK_SIZE as i32);
-        let chunk_z = z.div_euclid(CHUNK_SIZE as i32);
-        let chunk_coord = ChunkCoordinate { x: chunk_x, z: chunk_z };
+        let lower_state = DoorState { upper: false, open: false, hinge_right: false, facing };
+        let upper_state = DoorState { upper: true, ..lower_state };
+        self.set_block_with_state(pos, BlockType::Door, lower_state.state());
+        self.set_block_with_state(upper, BlockType::Door, upper_state.state());
+        true
+    }
 
-        if let Some(chunk) = self.get_chunk_mut(chunk_coord) {
-            let local_x = x.rem_euclid(CHUNK_SIZE as i32) as usize;
-            let local_z = z.rem_euclid(CHUNK_SIZE as i32) as usize;
-            chunk.set_block(local_x, y as usize, local_z, block);
-            true
-        } else {
-            false
+    /// Open or close the door with a half at `pos`, keeping both halves in step.
+    /// Returns false if there's no door there.
+    pub fn toggle_door(&mut self, pos: IVec3) -> bool {
+        if self.block_at(pos) != Some(BlockType::Door) {
+            return false;
         }
+        let door = DoorState::from_state(self.block_state_at(pos).unwrap_or(0));
+        let partner = pos + IVec3::Y * door.partner_offset();
+
+        let toggled = DoorState { open: !door.open, ..door };
+        self.set_block_with_state(pos, BlockType::Door, toggled.state());
+        if self.block_at(partner) == Some(BlockType::Door) {
+            let partner_state = DoorState { upper: !door.upper, ..toggled };
+            self.set_block_with_state(partner, BlockType::Door, partner_state.state());
+        }
+        true
+    }
+
+    /// Block state (variant) at a world position, if loaded
+    pub fn block_state_at(&self, pos: IVec3) -> Option<u8> {
+        let (chunk_coord, x, y, z) = Self::locate(pos)?;
+        self.get_chunk(chunk_coord).map(|chunk| chunk.get_block_state(x, y, z))
+    }
+
+    /// Relight the area around a changed block, into neighboring chunks where its
+    /// light reaches them
+    pub fn update_block_lighting(&mut self, change: &BlockChangeEvent) {
+        let Some((chunk_coord, x, _, z)) = Self::locate(change.position) else {
+            return;
+        };
+        let Some(chunk) = self.get_chunk_mut(chunk_coord) else {
+            return;
+        };
+
+        // Sky light only falls straight down, so just this column changes
+        LightingEngine::new().recalculate_sky_column(chunk, x, z);
+        self.relight_area(change.position, change.relight_radius());
+    }
+
+    /// Sky light (0-15) at a world position, if loaded
+    pub fn sky_light_at(&self, pos: IVec3) -> Option<u8> {
+        let (chunk_coord, x, y, z) = Self::locate(pos)?;
+        self.get_chunk(chunk_coord).map(|chunk| chunk.get_sky_light(x, y, z))
+    }
+
+    /// Red, green and blue block light (0-15 each) at a world position, if loaded
+    pub fn block_light_at(&self, pos: IVec3) -> Option<[u8; 3]> {
+        let (chunk_coord, x, y, z) = Self::locate(pos)?;
+        self.get_chunk(chunk_coord).map(|chunk| chunk.get_block_light_rgb(x, y, z))
+    }
+
+    /// Every loaded block in the inclusive box between `min` and `max`
+    pub fn blocks_in_aabb(&self, min: IVec3, max: IVec3) -> impl Iterator<Item = (IVec3, BlockType)> + '_ {
+        (min.x..=max.x).flat_map(move |x| {
+            (min.y..=max.y).flat_map(move |y| {
+                (min.z..=max.z).filter_map(move |z| {
+                    let pos = IVec3::new(x, y, z);
+                    self.block_at(pos).map(|block| (pos, block))
+                })
+            })
+        })
+    }
+
+    pub fn get_block_at(&self, x: i32, y: i32, z: i32) -> Option<BlockType> {
+        self.block_at(IVec3::new(x, y, z))
+    }
+
+    pub fn set_block_at(&mut self, x: i32, y: i32, z: i32, block: BlockType) -> bool {
+        self.set_block(IVec3::new(x, y, z), block)
     }
 
     /// Cast a ray for block interaction
     pub fn raycast(&self, ray: &crate::rendering::camera::Ray) -> Option<RaycastHit> {
-        let mut t = 0.0;
-        let step_size = 0.1;
+        let direction = ray.direction.normalize_or_zero();
+        if direction == Vec3::ZERO {
+            return None;
+        }
+
+        // Walk the grid cell by cell (Amanatides & Woo), so the face the ray
+        // crossed into each cell is known exactly
+        let mut cell = ray.origin.floor().as_ivec3();
+        let mut t_max = Vec3::ZERO;
+        let mut t_delta = Vec3::ZERO;
+        for axis in 0..3 {
+            if direction[axis] == 0.0 {
+                t_max[axis] = f32::INFINITY;
+                t_delta[axis] = f32::INFINITY;
+                continue;
+            }
+            t_delta[axis] = 1.0 / direction[axis].abs();
+            let boundary = if direction[axis] > 0.0 { cell[axis] as f32 + 1.0 } else { cell[axis] as f32 };
+            t_max[axis] = (boundary - ray.origin[axis]) / direction[axis];
+        }
 
-        while t < ray.max_distance {
-            let point = ray.point_at(t);
-            let block_x = point.x.floor() as i32;
-            let block_y = point.y.floor() as i32;
-            let block_z = point.z.floor() as i32;
+        // Starting inside a block counts as hitting the face the ray points away from
+        let dominant = (0..3).max_by(|&a, &b| direction[a].abs().total_cmp(&direction[b].abs())).unwrap_or(0);
+        let mut face = Face::entered_along(dominant, direction[dominant] > 0.0);
+        let mut t = 0.0;
 
-            if let Some(block) = self.get_block_at(block_x, block_y, block_z) {
+        while t <= ray.max_distance {
+            if let Some(block) = self.get_block_at(cell.x, cell.y, cell.z) {
                 if block != BlockType::Air {
                     return Some(RaycastHit {
-                        position: Vec3::new(block_x as f32, block_y as f32, block_z as f32),
+                        position: cell.as_vec3(),
                         distance: t,
                         block_type: block,
+                        block_state: self.block_state_at(cell).unwrap_or(0),
+                        face,
                     });
                 }
             }
 
-            t += step_size;
+            let axis = if t_max.x < t_max.y && t_max.x < t_max.z {
+                0
+            } else if t_max.y < t_max.z {
+                1
+            } else {
+                2
+            };
+            t = t_max[axis];
+            let positive = direction[axis] > 0.0;
+            cell[axis] += if positive { 1 } else { -1 };
+            t_max[axis] += t_delta[axis];
+            face = Face::entered_along(axis, positive);
         }
 
         None
     }
 
+    /// TNT currently counting down to an explosion
+    pub fn primed_tnt(&self) -> &[PrimedTnt] {
+        &self.primed_tnt
+    }
+
+    /// Take the explosions that happened since the last call
+    pub fn take_explosions(&mut self) -> Vec<ExplosionResult> {
+        std::mem::take(&mut self.explosions)
+    }
+
+    pub(crate) fn play_sound(&mut self, sound_id: &'static str) {
+        self.sounds.push(sound_id);
+    }
+
+    /// Take the sounds emitted by world events since the last call
+    pub fn take_sounds(&mut self) -> Vec<&'static str> {
+        std::mem::take(&mut self.sounds)
+    }
+
+    /// Take the block changes made since the last call, in the order they happened
+    pub fn take_block_changes(&mut self) -> Vec<BlockChangeEvent> {
+        std::mem::take(&mut self.block_changes)
+    }
+
+    /// Find a safe place to stand near the world origin and make it the spawn point.
+    ///
+    /// Loads the spawn chunk and picks the column closest to the origin whose top
+    /// block is solid ground rather than water or lava. The returned position is
+    /// where the player's feet rest, on top of that block.
+    pub fn find_safe_spawn(&mut self) -> Vec3 {
+        let spawn_chunk = ChunkCoordinate::containing(0, 0);
+        self.load_chunk(spawn_chunk);
+        let Some(chunk) = self.get_chunk(spawn_chunk) else {
+            return self.spawn_point;
+        };
+
+        let mut columns: Vec<(usize, usize)> = (0..CHUNK_SIZE)
+            .flat_map(|x| (0..CHUNK_SIZE).map(move |z| (x, z)))
+            .collect();
+        let (chunk_x, chunk_z) = spawn_chunk.world_position();
+        columns.sort_by_key(|&(x, z)| (chunk_x + x as i32).pow(2) + (chunk_z + z as i32).pow(2));
+
+        // Height map gives one above the highest non-air block, so there is open air above
+        let ground = columns.into_iter().find_map(|(x, z)| {
+            let height = chunk.get_height_at(x, z);
+            if height == 0 || height >= CHUNK_HEIGHT - 2 {
+                return None;
+            }
+            let top = chunk.get_block(x, height - 1, z);
+            (top.is_solid() && top != BlockType::Water && top != BlockType::Lava).then_some((x, height, z))
+        });
+
+        if let Some((x, height, z)) = ground {
+            self.spawn_point = Vec3::new(
+                chunk_x as f32 + x as f32 + 0.5,
+                height as f32,
+                chunk_z as f32 + z as f32 + 0.5,
+            );
+        }
+        self.spawn_point
+    }
+
     pub fn spawn_point(&self) -> Vec3 {
         self.spawn_point
     }
@@ -200,6 +971,74 @@ impl World {
         self.seed
     }
 
+    /// Time, weather and game rules, e.g. to save or send to clients
+    pub fn state(&self) -> &WorldState {
+        &self.state
+    }
+
+    pub fn set_state(&mut self, state: WorldState) {
+        self.state = state;
+        self.state.time_of_day = state.time_of_day.rem_euclid(1.0);
+    }
+
+    pub fn time_of_day(&self) -> f32 {
+        self.state.time_of_day
+    }
+
+    pub fn set_time_of_day(&mut self, time: f32) {
+        self.state.time_of_day = time.rem_euclid(1.0);
+    }
+
+    pub fn weather(&self) -> Weather {
+        self.state.weather
+    }
+
+    pub fn set_weather(&mut self, weather: Weather) {
+        self.state.weather = weather;
+    }
+
+    /// Start or stop rain at full intensity
+    pub fn set_raining(&mut self, raining: bool) {
+        self.state.weather = Weather { raining, intensity: 1.0 };
+    }
+
+    pub fn is_raining(&self) -> bool {
+        self.state.weather.raining
+    }
+
+    pub fn game_rules(&self) -> &GameRules {
+        &self.state.rules
+    }
+
+    pub fn game_rules_mut(&mut self) -> &mut GameRules {
+        &mut self.state.rules
+    }
+
+    /// Keep the ambient rain loop going while it rains
+    fn update_rain_sound(&mut self, delta_time: f32) {
+        if !self.state.weather.raining {
+            self.rain_sound_timer = 0.0;
+            return;
+        }
+        self.rain_sound_timer -= delta_time;
+        if self.rain_sound_timer <= 0.0 {
+            self.rain_sound_timer = RAIN_SOUND_INTERVAL;
+            self.play_sound("ambient.weather.rain");
+        }
+    }
+
+    /// Biome the generator assigns to the column at (x, z), if it has biomes
+    pub fn biome_at(&self, x: i32, z: i32) -> Option<Biome> {
+        self.generator.biome_at(x, z)
+    }
+
+    /// Strength of sunlight for the current time of day, from 0.2 at midnight to 1.0
+    /// for most of the day, dimmed while it rains. Time 0 is sunrise and 0.5 is sunset.
+    pub fn daylight(&self) -> f32 {
+        let sun_height = (self.state.time_of_day * std::f32::consts::TAU).sin();
+        (sun_height * 2.0 + 0.5).clamp(0.2, 1.0) * self.state.weather.sky_darkening()
+    }
+
     pub fn loaded_chunks(&self) -> &[ChunkCoordinate] {
         &self.loaded_chunks
     }
@@ -211,6 +1050,15 @@ impl World {
     pub fn render_distance(&self) -> i32 {
         self.render_distance
     }
+
+    pub fn chunk_budget(&self) -> usize {
+        self.chunk_budget
+    }
+
+    /// Set how many chunks may be loaded or unloaded per `load_chunks_around` call
+    pub fn set_chunk_budget(&mut self, budget: usize) {
+        self.chunk_budget = budget.max(1);
+    }
 }
 
 /// Result of a raycast operation
@@ -219,10 +1067,475 @@ pub struct RaycastHit {
     pub position: Vec3,
     pub distance: f32,
     pub block_type: BlockType,
+    pub block_state: u8,
+    /// Face of the block the ray hit
+    pub face: Face,
+}
+
+/// A block in a loaded chunk changed from `old` to `new`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockChangeEvent {
+    pub position: IVec3,
+    pub old: BlockType,
+    pub new: BlockType,
+}
+
+impl BlockChangeEvent {
+    /// How far, in blocks, the change can alter block light: the brightest of the
+    /// light the old block gave off and the light the new one gives off
+    pub fn light_radius(&self) -> i32 {
+        self.old.light_level().max(self.new.light_level()) as i32
+    }
+
+    /// How far `World::update_block_lighting` relights around the change: the light
+    /// radius, but always past the neighbors so light can flow into a new gap or
+    /// be shut out by a new wall
+    pub fn relight_radius(&self) -> i32 {
+        self.light_radius().max(2)
+    }
 }
 
 impl Default for World {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::world::testing::{flat_world, flat_world_with_surface, FlatGenerator, TempDir};
+
+    #[test]
+    fn block_at_handles_negative_coordinates() {
+        let world = flat_world(10);
+
+        assert_eq!(world.block_at(IVec3::new(-1, 10, -1)), Some(BlockType::Stone));
+        assert_eq!(world.block_at(IVec3::new(-16, 11, -16)), Some(BlockType::Air));
+        // Past the loaded 3x3 chunks, and outside the world's height
+        assert_eq!(world.block_at(IVec3::new(-17, 10, 0)), None);
+        assert_eq!(world.block_at(IVec3::new(0, -1, 0)), None);
+        assert_eq!(world.block_at(IVec3::new(0, CHUNK_HEIGHT as i32, 0)), None);
+    }
+
+    #[test]
+    fn set_block_lands_in_the_chunk_on_each_side_of_a_border() {
+        let mut world = flat_world(10);
+
+        assert!(world.set_block(IVec3::new(-1, 20, 0), BlockType::Glass));
+        assert!(world.set_block(IVec3::new(0, 20, -1), BlockType::Dirt));
+        assert!(!world.set_block(IVec3::new(40, 20, 0), BlockType::Dirt));
+
+        let west = world.get_chunk(ChunkCoordinate::new(-1, 0)).unwrap();
+        assert_eq!(west.get_block(CHUNK_SIZE - 1, 20, 0), BlockType::Glass);
+        let north = world.get_chunk(ChunkCoordinate::new(0, -1)).unwrap();
+        assert_eq!(north.get_block(0, 20, CHUNK_SIZE - 1), BlockType::Dirt);
+        assert_eq!(world.block_at(IVec3::new(0, 20, 0)), Some(BlockType::Air));
+    }
+
+    #[test]
+    fn is_solid_at_across_chunk_borders() {
+        let mut world = flat_world(10);
+        world.set_block(IVec3::new(-1, 11, -1), BlockType::Water);
+
+        assert!(world.is_solid_at(IVec3::new(-1, 10, -1)));
+        assert!(world.is_solid_at(IVec3::new(0, 10, 0)));
+        assert!(!world.is_solid_at(IVec3::new(-1, 11, -1)));
+        assert!(!world.is_solid_at(IVec3::new(-17, 10, 0)));
+    }
+
+    #[test]
+    fn blocks_in_aabb_spans_chunks_and_skips_unloaded_ones() {
+        let mut world = flat_world(10);
+        world.set_block(IVec3::new(-1, 11, 0), BlockType::Dirt);
+
+        let blocks: Vec<_> = world.blocks_in_aabb(IVec3::new(-2, 11, -2), IVec3::new(1, 11, 1)).collect();
+        assert_eq!(blocks.len(), 16);
+        assert!(blocks.contains(&(IVec3::new(-1, 11, 0), BlockType::Dirt)));
+        assert_eq!(blocks.iter().filter(|(_, block)| *block == BlockType::Air).count(), 15);
+
+        // Only the loaded column of the box at x = -16 is returned
+        let edge: Vec<_> = world.blocks_in_aabb(IVec3::new(-17, 10, 0), IVec3::new(-16, 10, 0)).collect();
+        assert_eq!(edge, vec![(IVec3::new(-16, 10, 0), BlockType::Stone)]);
+    }
+
+    #[test]
+    fn edited_chunk_is_saved_when_unloaded() {
+        let dir = TempDir::new("save_on_unload");
+        let mut world = flat_world(10);
+        world.storage = Some(WorldStorage::new(dir.path()));
+        let edited = ChunkCoordinate::new(1, 1);
+        let untouched = ChunkCoordinate::new(-1, -1);
+        world.set_block(IVec3::new(20, 11, 20), BlockType::Glass);
+
+        world.unload_chunk(edited);
+        world.unload_chunk(untouched);
+
+        let storage = world.storage().unwrap();
+        let saved = storage.load_chunk(edited).unwrap().expect("edited chunk was flushed on unload");
+        assert_eq!(saved.get_block(4, 11, 4), BlockType::Glass);
+        assert!(storage.load_chunk(untouched).unwrap().is_none());
+    }
+
+    #[test]
+    fn chunk_budget_spreads_loads_over_calls_nearest_first() {
+        let generator = FlatGenerator { ground: 10, surface: BlockType::Stone };
+        let mut world = World::with_generator(1, Box::new(generator));
+        world.set_render_distance(3);
+        world.set_chunk_budget(4);
+        let origin = ChunkCoordinate::new(0, 0);
+        let expected = origin.within_radius(3).len();
+
+        let mut calls = 0;
+        loop {
+            let processed = world.load_chunks_around(Vec3::new(8.0, 64.0, 8.0));
+            assert!(processed <= 4);
+            if processed == 0 {
+                break;
+            }
+            calls += 1;
+        }
+
+        assert_eq!(world.loaded_chunks().len(), expected);
+        assert_eq!(calls, expected.div_ceil(4));
+        let distances: Vec<_> = world.loaded_chunks().iter().map(|c| origin.distance_squared(*c)).collect();
+        assert!(distances.windows(2).all(|pair| pair[0] <= pair[1]), "load order {distances:?}");
+    }
+
+    #[test]
+    fn farthest_chunks_unload_first() {
+        let generator = FlatGenerator { ground: 10, surface: BlockType::Stone };
+        let mut world = World::with_generator(1, Box::new(generator));
+        world.set_render_distance(2);
+        world.set_chunk_budget(100);
+        world.load_chunks_around(Vec3::new(8.0, 64.0, 8.0));
+        let old_chunks = world.loaded_chunks().len();
+
+        // Far enough that every old chunk is out of range; the budget covers the new
+        // ring plus a single unload
+        let player = Vec3::new(20.0 * CHUNK_SIZE as f32 + 8.0, 64.0, 8.0);
+        world.set_chunk_budget(old_chunks + 1);
+        world.load_chunks_around(player);
+
+        assert!(!world.is_chunk_loaded(ChunkCoordinate::new(-2, 0)));
+        assert_eq!(world.loaded_chunks().len(), old_chunks * 2 - 1);
+    }
+
+    #[test]
+    fn each_block_change_emits_one_event() {
+        let mut world = flat_world(10);
+        world.take_block_changes();
+        let pos = IVec3::new(-3, 11, 5);
+
+        world.set_block(pos, BlockType::Dirt);
+        assert_eq!(
+            world.take_block_changes(),
+            vec![BlockChangeEvent { position: pos, old: BlockType::Air, new: BlockType::Dirt }]
+        );
+
+        world.set_block(pos, BlockType::Dirt);
+        assert!(world.take_block_changes().is_empty());
+
+        world.set_block(pos, BlockType::Air);
+        assert_eq!(
+            world.take_block_changes(),
+            vec![BlockChangeEvent { position: pos, old: BlockType::Dirt, new: BlockType::Air }]
+        );
+    }
+
+    /// Places one glass block in every chunk at local (1, 2, 3)
+    struct SingleBlockGenerator;
+
+    impl ChunkGenerator for SingleBlockGenerator {
+        fn generate_chunk(&self, coord: ChunkCoordinate) -> Chunk {
+            let mut chunk = Chunk::new(coord);
+            chunk.set_block(1, 2, 3, BlockType::Glass);
+            chunk
+        }
+    }
+
+    #[test]
+    fn world_uses_the_injected_generator() {
+        let mut world = World::with_generator(7, Box::new(SingleBlockGenerator));
+        world.load_chunk_now(ChunkCoordinate::new(-2, 3));
+
+        assert_eq!(world.get_block_at(-31, 2, 51), Some(BlockType::Glass));
+        assert_eq!(world.get_block_at(-31, 3, 51), Some(BlockType::Air));
+        assert_eq!(world.get_chunk(ChunkCoordinate::new(-2, 3)).unwrap().block_count(), 1);
+    }
+
+    /// Flat terrain that counts how many chunks it was asked for
+    struct CountingGenerator(Arc<AtomicUsize>);
+
+    impl ChunkGenerator for CountingGenerator {
+        fn generate_chunk(&self, coord: ChunkCoordinate) -> Chunk {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            FlatGenerator { ground: 10, surface: BlockType::Stone }.generate_chunk(coord)
+        }
+    }
+
+    #[test]
+    fn repeated_load_requests_generate_each_chunk_once() {
+        let generated = Arc::new(AtomicUsize::new(0));
+        let mut world = World::with_generator(1, Box::new(CountingGenerator(Arc::clone(&generated))));
+        let coord = ChunkCoordinate::new(4, 4);
+
+        assert!(world.schedule_chunk(coord));
+        assert!(!world.schedule_chunk(coord));
+        assert!(world.is_chunk_pending(coord) && !world.is_chunk_loaded(coord));
+
+        world.set_render_distance(2);
+        world.set_chunk_budget(3);
+        let player = Vec3::new(8.0, 64.0, 8.0);
+        while world.load_chunks_around(player) > 0 {
+            world.load_chunks_around(player);
+        }
+
+        let ring = ChunkCoordinate::new(0, 0).within_radius(2).len();
+        assert_eq!(generated.load(Ordering::SeqCst), ring);
+        assert_eq!(world.loaded_chunks().len(), ring);
+        // Out of range of the player, so the early request was dropped, not loaded
+        assert!(!world.is_chunk_pending(coord) && !world.is_chunk_loaded(coord));
+    }
+
+    #[test]
+    fn placed_variant_is_stored_and_reset_by_replacing_the_block() {
+        let mut world = flat_world(10);
+        let pos = IVec3::new(2, 11, -2);
+
+        assert!(world.set_block_with_state(pos, BlockType::Flower, FlowerKind::Cornflower.state()));
+        assert_eq!(world.block_at(pos), Some(BlockType::Flower));
+        assert_eq!(world.block_state_at(pos), Some(FlowerKind::Cornflower.state()));
+
+        world.set_block(pos, BlockType::Dirt);
+        assert_eq!(world.block_state_at(pos), Some(0));
+    }
+
+    #[test]
+    fn grass_spreads_onto_sunlit_dirt_over_ticks() {
+        let mut world = flat_world_with_surface(10, BlockType::Grass);
+        let dirt = IVec3::new(0, 10, 0);
+        world.set_block(dirt, BlockType::Dirt);
+
+        for _ in 0..500 {
+            if world.block_at(dirt) == Some(BlockType::Grass) {
+                break;
+            }
+            world.random_tick(IVec3::new(1, 10, 0), BlockType::Grass);
+        }
+
+        assert_eq!(world.block_at(dirt), Some(BlockType::Grass));
+    }
+
+    #[test]
+    fn grass_under_a_solid_block_turns_to_dirt() {
+        let mut world = flat_world_with_surface(10, BlockType::Grass);
+        let covered = IVec3::new(3, 10, 3);
+        world.set_block(covered + IVec3::Y, BlockType::Stone);
+        // Glass lets light through, so the grass under it survives
+        let glazed = IVec3::new(5, 10, 5);
+        world.set_block(glazed + IVec3::Y, BlockType::Glass);
+
+        world.random_tick(covered, BlockType::Grass);
+        world.random_tick(glazed, BlockType::Grass);
+
+        assert_eq!(world.block_at(covered), Some(BlockType::Dirt));
+        assert_eq!(world.block_at(glazed), Some(BlockType::Grass));
+    }
+
+    #[test]
+    fn safe_spawn_stands_on_solid_ground_with_room_above() {
+        for seed in [1, 42, 12345] {
+            let mut world = World::with_seed(seed);
+            let spawn = world.find_safe_spawn();
+            let feet = spawn.floor().as_ivec3();
+
+            assert_eq!(world.spawn_point(), spawn);
+            let ground = world.block_at(feet - IVec3::Y).unwrap();
+            assert!(ground.is_solid() && ground != BlockType::Water, "seed {seed}: standing on {ground:?}");
+            assert_eq!(world.block_at(feet), Some(BlockType::Air), "seed {seed}");
+            assert_eq!(world.block_at(feet + IVec3::Y), Some(BlockType::Air), "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn regenerating_an_edited_chunk_restores_the_generated_blocks() {
+        let dir = TempDir::new("regenerate_chunk");
+        let mut world = flat_world(10);
+        world.storage = Some(WorldStorage::new(dir.path()));
+        let coord = ChunkCoordinate::new(0, 0);
+        let ground = IVec3::new(3, 10, 3);
+        let generated = world.block_at(ground);
+        world.set_block(ground, BlockType::Glass);
+        world.unload_chunk(coord);
+        world.load_chunk_now(coord);
+        assert_eq!(world.block_at(ground), Some(BlockType::Glass));
+
+        assert!(world.regenerate_chunk(coord).unwrap());
+
+        assert_eq!(world.block_at(ground), generated);
+        assert!(world.storage().unwrap().load_chunk(coord).unwrap().is_none());
+    }
+
+    #[test]
+    fn blocks_above_the_build_limit_are_rejected() {
+        let mut world = flat_world(10);
+        world.set_build_limit(20);
+
+        assert!(world.set_block(IVec3::new(2, 19, 2), BlockType::Stone));
+        assert!(!world.set_block(IVec3::new(2, 20, 2), BlockType::Stone));
+        assert_eq!(world.block_at(IVec3::new(2, 20, 2)), Some(BlockType::Air));
+    }
+
+    #[test]
+    fn coordinates_near_i32_max_are_outside_the_world() {
+        let mut world = flat_world(10);
+        for pos in [IVec3::new(i32::MAX, 11, 0), IVec3::new(0, 11, i32::MIN), IVec3::new(WORLD_BORDER + 1, 11, 0)] {
+            assert_eq!(world.block_at(pos), None, "{pos}");
+            assert!(!world.set_block(pos, BlockType::Stone), "{pos}");
+        }
+
+        let far = ChunkCoordinate::containing(i32::MAX, i32::MAX);
+        assert!(!far.is_within_border());
+        assert_eq!(ChunkCoordinate::new(i32::MAX, i32::MIN).world_position(), (i32::MAX, i32::MIN));
+    }
+
+    #[test]
+    fn placed_door_fills_two_cells_and_toggles_both_halves() {
+        let mut world = flat_world(10);
+        let lower = IVec3::new(3, 11, 3);
+        let upper = lower + IVec3::Y;
+
+        assert!(world.place_door(lower, DoorFacing::East));
+
+        assert_eq!(world.block_at(lower), Some(BlockType::Door));
+        assert_eq!(world.block_at(upper), Some(BlockType::Door));
+        let half = |world: &World, pos| DoorState::from_state(world.block_state_at(pos).unwrap());
+        assert!(!half(&world, lower).upper && half(&world, upper).upper);
+
+        // Toggling from either half opens and closes both
+        assert!(world.toggle_door(upper));
+        assert!(half(&world, lower).open && half(&world, upper).open);
+        assert!(world.toggle_door(lower));
+        assert!(!half(&world, lower).open && !half(&world, upper).open);
+        assert_eq!(half(&world, upper).facing, DoorFacing::East);
+
+        // Breaking one half takes the other with it
+        world.set_block(lower, BlockType::Air);
+        assert_eq!(world.block_at(upper), Some(BlockType::Air));
+    }
+
+    #[test]
+    fn door_needs_two_free_cells() {
+        let mut world = flat_world(10);
+        let lower = IVec3::new(3, 11, 3);
+        world.set_block(lower + IVec3::Y, BlockType::Stone);
+
+        assert!(!world.place_door(lower, DoorFacing::North));
+        assert_eq!(world.block_at(lower), Some(BlockType::Air));
+    }
+
+    #[test]
+    fn requested_region_streams_every_chunk_without_loading_them() {
+        let world = flat_world(10);
+        let (min, max) = (ChunkCoordinate::new(4, -2), ChunkCoordinate::new(7, 1));
+
+        let mut received: Vec<ChunkCoordinate> = world.request_region(min, max).iter().map(|(coord, _)| coord).collect();
+
+        received.sort_by_key(|coord| (coord.x, coord.z));
+        let expected: Vec<ChunkCoordinate> =
+            (4..=7).flat_map(|x| (-2..=1).map(move |z| ChunkCoordinate::new(x, z))).collect();
+        assert_eq!(received.len(), 16);
+        assert_eq!(received, expected);
+        assert!(received.iter().all(|&coord| !world.is_chunk_loaded(coord)));
+    }
+
+    #[test]
+    fn rain_sets_the_weather_flag_and_darkens_the_sky() {
+        let mut world = flat_world(10);
+        let clear = world.daylight();
+
+        world.set_raining(true);
+
+        assert!(world.is_raining());
+        assert!(world.state().weather.raining);
+        assert!(world.daylight() < clear);
+    }
+
+    #[test]
+    fn autosave_writes_only_dirty_chunks() {
+        let dir = TempDir::new("autosave_dirty");
+        let mut world = flat_world(10);
+        world.storage = Some(WorldStorage::new(dir.path()));
+        let edited = ChunkCoordinate::new(1, 1);
+        world.set_block(IVec3::new(20, 11, 20), BlockType::Glass);
+        let player = Player::new(Vec3::new(0.5, 12.0, 0.5));
+
+        world.begin_autosave();
+        while !world.continue_autosave(1, GameMode::Creative, &player).unwrap() {}
+
+        let storage = world.storage().unwrap();
+        assert!(storage.load_chunk(edited).unwrap().is_some());
+        for x in -1..=1 {
+            for z in -1..=1 {
+                let coord = ChunkCoordinate::new(x, z);
+                if coord != edited {
+                    assert!(storage.load_chunk(coord).unwrap().is_none(), "clean chunk {coord:?} was written");
+                }
+            }
+        }
+        assert!(!world.get_chunk(edited).unwrap().dirty);
+    }
+
+    #[test]
+    fn failed_autosave_write_stops_the_autosave_and_keeps_chunks_dirty() {
+        let dir = TempDir::new("autosave_failure");
+        let mut world = flat_world(10);
+        world.storage = Some(WorldStorage::new(dir.path()));
+        world.set_block(IVec3::new(20, 11, 20), BlockType::Glass);
+        world.set_block(IVec3::new(-10, 11, -10), BlockType::Glass);
+        let player = Player::new(Vec3::new(0.5, 12.0, 0.5));
+        // A file where the chunk directory should be makes every chunk write fail
+        std::fs::create_dir_all(dir.path()).unwrap();
+        std::fs::write(dir.path().join("chunks"), b"").unwrap();
+
+        world.begin_autosave();
+        assert!(world.continue_autosave(4, GameMode::Creative, &player).is_err());
+
+        assert!(!world.is_autosaving());
+        assert!(world.get_chunk(ChunkCoordinate::new(1, 1)).unwrap().dirty);
+        assert!(world.get_chunk(ChunkCoordinate::new(-1, -1)).unwrap().dirty);
+
+        // The next autosave picks the chunks up again once writing works
+        std::fs::remove_file(dir.path().join("chunks")).unwrap();
+        world.begin_autosave();
+        assert!(world.continue_autosave(4, GameMode::Creative, &player).unwrap());
+        assert!(!world.get_chunk(ChunkCoordinate::new(1, 1)).unwrap().dirty);
+    }
+
+    #[test]
+    fn items_in_pickup_radius_are_collected_only_after_the_delay() {
+        let mut world = flat_world(10);
+        world.set_item_config(ItemConfig { pickup_radius: 2.0, pickup_delay: 1.0, ..ItemConfig::default() });
+        world.spawn_entity(Box::new(ItemEntity::new(Vec3::new(2.0, 11.0, 0.5), BlockType::Dirt, 3)));
+        world.spawn_entity(Box::new(ItemEntity::new(Vec3::new(8.0, 11.0, 0.5), BlockType::Sand, 1)));
+        let player = Aabb::from_feet(Vec3::new(0.5, 11.0, 0.5), 0.6, 1.8);
+        let mut collected = Vec::new();
+
+        world.update_entities(0.5);
+        world.collect_items(&player, |item, count| {
+            collected.push((item, count));
+            count
+        });
+        assert!(collected.is_empty(), "picked up before the delay: {collected:?}");
+
+        world.update_entities(0.6);
+        world.collect_items(&player, |item, count| {
+            collected.push((item, count));
+            count
+        });
+        // Only the item within the radius
+        assert_eq!(collected, [(BlockType::Dirt, 3)]);
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::HashSet;
+use glam::{IVec3, Vec3};
+
+use crate::world::{BlockType, ItemEntity, World};
+
+/// Most neighbor updates handled per tick; the rest wait for the next one so a
+/// large cascade (e.g. a tall sand pillar) can't stall a frame
+pub const MAX_NEIGHBOR_UPDATES_PER_TICK: usize = 4096;
+
+/// Offsets to the six blocks sharing a face with a block
+const FACE_NEIGHBORS: [IVec3; 6] = [IVec3::X, IVec3::NEG_X, IVec3::Y, IVec3::NEG_Y, IVec3::Z, IVec3::NEG_Z];
+
+/// A block told that the block at `source` next to it changed. A block that was
+/// just placed gets one with `source` equal to its own position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NeighborUpdate {
+    pub position: IVec3,
+    pub source: IVec3,
+}
+
+/// What a block does when a neighbor changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NeighborReaction {
+    None,
+    /// Break, dropping its items, e.g. a torch whose support was removed
+    Break,
+    /// Drop down onto the first block below that can hold it
+    Fall,
+}
+
+impl BlockType {
+    /// React to the block at `source` next to `pos` changing, or to having just been
+    /// placed when `source` is `pos`. Unloaded cells count as support, so blocks at
+    /// the edge of loaded terrain stay put.
+    pub fn on_neighbor_changed(&self, world: &World, pos: IVec3, source: IVec3) -> NeighborReaction {
+        let below = pos - IVec3::Y;
+        if source != below && source != pos {
+            return NeighborReaction::None;
+        }
+        let Some(support) = world.block_at(below) else {
+            return NeighborReaction::None;
+        };
+
+        if self.needs_support() && !support.is_solid() {
+            NeighborReaction::Break
+        } else if self.is_affected_by_gravity() && support.is_replaceable() {
+            NeighborReaction::Fall
+        } else {
+            NeighborReaction::None
+        }
+    }
+}
+
+impl World {
+    /// Tell the six blocks around `pos` that it changed, on the next tick. The
+    /// block at `pos` is told too, so e.g. sand placed in midair falls.
+    pub(crate) fn notify_neighbors(&mut self, pos: IVec3) {
+        let around = FACE_NEIGHBORS.iter().map(|&offset| NeighborUpdate {
+            position: pos + offset,
+            source: pos,
+        });
+        self.neighbor_updates.extend(around.chain(std::iter::once(NeighborUpdate { position: pos, source: pos })));
+    }
+
+    /// Neighbor updates waiting for the next tick
+    pub fn pending_neighbor_updates(&self) -> &[NeighborUpdate] {
+        &self.neighbor_updates
+    }
+
+    /// Run the neighbor updates queued before this tick. Changes they make queue
+    /// more updates, which run on the following tick.
+    pub(crate) fn process_neighbor_updates(&mut self) {
+        let count = self.neighbor_updates.len().min(MAX_NEIGHBOR_UPDATES_PER_TICK);
+        let updates: Vec<NeighborUpdate> = self.neighbor_updates.drain(..count).collect();
+
+        let mut seen = HashSet::new();
+        for update in updates {
+            if !seen.insert(update) {
+                continue;
+            }
+            let Some(block) = self.block_at(update.position) else {
+                continue;
+            };
+            match block.on_neighbor_changed(self, update.position, update.source) {
+                NeighborReaction::None => {}
+                NeighborReaction::Break => self.break_unsupported(update.position, block),
+                NeighborReaction::Fall => self.drop_block(update.position, block),
+            }
+        }
+    }
+
+    fn break_unsupported(&mut self, pos: IVec3, block: BlockType) {
+        self.set_block(pos, BlockType::Air);
+        for (item_type, count) in block.drops() {
+            let position = pos.as_vec3() + Vec3::new(0.5, 0.0, 0.5);
+            self.spawn_entity(Box::new(ItemEntity::new(position, item_type, count)));
+        }
+    }
+
+    /// Move a falling block straight down until it rests on something
+    fn drop_block(&mut self, pos: IVec3, block: BlockType) {
+        let mut landing = pos;
+        while landing.y > 0 && self.block_at(landing - IVec3::Y).is_some_and(|below| below.is_replaceable()) {
+            landing.y -= 1;
+        }
+        let state = self.block_state_at(pos).unwrap_or(0);
+        self.set_block(pos, BlockType::Air);
+        self.set_block_with_state(landing, block, state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::testing::flat_world;
+
+    #[test]
+    fn removing_a_block_queues_updates_for_its_neighbors() {
+        let mut world = flat_world(10);
+        let support = IVec3::new(4, 10, 4);
+        world.process_neighbor_updates();
+
+        world.set_block(support, BlockType::Air);
+
+        let updates = world.pending_neighbor_updates();
+        for offset in FACE_NEIGHBORS {
+            assert!(updates.contains(&NeighborUpdate { position: support + offset, source: support }));
+        }
+    }
+
+    #[test]
+    fn torch_breaks_when_its_support_is_removed() {
+        let mut world = flat_world(10);
+        let support = IVec3::new(4, 11, 4);
+        let torch = support + IVec3::Y;
+        world.set_block(support, BlockType::Stone);
+        world.set_block(torch, BlockType::Torch);
+        world.process_neighbor_updates();
+        assert_eq!(world.block_at(torch), Some(BlockType::Torch));
+
+        world.set_block(support, BlockType::Air);
+        world.process_neighbor_updates();
+
+        assert_eq!(world.block_at(torch), Some(BlockType::Air));
+    }
+
+    #[test]
+    fn sand_placed_in_midair_falls_to_the_ground() {
+        let mut world = flat_world(10);
+        let placed = IVec3::new(4, 15, 4);
+
+        world.set_block(placed, BlockType::Sand);
+        world.process_neighbor_updates();
+
+        assert_eq!(world.block_at(placed), Some(BlockType::Air));
+        assert_eq!(world.block_at(IVec3::new(4, 11, 4)), Some(BlockType::Sand));
+    }
+}
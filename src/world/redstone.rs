@@ -0,0 +1,160 @@
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use glam::IVec3;
+use log::warn;
+
+use crate::world::{BlockType, World, POWERED_BIT};
+
+/// Strongest redstone signal; wire loses one level per block from its source
+pub const MAX_POWER: u8 = 15;
+
+/// Wire block state bits holding its signal strength
+const POWER_MASK: u8 = 0x0F;
+
+/// Most wires recomputed for a single change, so a huge network can't stall a tick
+const MAX_NETWORK_SIZE: usize = 4096;
+
+/// Cells that can feed or carry a signal to a block: its six face neighbors
+const NEIGHBORS: [IVec3; 6] = [IVec3::X, IVec3::NEG_X, IVec3::Y, IVec3::NEG_Y, IVec3::Z, IVec3::NEG_Z];
+
+/// Signal strength stored in a redstone wire's block state
+pub fn wire_power(state: u8) -> u8 {
+    state & POWER_MASK
+}
+
+impl BlockType {
+    /// Signal a block feeds into the wire next to it: full power from redstone
+    /// torches and from switches that are on, nothing otherwise
+    pub fn emitted_power(&self, state: u8) -> u8 {
+        match self {
+            BlockType::RedstoneTorch => MAX_POWER,
+            BlockType::Lever | BlockType::Button | BlockType::PressurePlate if state & POWERED_BIT != 0 => MAX_POWER,
+            _ => 0,
+        }
+    }
+
+    /// Blocks whose placement, removal or state change can change wire power
+    pub fn is_redstone_component(&self) -> bool {
+        matches!(self, BlockType::RedstoneWire | BlockType::RedstoneTorch) || self.is_switch()
+    }
+}
+
+impl World {
+    /// Signal strength of the redstone wire at `pos`, or 0 if there's no wire
+    pub fn redstone_power_at(&self, pos: IVec3) -> u8 {
+        match self.block_at(pos) {
+            Some(BlockType::RedstoneWire) => wire_power(self.block_state_at(pos).unwrap_or(0)),
+            _ => 0,
+        }
+    }
+
+    /// Recompute the power of every wire in the network(s) touching `pos` after the
+    /// block there changed. Only those wires are visited, so unrelated circuits
+    /// elsewhere in the world are left alone.
+    pub(crate) fn update_redstone(&mut self, pos: IVec3) {
+        let network = self.wire_network(pos);
+        if network.is_empty() {
+            return;
+        }
+
+        // Spread from the wires next to a source, strongest first, so each wire
+        // ends up with its best path
+        let mut power: HashMap<IVec3, u8> = network.iter().map(|&wire| (wire, 0)).collect();
+        let mut queue = BinaryHeap::new();
+        for &wire in &network {
+            let source = NEIGHBORS
+                .iter()
+                .filter_map(|&offset| {
+                    let neighbor = wire + offset;
+                    let block = self.block_at(neighbor)?;
+                    Some(block.emitted_power(self.block_state_at(neighbor).unwrap_or(0)))
+                })
+                .max()
+                .unwrap_or(0);
+            if source > 0 {
+                queue.push((source, wire.to_array()));
+            }
+        }
+
+        while let Some((level, wire)) = queue.pop() {
+            let wire = IVec3::from_array(wire);
+            match power.get_mut(&wire) {
+                Some(current) if *current < level => *current = level,
+                _ => continue,
+            }
+            if level <= 1 {
+                continue;
+            }
+            for offset in NEIGHBORS {
+                let neighbor = wire + offset;
+                if power.get(&neighbor).is_some_and(|&current| current < level - 1) {
+                    queue.push((level - 1, neighbor.to_array()));
+                }
+            }
+        }
+
+        for (wire, level) in power {
+            let state = self.block_state_at(wire).unwrap_or(0);
+            let powered = (state & !POWER_MASK) | level;
+            if powered != state {
+                self.set_block_with_state(wire, BlockType::RedstoneWire, powered);
+            }
+        }
+    }
+
+    /// Wires connected to `pos`, including `pos` itself if it's a wire
+    fn wire_network(&self, pos: IVec3) -> HashSet<IVec3> {
+        let is_wire = |cell: IVec3| self.block_at(cell) == Some(BlockType::RedstoneWire);
+
+        let mut network = HashSet::new();
+        let mut stack: Vec<IVec3> = std::iter::once(pos)
+            .chain(NEIGHBORS.iter().map(|&offset| pos + offset))
+            .filter(|&cell| is_wire(cell))
+            .collect();
+        while let Some(wire) = stack.pop() {
+            if network.contains(&wire) {
+                continue;
+            }
+            if network.len() >= MAX_NETWORK_SIZE {
+                warn!(
+                    "Redstone network at {:?} has over {} wires; the rest keep their old power",
+                    pos, MAX_NETWORK_SIZE
+                );
+                break;
+            }
+            network.insert(wire);
+            for offset in NEIGHBORS {
+                let neighbor = wire + offset;
+                if !network.contains(&neighbor) && is_wire(neighbor) {
+                    stack.push(neighbor);
+                }
+            }
+        }
+        network
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::testing::flat_world;
+
+    #[test]
+    fn lever_powers_a_wire_run_with_decreasing_signal_until_switched_off() {
+        let mut world = flat_world(10);
+        let lever = IVec3::new(0, 11, 0);
+        let wires: Vec<IVec3> = (1..=5).map(|x| IVec3::new(x, 11, 0)).collect();
+        world.set_block(lever, BlockType::Lever);
+        for &wire in &wires {
+            world.set_block(wire, BlockType::RedstoneWire);
+        }
+
+        world.use_block(lever);
+
+        let powers: Vec<u8> = wires.iter().map(|&wire| world.redstone_power_at(wire)).collect();
+        assert_eq!(powers, [15, 14, 13, 12, 11]);
+
+        world.use_block(lever);
+
+        assert!(wires.iter().all(|&wire| world.redstone_power_at(wire) == 0));
+    }
+}
@@ -0,0 +1,54 @@
+use glam::Vec3;
+
+use crate::world::{BlockType, DoorFacing};
+
+/// Slab state bit set for a slab filling the upper half of its block
+pub const SLAB_TOP_BIT: u8 = 1;
+
+impl BlockType {
+    /// Boxes, in block-local coordinates from 0 to 1, that entities collide with.
+    /// Empty for blocks that can be walked through.
+    pub fn collision_boxes(&self, state: u8) -> Vec<(Vec3, Vec3)> {
+        if !self.is_solid_with_state(state) {
+            return Vec::new();
+        }
+        match self {
+            BlockType::Slab => vec![slab_bounds(state & SLAB_TOP_BIT != 0)],
+            // A bottom slab with a half-height step on the side the stairs climb towards
+            BlockType::Stairs => {
+                let step = match stairs_facing(state) {
+                    DoorFacing::North => (Vec3::new(0.0, 0.5, 0.5), Vec3::ONE),
+                    DoorFacing::South => (Vec3::new(0.0, 0.5, 0.0), Vec3::new(1.0, 1.0, 0.5)),
+                    DoorFacing::East => (Vec3::new(0.5, 0.5, 0.0), Vec3::ONE),
+                    DoorFacing::West => (Vec3::new(0.0, 0.5, 0.0), Vec3::new(0.5, 1.0, 1.0)),
+                };
+                vec![slab_bounds(false), step]
+            }
+            _ => vec![(Vec3::ZERO, Vec3::ONE)],
+        }
+    }
+}
+
+/// Direction a staircase climbs towards, stored in bits 0-1 of its state
+pub fn stairs_facing(state: u8) -> DoorFacing {
+    DoorFacing::from_bits(state)
+}
+
+fn slab_bounds(top: bool) -> (Vec3, Vec3) {
+    if top {
+        (Vec3::new(0.0, 0.5, 0.0), Vec3::ONE)
+    } else {
+        (Vec3::ZERO, Vec3::new(1.0, 0.5, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bottom_slab_collides_with_its_lower_half() {
+        assert_eq!(BlockType::Slab.collision_boxes(0), vec![(Vec3::ZERO, Vec3::new(1.0, 0.5, 1.0))]);
+        assert_eq!(BlockType::Slab.collision_boxes(SLAB_TOP_BIT), vec![(Vec3::new(0.0, 0.5, 0.0), Vec3::ONE)]);
+    }
+}
@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+
+/// How much of the daylight rain at full intensity blocks
+const RAIN_DARKENING: f32 = 0.4;
+
+/// Current weather. Intensity only matters while it's raining.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Weather {
+    pub raining: bool,
+    /// Strength of the rain (or snow, in cold biomes), 0-1
+    pub intensity: f32,
+}
+
+impl Weather {
+    pub const CLEAR: Weather = Weather { raining: false, intensity: 1.0 };
+
+    /// Intensity of whatever is falling, 0 in clear weather
+    pub fn precipitation(&self) -> f32 {
+        if self.raining { self.intensity.clamp(0.0, 1.0) } else { 0.0 }
+    }
+
+    /// Multiplier applied to daylight: overcast skies are darker
+    pub fn sky_darkening(&self) -> f32 {
+        1.0 - RAIN_DARKENING * self.precipitation()
+    }
+}
+
+impl Default for Weather {
+    fn default() -> Self {
+        Self::CLEAR
+    }
+}
+
+/// Switches for world-wide behavior
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GameRules {
+    /// Advance the time of day
+    pub daylight_cycle: bool,
+    /// Spawn passive mobs
+    pub mob_spawning: bool,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self {
+            daylight_cycle: true,
+            mob_spawning: true,
+        }
+    }
+}
+
+/// World-global state that isn't tied to any chunk: time, weather and rules.
+/// Saved with the world, and small enough to send whole, or as a diff, to clients.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct WorldState {
+    /// Fraction of the day cycle elapsed, in [0, 1)
+    pub time_of_day: f32,
+    pub weather: Weather,
+    pub rules: GameRules,
+}
+
+/// One part of `WorldState` that differs between two snapshots
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WorldStateChange {
+    TimeOfDay(f32),
+    Weather(Weather),
+    Rules(GameRules),
+}
+
+impl WorldState {
+    /// The changes that turn `previous` into this state
+    pub fn diff(&self, previous: &WorldState) -> Vec<WorldStateChange> {
+        let mut changes = Vec::new();
+        if self.time_of_day != previous.time_of_day {
+            changes.push(WorldStateChange::TimeOfDay(self.time_of_day));
+        }
+        if self.weather != previous.weather {
+            changes.push(WorldStateChange::Weather(self.weather));
+        }
+        if self.rules != previous.rules {
+            changes.push(WorldStateChange::Rules(self.rules));
+        }
+        changes
+    }
+
+    /// Apply changes produced by `diff`
+    pub fn apply(&mut self, changes: &[WorldStateChange]) {
+        for change in changes {
+            match *change {
+                WorldStateChange::TimeOfDay(time) => self.time_of_day = time,
+                WorldStateChange::Weather(weather) => self.weather = weather,
+                WorldStateChange::Rules(rules) => self.rules = rules,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn world_state_survives_a_serialization_round_trip() {
+        let state = WorldState {
+            time_of_day: 0.37,
+            weather: Weather { raining: true, intensity: 0.6 },
+            rules: GameRules { daylight_cycle: false, mob_spawning: true },
+        };
+
+        let bytes = bincode::serialize(&state).unwrap();
+        let restored: WorldState = bincode::deserialize(&bytes).unwrap();
+
+        assert_eq!(restored, state);
+    }
+
+    #[test]
+    fn applying_a_diff_reproduces_the_newer_state() {
+        let before = WorldState::default();
+        let after = WorldState { time_of_day: 0.5, weather: Weather { raining: true, intensity: 1.0 }, ..before };
+
+        let changes = after.diff(&before);
+        let mut synced = before;
+        synced.apply(&changes);
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(synced, after);
+    }
+}
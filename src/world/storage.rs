@@ -0,0 +1,166 @@
+use anyhow::Result;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use crate::game::{GameMode, Player};
+use crate::world::{Chunk, ChunkCoordinate, World, WorldState};
+
+/// World-global data saved alongside the chunks (the equivalent of `level.dat`)
+#[derive(Serialize, Deserialize)]
+pub struct WorldMetadata {
+    pub seed: u64,
+    pub spawn_point: Vec3,
+    /// Time of day, weather and game rules
+    pub state: WorldState,
+    pub game_mode: GameMode,
+    pub player: Player,
+}
+
+impl WorldMetadata {
+    pub fn capture(world: &World, game_mode: GameMode, player: &Player) -> Self {
+        Self {
+            seed: world.seed(),
+            spawn_point: world.spawn_point(),
+            state: *world.state(),
+            game_mode,
+            player: player.clone(),
+        }
+    }
+}
+
+/// Directory-backed save storage: `level.dat` plus one compressed file per chunk
+pub struct WorldStorage {
+    root: PathBuf,
+}
+
+impl WorldStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn metadata_path(&self) -> PathBuf {
+        self.root.join("level.dat")
+    }
+
+    fn chunk_path(&self, coord: ChunkCoordinate) -> PathBuf {
+        self.root.join("chunks").join(format!("{}_{}.chunk", coord.x, coord.z))
+    }
+
+    pub fn save_metadata(&self, metadata: &WorldMetadata) -> Result<()> {
+        write_compressed(&self.metadata_path(), metadata)
+    }
+
+    /// Read the world metadata, or `None` for a world that was never saved
+    pub fn load_metadata(&self) -> Result<Option<WorldMetadata>> {
+        read_compressed(&self.metadata_path())
+    }
+
+    pub fn save_chunk(&self, chunk: &Chunk) -> Result<()> {
+        write_compressed(&self.chunk_path(chunk.coordinate), chunk)
+    }
+
+    /// Remove a chunk's saved data so it is generated afresh next time it loads
+    pub fn delete_chunk(&self, coord: ChunkCoordinate) -> Result<()> {
+        let path = self.chunk_path(coord);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// Read a saved chunk, or `None` if it has never been saved
+    pub fn load_chunk(&self, coord: ChunkCoordinate) -> Result<Option<Chunk>> {
+        read_compressed(&self.chunk_path(coord))
+    }
+}
+
+fn write_compressed<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Write to a temporary file and rename it over the target, so a crash mid-write
+    // leaves the previous save intact
+    let temp_path = path.with_extension("tmp");
+    let writer = BufWriter::new(File::create(&temp_path)?);
+    let mut encoder = GzEncoder::new(writer, Compression::fast());
+    bincode::serialize_into(&mut encoder, value)?;
+    encoder.finish()?.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    fs::rename(&temp_path, path)?;
+
+    // The rename is only durable once the directory entry itself reaches the disk
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => sync_dir(parent),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> Result<()> {
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+// Directories can't be opened as files here; rename is as durable as it gets
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> Result<()> {
+    Ok(())
+}
+
+fn read_compressed<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<Option<T>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let reader = BufReader::new(File::open(path)?);
+    let value = bincode::deserialize_from(GzDecoder::new(reader))?;
+    Ok(Some(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::ItemStack;
+    use crate::world::testing::TempDir;
+    use crate::world::BlockType;
+
+    #[test]
+    fn metadata_round_trips_through_storage() {
+        let dir = TempDir::new("metadata_round_trip");
+        let storage = WorldStorage::new(dir.path());
+
+        let mut world = World::with_seed(42);
+        world.set_spawn_point(Vec3::new(8.5, 70.0, -3.5));
+        world.set_time_of_day(0.75);
+        let mut player = Player::new(Vec3::new(1.5, 80.0, 2.5));
+        player.damage(5.0);
+        player.inventory_mut().add_item(ItemStack::new(BlockType::Cobblestone, 12));
+
+        storage.save_metadata(&WorldMetadata::capture(&world, GameMode::Survival, &player)).unwrap();
+        let loaded = storage.load_metadata().unwrap().expect("metadata was saved");
+
+        assert_eq!(loaded.seed, 42);
+        assert_eq!(loaded.spawn_point, world.spawn_point());
+        assert_eq!(loaded.state, *world.state());
+        assert_eq!(loaded.game_mode, GameMode::Survival);
+        assert_eq!(loaded.player.position(), player.position());
+        assert_eq!(loaded.player.health(), player.health());
+        assert_eq!(loaded.player.inventory(), player.inventory());
+    }
+
+    #[test]
+    fn unsaved_world_has_no_metadata() {
+        let dir = TempDir::new("no_metadata");
+        assert!(WorldStorage::new(dir.path()).load_metadata().unwrap().is_none());
+    }
+}
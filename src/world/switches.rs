@@ -0,0 +1,172 @@
+use std::collections::HashSet;
+use glam::IVec3;
+
+use crate::game::Aabb;
+use crate::world::{BlockType, World};
+
+/// Block state bit set while a lever, button or pressure plate is on
+pub const POWERED_BIT: u8 = 1;
+
+/// Seconds a pressed button stays on before popping back out
+pub const BUTTON_PRESS_TIME: f32 = 1.0;
+
+/// A pressed button waiting to reset
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PressedButton {
+    pub position: IVec3,
+    pub remaining: f32,
+}
+
+impl BlockType {
+    /// Blocks that do something when used (right-clicked) instead of having a
+    /// block placed against them
+    pub fn is_interactive(&self) -> bool {
+        matches!(self, BlockType::Door | BlockType::Lever | BlockType::Button)
+    }
+
+    /// Levers, buttons and pressure plates: blocks switched on and off by the player
+    pub fn is_switch(&self) -> bool {
+        matches!(self, BlockType::Lever | BlockType::Button | BlockType::PressurePlate)
+    }
+}
+
+impl World {
+    /// Use the block at `pos` as if right-clicked: doors open or close, levers flip
+    /// and buttons press. Returns false if the block doesn't react to being used.
+    pub fn use_block(&mut self, pos: IVec3) -> bool {
+        match self.block_at(pos) {
+            Some(BlockType::Door) => self.toggle_door(pos),
+            Some(BlockType::Lever) => {
+                let powered = self.is_switch_on(pos);
+                self.set_switch(pos, !powered);
+                true
+            }
+            Some(BlockType::Button) => {
+                // Pressing a button that's already down does nothing
+                if !self.is_switch_on(pos) {
+                    self.set_switch(pos, true);
+                    self.pressed_buttons.push(PressedButton { position: pos, remaining: BUTTON_PRESS_TIME });
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the lever, button or pressure plate at `pos` is on
+    pub fn is_switch_on(&self, pos: IVec3) -> bool {
+        match self.block_at(pos) {
+            Some(block) if block.is_switch() => self.block_state_at(pos).unwrap_or(0) & POWERED_BIT != 0,
+            _ => false,
+        }
+    }
+
+    fn set_switch(&mut self, pos: IVec3, on: bool) {
+        let block = match self.block_at(pos) {
+            Some(block) if block.is_switch() => block,
+            _ => return,
+        };
+        let state = self.block_state_at(pos).unwrap_or(0);
+        let state = if on { state | POWERED_BIT } else { state & !POWERED_BIT };
+        self.set_block_with_state(pos, block, state);
+    }
+
+    /// Count down pressed buttons and release those whose time is up
+    pub(crate) fn update_buttons(&mut self, delta_time: f32) {
+        for button in &mut self.pressed_buttons {
+            button.remaining -= delta_time;
+        }
+
+        let (released, held): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pressed_buttons)
+            .into_iter()
+            .partition(|button| button.remaining <= 0.0);
+        self.pressed_buttons = held;
+
+        for button in released {
+            self.set_switch(button.position, false);
+        }
+    }
+
+    /// Turn on every pressure plate an entity or one of `bodies` (e.g. the player)
+    /// is standing on, and turn off the ones nothing stands on any more
+    pub fn update_pressure_plates(&mut self, bodies: &[Aabb]) {
+        let boxes: Vec<Aabb> = bodies
+            .iter()
+            .copied()
+            .chain(self.entities.iter().map(|entity| entity.aabb()))
+            .collect();
+
+        let mut pressed = HashSet::new();
+        for aabb in &boxes {
+            // Plates are thin, so only the cells at the bottom of the box can touch one
+            let min = aabb.min.floor().as_ivec3();
+            let max = aabb.max.floor().as_ivec3();
+            for (pos, block) in self.blocks_in_aabb(min, IVec3::new(max.x, min.y, max.z)) {
+                if block == BlockType::PressurePlate {
+                    pressed.insert(pos);
+                }
+            }
+        }
+
+        let released: Vec<IVec3> = self.active_plates.difference(&pressed).copied().collect();
+        for pos in released {
+            self.set_switch(pos, false);
+        }
+        for &pos in &pressed {
+            if !self.is_switch_on(pos) {
+                self.set_switch(pos, true);
+            }
+        }
+        self.active_plates = pressed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec3;
+    use crate::world::testing::flat_world;
+
+    #[test]
+    fn button_turns_on_when_used_and_resets_after_its_delay() {
+        let mut world = flat_world(10);
+        let pos = IVec3::new(2, 11, 2);
+        world.set_block(pos, BlockType::Button);
+
+        assert!(world.use_block(pos));
+        assert!(world.is_switch_on(pos));
+
+        world.update_buttons(BUTTON_PRESS_TIME * 0.5);
+        assert!(world.is_switch_on(pos));
+        world.update_buttons(BUTTON_PRESS_TIME * 0.6);
+        assert!(!world.is_switch_on(pos));
+    }
+
+    #[test]
+    fn lever_latches_until_used_again() {
+        let mut world = flat_world(10);
+        let pos = IVec3::new(2, 11, 2);
+        world.set_block(pos, BlockType::Lever);
+
+        world.use_block(pos);
+        world.update_buttons(BUTTON_PRESS_TIME * 2.0);
+        assert!(world.is_switch_on(pos));
+
+        world.use_block(pos);
+        assert!(!world.is_switch_on(pos));
+    }
+
+    #[test]
+    fn pressure_plate_is_on_only_while_the_player_stands_on_it() {
+        let mut world = flat_world(10);
+        let pos = IVec3::new(2, 11, 2);
+        world.set_block(pos, BlockType::PressurePlate);
+        let player = |x: f32| Aabb::from_feet(Vec3::new(x, 11.0, 2.5), 0.6, 1.8);
+
+        world.update_pressure_plates(&[player(2.5)]);
+        assert!(world.is_switch_on(pos));
+
+        world.update_pressure_plates(&[player(6.5)]);
+        assert!(!world.is_switch_on(pos));
+    }
+}
@@ -0,0 +1,66 @@
+// Small, predictable worlds for unit tests
+
+use std::path::{Path, PathBuf};
+
+use crate::world::{BlockType, Chunk, ChunkCoordinate, ChunkGenerator, World, CHUNK_SIZE};
+
+/// Generates flat terrain: stone up to `ground`, topped with `surface` at `ground`,
+/// air above
+pub struct FlatGenerator {
+    pub ground: usize,
+    pub surface: BlockType,
+}
+
+impl ChunkGenerator for FlatGenerator {
+    fn generate_chunk(&self, coord: ChunkCoordinate) -> Chunk {
+        let mut chunk = Chunk::new(coord);
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                for y in 0..self.ground {
+                    chunk.set_block(x, y, z, BlockType::Stone);
+                }
+                chunk.set_block(x, self.ground, z, self.surface);
+            }
+        }
+        chunk.calculate_lighting();
+        chunk
+    }
+}
+
+/// A flat stone world with the 3x3 chunks around the origin loaded and the top of
+/// the ground at `ground`
+pub fn flat_world(ground: usize) -> World {
+    flat_world_with_surface(ground, BlockType::Stone)
+}
+
+/// Like `flat_world`, with `surface` as the top layer of the ground
+pub fn flat_world_with_surface(ground: usize, surface: BlockType) -> World {
+    let mut world = World::with_generator(1, Box::new(FlatGenerator { ground, surface }));
+    for x in -1..=1 {
+        for z in -1..=1 {
+            world.load_chunk_now(ChunkCoordinate::new(x, z));
+        }
+    }
+    world
+}
+
+/// A fresh directory under the system temp dir, deleted again when dropped
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("minecraft_clone_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}